@@ -0,0 +1,341 @@
+//! Types for representing a PE/COFF image, such as a Windows DLL.
+//! Reference pulled from [here](https://docs.microsoft.com/en-us/windows/win32/debug/pe-format)
+
+mod api_set;
+mod archive;
+mod authenticode;
+mod checksum;
+mod clr;
+mod coff_header;
+mod debug;
+mod dependencies;
+mod digest;
+mod dos_header;
+mod export;
+mod import;
+mod import_library;
+mod load_config;
+mod metadata;
+mod module_definition;
+mod object;
+mod optional_header;
+mod relocation;
+mod resource;
+mod rich_header;
+mod section_table;
+mod tls;
+
+pub use self::api_set::ApiSetEntry;
+pub use self::api_set::ApiSetHost;
+pub use self::api_set::ApiSetSchema;
+pub use self::archive::Archive;
+pub use self::archive::ArchiveMember;
+pub use self::archive::ArchiveSymbol;
+pub use self::authenticode::Certificate;
+pub use self::authenticode::DigestAlgorithm;
+pub use self::authenticode::SignedDigest;
+pub use self::authenticode::WIN_CERT_TYPE_PKCS_SIGNED_DATA;
+pub use self::authenticode::WIN_CERT_TYPE_X509;
+pub use self::checksum::ImageChecksum;
+pub use self::clr::ClrHeader;
+pub use self::clr::COMIMAGE_FLAGS_32BITPREFERRED;
+pub use self::clr::COMIMAGE_FLAGS_32BITREQUIRED;
+pub use self::clr::COMIMAGE_FLAGS_ILONLY;
+pub use self::clr::COMIMAGE_FLAGS_IL_LIBRARY;
+pub use self::clr::COMIMAGE_FLAGS_NATIVE_ENTRYPOINT;
+pub use self::clr::COMIMAGE_FLAGS_STRONGNAMESIGNED;
+pub use self::clr::COMIMAGE_FLAGS_TRACKDEBUGDATA;
+pub use self::coff_header::CoffHeader;
+pub use self::coff_header::Machine;
+pub use self::coff_header::IMAGE_FILE_32BIT_MACHINE;
+pub use self::coff_header::IMAGE_FILE_DEBUG_STRIPPED;
+pub use self::coff_header::IMAGE_FILE_DLL;
+pub use self::coff_header::IMAGE_FILE_EXECUTABLE_IMAGE;
+pub use self::coff_header::IMAGE_FILE_LARGE_ADDRESS_AWARE;
+pub use self::coff_header::IMAGE_FILE_RELOCS_STRIPPED;
+pub use self::coff_header::IMAGE_FILE_SYSTEM;
+pub use self::debug::CodeView;
+pub use self::debug::DebugData;
+pub use self::debug::DebugEntry;
+pub use self::debug::DebugType;
+pub use self::debug::Guid;
+pub use self::debug::PogoEntry;
+pub use self::debug::VcFeature;
+pub use self::dependencies::Dependency;
+pub use self::dependencies::DependencyResolver;
+pub use self::dos_header::DosHeader;
+pub use self::export::Export;
+pub use self::export::ExportDirectory;
+pub use self::export::Exports;
+pub use self::import::Import;
+pub use self::import::ImportedDll;
+pub use self::load_config::GuardFunction;
+pub use self::load_config::GuardFunctions;
+pub use self::load_config::LoadConfig;
+pub use self::load_config::IMAGE_GUARD_CFW_INSTRUMENTED;
+pub use self::load_config::IMAGE_GUARD_CF_ENABLE_EXPORT_SUPPRESSION;
+pub use self::load_config::IMAGE_GUARD_CF_EXPORT_SUPPRESSION_INFO_PRESENT;
+pub use self::load_config::IMAGE_GUARD_CF_FUNCTION_TABLE_PRESENT;
+pub use self::load_config::IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK;
+pub use self::load_config::IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT;
+pub use self::load_config::IMAGE_GUARD_CF_INSTRUMENTED;
+pub use self::load_config::IMAGE_GUARD_CF_LONGJUMP_TABLE_PRESENT;
+pub use self::load_config::IMAGE_GUARD_DELAYLOAD_IAT_IN_ITS_OWN_SECTION;
+pub use self::load_config::IMAGE_GUARD_EH_CONTINUATION_TABLE_PRESENT;
+pub use self::load_config::IMAGE_GUARD_FLAG_EXPORT_SUPPRESSED;
+pub use self::load_config::IMAGE_GUARD_FLAG_FID_LANGEXCPTHANDLER;
+pub use self::load_config::IMAGE_GUARD_FLAG_FID_SUPPRESSED;
+pub use self::load_config::IMAGE_GUARD_FLAG_FID_XFG;
+pub use self::load_config::IMAGE_GUARD_PROTECT_DELAYLOAD_IAT;
+pub use self::load_config::IMAGE_GUARD_RETPOLINE_PRESENT;
+pub use self::load_config::IMAGE_GUARD_RF_ENABLE;
+pub use self::load_config::IMAGE_GUARD_RF_INSTRUMENTED;
+pub use self::load_config::IMAGE_GUARD_RF_STRICT;
+pub use self::load_config::IMAGE_GUARD_SECURITY_COOKIE_UNUSED;
+pub use self::load_config::IMAGE_GUARD_XFG_ENABLED;
+pub use self::metadata::AssemblyName;
+pub use self::metadata::Metadata;
+pub use self::metadata::MetadataStream;
+pub use self::metadata::TypeDefinition;
+pub use self::metadata::AFA_PUBLIC_KEY;
+pub use self::metadata::AFA_RETARGETABLE;
+pub use self::metadata::TD_ABSTRACT;
+pub use self::metadata::TD_INTERFACE;
+pub use self::metadata::TD_NESTED_PUBLIC;
+pub use self::metadata::TD_NOT_PUBLIC;
+pub use self::metadata::TD_PUBLIC;
+pub use self::metadata::TD_SEALED;
+pub use self::metadata::TD_VISIBILITY_MASK;
+pub use self::module_definition::DefinitionExport;
+pub use self::module_definition::ModuleDefinition;
+pub use self::object::ImportObject;
+pub use self::object::Object;
+pub use self::object::ObjectFile;
+pub use self::object::Symbol;
+pub use self::object::IMAGE_SYM_ABSOLUTE;
+pub use self::object::IMAGE_SYM_CLASS_EXTERNAL;
+pub use self::object::IMAGE_SYM_CLASS_SECTION;
+pub use self::object::IMAGE_SYM_CLASS_STATIC;
+pub use self::object::IMAGE_SYM_CLASS_WEAK_EXTERNAL;
+pub use self::object::IMAGE_SYM_DEBUG;
+pub use self::object::IMAGE_SYM_UNDEFINED;
+pub use self::object::IMPORT_OBJECT_CODE;
+pub use self::object::IMPORT_OBJECT_CONST;
+pub use self::object::IMPORT_OBJECT_DATA;
+pub use self::object::IMPORT_OBJECT_NAME;
+pub use self::object::IMPORT_OBJECT_NAME_EXPORTAS;
+pub use self::object::IMPORT_OBJECT_NAME_NO_PREFIX;
+pub use self::object::IMPORT_OBJECT_NAME_UNDECORATE;
+pub use self::object::IMPORT_OBJECT_ORDINAL;
+pub use self::optional_header::DataDirectory;
+pub use self::optional_header::DataDirectoryType;
+pub use self::optional_header::OptionalHeader;
+pub use self::optional_header::PeFormat;
+pub use self::optional_header::Subsystem;
+pub use self::optional_header::IMAGE_DLLCHARACTERISTICS_APPCONTAINER;
+pub use self::optional_header::IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE;
+pub use self::optional_header::IMAGE_DLLCHARACTERISTICS_FORCE_INTEGRITY;
+pub use self::optional_header::IMAGE_DLLCHARACTERISTICS_GUARD_CF;
+pub use self::optional_header::IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA;
+pub use self::optional_header::IMAGE_DLLCHARACTERISTICS_NO_SEH;
+pub use self::optional_header::IMAGE_DLLCHARACTERISTICS_NX_COMPAT;
+pub use self::relocation::Relocation;
+pub use self::relocation::RelocationBlock;
+pub use self::relocation::RelocationType;
+pub use self::relocation::Relocations;
+pub use self::resource::FixedFileInfo;
+pub use self::resource::Resource;
+pub use self::resource::ResourceId;
+pub use self::resource::VersionInfo;
+pub use self::resource::VersionStrings;
+pub use self::resource::RT_ACCELERATOR;
+pub use self::resource::RT_ANICURSOR;
+pub use self::resource::RT_ANIICON;
+pub use self::resource::RT_BITMAP;
+pub use self::resource::RT_CURSOR;
+pub use self::resource::RT_DIALOG;
+pub use self::resource::RT_DLGINCLUDE;
+pub use self::resource::RT_FONT;
+pub use self::resource::RT_FONTDIR;
+pub use self::resource::RT_GROUP_CURSOR;
+pub use self::resource::RT_GROUP_ICON;
+pub use self::resource::RT_HTML;
+pub use self::resource::RT_ICON;
+pub use self::resource::RT_MANIFEST;
+pub use self::resource::RT_MENU;
+pub use self::resource::RT_MESSAGETABLE;
+pub use self::resource::RT_PLUGPLAY;
+pub use self::resource::RT_RCDATA;
+pub use self::resource::RT_STRING;
+pub use self::resource::RT_VERSION;
+pub use self::resource::RT_VXD;
+pub use self::rich_header::RichEntry;
+pub use self::rich_header::RichHeader;
+pub use self::section_table::SectionTable;
+pub use self::section_table::SectionTableEntry;
+pub use self::section_table::IMAGE_SCN_CNT_CODE;
+pub use self::section_table::IMAGE_SCN_CNT_INITIALIZED_DATA;
+pub use self::section_table::IMAGE_SCN_CNT_UNINITIALIZED_DATA;
+pub use self::section_table::IMAGE_SCN_MEM_DISCARDABLE;
+pub use self::section_table::IMAGE_SCN_MEM_EXECUTE;
+pub use self::section_table::IMAGE_SCN_MEM_READ;
+pub use self::section_table::IMAGE_SCN_MEM_SHARED;
+pub use self::section_table::IMAGE_SCN_MEM_WRITE;
+pub use self::tls::TlsCallbacks;
+pub use self::tls::TlsDirectory;
+use crate::errors::ParseError;
+use crate::parsing::check_bounds;
+use crate::parsing::read_bytes;
+use crate::parsing::read_vec;
+use crate::parsing::Parse;
+use crate::parsing::ParseSeekable;
+use std::convert::TryFrom;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+pub struct PeDescriptor {
+    pub dos_header: DosHeader,
+    pub coff_header: CoffHeader,
+    pub optional_header: OptionalHeader,
+    pub section_table: SectionTable,
+}
+
+impl Parse for PeDescriptor {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        let dos_header = DosHeader::parse(reader)?;
+
+        // Skip the DOS stub to reach the PE signature
+        let stub_size = dos_header
+            .new_header_offset
+            .checked_sub(DosHeader::SIZE)
+            .ok_or_else(|| ParseError::InvalidValue {
+                value: dos_header.new_header_offset.to_le_bytes().into(),
+            })?;
+        read_vec(reader, stub_size as usize)?;
+
+        const SIGNATURE: [u8; 4] = *b"PE\0\0";
+        let signature = read_bytes::<R, 4>(reader)?;
+        if signature != SIGNATURE {
+            return Err(ParseError::InvalidValue {
+                value: signature.into(),
+            });
+        }
+
+        let coff_header = CoffHeader::parse(reader)?;
+        let optional_header = read_vec(reader, coff_header.optional_header_size as usize)?;
+        let optional_header = OptionalHeader::parse(&mut optional_header.as_slice())?;
+        let section_table = SectionTable::parse_entries(reader, coff_header.section_count.into())?;
+
+        Ok(Self {
+            dos_header,
+            coff_header,
+            optional_header,
+            section_table,
+        })
+    }
+}
+
+impl ParseSeekable for PeDescriptor {
+    fn parse_seekable<R: Read + Seek>(reader: &mut R) -> Result<Self, ParseError> {
+        reader.seek(SeekFrom::Start(0))?;
+        Self::parse(reader)
+    }
+}
+
+impl PeDescriptor {
+    /// Converts a relative virtual address into an offset within the file,
+    /// using the section which it is loaded from or, for addresses within
+    /// the headers, the headers themselves.
+    pub fn offset_of_rva(&self, rva: u32) -> Option<u64> {
+        if rva < self.optional_header.headers_size {
+            return Some(rva.into());
+        }
+
+        self.section_table.entries().iter().find_map(|section| {
+            let offset = rva.checked_sub(section.virtual_address)?;
+            let size = section.virtual_size.max(section.raw_data_size);
+            if offset < size && offset < section.raw_data_size {
+                Some(u64::from(section.raw_data_offset) + u64::from(offset))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Converts a virtual address into one relative to the image base.
+    pub fn rva_of_va(&self, va: u64) -> Option<u32> {
+        let image_base = u64::from(self.optional_header.image_base);
+        va.checked_sub(image_base)
+            .and_then(|rva| u32::try_from(rva).ok())
+    }
+
+    /// Gets the data directory of the provided type, if the image has one.
+    pub fn data_directory(&self, directory_type: DataDirectoryType) -> Option<DataDirectory> {
+        self.optional_header.data_directory(directory_type)
+    }
+
+    /// Gets the offset of the optional header within the file, which follows
+    /// the PE signature and the COFF header.
+    pub(crate) fn optional_header_offset(&self) -> u64 {
+        u64::from(self.dos_header.new_header_offset) + 24
+    }
+
+    /// Gets the offset of the checksum field within the file.
+    pub(crate) fn checksum_offset(&self) -> u64 {
+        self.optional_header_offset() + 64
+    }
+
+    /// Moves the reader to the file offset of a relative virtual address,
+    /// ensuring that `size` bytes can be read from there.
+    pub(crate) fn seek_to_rva<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        rva: u32,
+        size: u32,
+    ) -> Result<(), ParseError> {
+        let offset = self.offset_of_rva(rva).ok_or(ParseError::OutOfBounds {
+            offset: rva.into(),
+            size: size.into(),
+        })?;
+        check_bounds(reader, offset, size.into())?;
+        reader.seek(SeekFrom::Start(offset))?;
+        Ok(())
+    }
+
+    /// Reads the null terminated string at a relative virtual address.
+    pub(crate) fn read_string<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        rva: u32,
+    ) -> Result<String, ParseError> {
+        self.seek_to_rva(reader, rva, 1)?;
+
+        let mut string = Vec::new();
+        loop {
+            let mut chunk = [0; 64];
+            let read = reader.read(&mut chunk)?;
+            if read == 0 {
+                return Err(ParseError::MissingData);
+            }
+            match chunk[..read].iter().position(|byte| *byte == 0) {
+                Some(end) => {
+                    string.extend_from_slice(&chunk[..end]);
+                    break;
+                }
+                None => string.extend_from_slice(&chunk[..read]),
+            }
+        }
+
+        Ok(String::from_utf8_lossy(&string).into_owned())
+    }
+}
+
+/// Adds `size` to a relative virtual address, failing for tables which would
+/// run past the end of the address space rather than wrapping around.
+pub(crate) fn add_rva(rva: u32, size: u32) -> Result<u32, ParseError> {
+    rva.checked_add(size).ok_or(ParseError::OutOfBounds {
+        offset: rva.into(),
+        size: size.into(),
+    })
+}
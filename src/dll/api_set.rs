@@ -0,0 +1,166 @@
+//! Reads the API set schema, which maps the virtual `api-ms-win-*` and
+//! `ext-ms-*` DLL names onto the DLLs that implement them. Windows keeps it
+//! in the `.apiset` section of `apisetschema.dll`.
+
+use crate::dll::PeDescriptor;
+use crate::errors::ParseError;
+use crate::parsing::read_vec;
+use crate::parsing::Parse;
+use crate::parsing::ParseFromEndianess;
+use crate::parsing::ParseSeekable;
+use crate::shared::Endianess;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+/// The schema layout used since Windows 10. Earlier versions are laid out
+/// differently and aren't supported.
+const SCHEMA_VERSION: u32 = 6;
+
+pub struct ApiSetSchema {
+    entries: Vec<ApiSetEntry>,
+}
+
+pub struct ApiSetEntry {
+    /// The name of the API set, without the `.dll` extension.
+    pub name: String,
+    /// The length of the part of the name which is compared, which leaves
+    /// out the minor version so that any revision of the set matches.
+    pub hashed_length: usize,
+    pub hosts: Vec<ApiSetHost>,
+}
+
+pub struct ApiSetHost {
+    /// The DLL which this host is used for instead of the default one.
+    pub importer: Option<String>,
+    pub name: String,
+}
+
+impl Parse for ApiSetSchema {
+    /// Parses the contents of the `.apiset` section.
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        // Offsets past the end of the data, including those which overflow,
+        // are all treated as missing data
+        let slice = |offset: u32, length: u32| {
+            let start = offset as usize;
+            start
+                .checked_add(length as usize)
+                .and_then(|end| data.get(start..end))
+                .ok_or(ParseError::MissingData)
+        };
+        let record = |offset: u32, index: u32, size: u32| {
+            index
+                .checked_mul(size)
+                .and_then(|start| offset.checked_add(start))
+                .ok_or(ParseError::MissingData)
+        };
+        let word = |offset: u32, field: u32| -> Result<u32, ParseError> {
+            let offset = offset.checked_add(field).ok_or(ParseError::MissingData)?;
+            let bytes = slice(offset, 4)?;
+            u32::parse_from_endianess(&mut &bytes[..], Endianess::Little)
+        };
+        let string = |offset: u32, length: u32| -> Result<String, ParseError> {
+            let bytes = slice(offset, length)?;
+            let units = bytes
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect::<Vec<_>>();
+            Ok(String::from_utf16_lossy(&units))
+        };
+
+        let version = word(0, 0)?;
+        if version != SCHEMA_VERSION {
+            return Err(ParseError::InvalidValue {
+                value: version.to_le_bytes().into(),
+            });
+        }
+
+        let count = word(0, 12)?;
+        let entries_offset = word(0, 16)?;
+        let mut entries = Vec::new();
+        for index in 0..count {
+            let entry = record(entries_offset, index, 24)?;
+            let name = string(word(entry, 4)?, word(entry, 8)?)?;
+            let hashed_length = word(entry, 12)? as usize / 2;
+            let values_offset = word(entry, 16)?;
+            let value_count = word(entry, 20)?;
+
+            let mut hosts = Vec::new();
+            for value_index in 0..value_count {
+                let value = record(values_offset, value_index, 20)?;
+                let importer = match word(value, 8)? {
+                    0 => None,
+                    length => Some(string(word(value, 4)?, length)?),
+                };
+                let name = string(word(value, 12)?, word(value, 16)?)?;
+                hosts.push(ApiSetHost { importer, name });
+            }
+
+            entries.push(ApiSetEntry {
+                name: name.to_ascii_lowercase(),
+                hashed_length,
+                hosts,
+            });
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+impl ApiSetSchema {
+    /// Reads the schema from the `.apiset` section of `apisetschema.dll`.
+    pub fn parse_dll<R: Read + Seek>(reader: &mut R) -> Result<Self, ParseError> {
+        let descriptor = PeDescriptor::parse_seekable(reader)?;
+        let section = descriptor
+            .section_table
+            .by_name(".apiset")
+            .ok_or(ParseError::MissingData)?;
+
+        reader.seek(SeekFrom::Start(section.raw_data_offset.into()))?;
+        let data = read_vec(reader, section.raw_data_size as usize)?;
+        Self::parse(&mut data.as_slice())
+    }
+
+    pub fn entries(&self) -> &[ApiSetEntry] {
+        &self.entries
+    }
+
+    /// Whether the DLL name refers to an API set rather than a file.
+    pub fn is_api_set(name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        name.starts_with("api-") || name.starts_with("ext-")
+    }
+
+    /// Finds the DLL which provides the API set `name` to `importer`. The
+    /// result is `None` for names which aren't in the schema, as well as for
+    /// API sets which have no host on this system.
+    pub fn resolve(&self, name: &str, importer: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        let name = name.strip_suffix(".dll").unwrap_or(&name);
+        let prefix = &name[..name.rfind('-').unwrap_or(name.len())];
+
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| entry.name.get(..entry.hashed_length) == Some(prefix))?;
+
+        // Hosts specific to the importing DLL take priority over the default
+        let host = entry
+            .hosts
+            .iter()
+            .find(|host| {
+                matches!(
+                    &host.importer,
+                    Some(host_importer) if host_importer.eq_ignore_ascii_case(importer)
+                )
+            })
+            .or_else(|| entry.hosts.iter().find(|host| host.importer.is_none()))?;
+        if host.name.is_empty() {
+            None
+        } else {
+            Some(&host.name)
+        }
+    }
+}
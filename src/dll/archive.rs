@@ -0,0 +1,273 @@
+//! Archives in the `ar` format used for static and import libraries
+//! (`.lib`), whose first members index the symbols the others define.
+
+use crate::dll::Object;
+use crate::errors::ParseError;
+use crate::parsing::read_bytes;
+use crate::parsing::read_vec;
+use crate::parsing::ParseFromEndianess;
+use crate::parsing::ParseSeekable;
+use crate::shared::Endianess;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+const SIGNATURE: [u8; 8] = *b"!<arch>\n";
+const HEADER_SIZE: u64 = 60;
+
+pub struct Archive {
+    /// The members holding objects, leaving out the linker and long names
+    /// members.
+    pub members: Vec<ArchiveMember>,
+    /// The symbols indexed by the linker members, in the order of the first
+    /// linker member.
+    pub symbols: Vec<ArchiveSymbol>,
+    /// The offsets of the members, as listed in the second linker member.
+    /// Only archives in the Microsoft format have one.
+    pub member_offsets: Option<Vec<u32>>,
+}
+
+pub struct ArchiveMember {
+    pub name: String,
+    pub timestamp: u64,
+    pub user_id: Option<u32>,
+    pub group_id: Option<u32>,
+    pub mode: u32,
+    /// The offset of the header of the member, which the linker members
+    /// refer to it by.
+    pub header_offset: u64,
+    pub data_offset: u64,
+    pub size: u64,
+}
+
+/// A symbol of the index, along with the member which defines it.
+pub struct ArchiveSymbol {
+    pub name: String,
+    /// The index of the member in `Archive::members`, if it could be found.
+    pub member: Option<usize>,
+}
+
+impl Archive {
+    /// Reads the data of a member.
+    pub fn member_data<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        member: &ArchiveMember,
+    ) -> Result<Vec<u8>, ParseError> {
+        reader.seek(SeekFrom::Start(member.data_offset))?;
+        read_vec(reader, member.size as usize)
+    }
+
+    /// Reads the object held by a member.
+    pub fn member_object<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        member: &ArchiveMember,
+    ) -> Result<Object, ParseError> {
+        let data = self.member_data(reader, member)?;
+        Object::parse_seekable(&mut Cursor::new(data))
+    }
+
+    /// Finds the member which defines a symbol, using the index.
+    pub fn find_symbol(&self, name: &str) -> Option<&ArchiveMember> {
+        self.symbols
+            .iter()
+            .find(|symbol| symbol.name == name)
+            .and_then(|symbol| symbol.member)
+            .map(|member| &self.members[member])
+    }
+}
+
+impl ParseSeekable for Archive {
+    fn parse_seekable<R: Read + Seek>(reader: &mut R) -> Result<Self, ParseError> {
+        reader.seek(SeekFrom::Start(0))?;
+        let signature = read_bytes::<R, 8>(reader)?;
+        if signature != SIGNATURE {
+            return Err(ParseError::InvalidValue {
+                value: signature.into(),
+            });
+        }
+
+        let end = reader.seek(SeekFrom::End(0))?;
+        let mut offset = SIGNATURE.len() as u64;
+        let mut members = Vec::new();
+        let mut first_linker = None;
+        let mut second_linker = None;
+        let mut long_names = Vec::new();
+        while offset + HEADER_SIZE <= end {
+            reader.seek(SeekFrom::Start(offset))?;
+            let member = ArchiveMember::parse(reader, offset)?;
+            if member.data_offset + member.size > end {
+                return Err(ParseError::OutOfBounds {
+                    offset: member.data_offset,
+                    size: member.size,
+                });
+            }
+
+            // Members start at even offsets
+            offset = member.data_offset + member.size + member.size % 2;
+
+            // The linker members are both named "/", in order
+            match member.name.as_str() {
+                "/" if first_linker.is_none() => {
+                    first_linker = Some(read_vec(reader, member.size as usize)?)
+                }
+                "/" => second_linker = Some(read_vec(reader, member.size as usize)?),
+                "//" => long_names = read_vec(reader, member.size as usize)?,
+                "/SYM64/" => {}
+                _ => members.push(member),
+            }
+        }
+
+        // Long names are given as their offset in the long names member
+        for member in &mut members {
+            let name = member
+                .name
+                .strip_prefix('/')
+                .and_then(|offset| offset.parse::<usize>().ok())
+                .and_then(|offset| long_names.get(offset..))
+                .map(|name| {
+                    let length = name
+                        .iter()
+                        .position(|byte| *byte == 0 || *byte == b'\n')
+                        .unwrap_or(name.len());
+                    let name = String::from_utf8_lossy(&name[..length]).into_owned();
+                    name.strip_suffix('/').map(String::from).unwrap_or(name)
+                });
+            if let Some(name) = name {
+                member.name = name;
+            }
+        }
+
+        let (symbols, member_offsets) = match (second_linker, first_linker) {
+            (Some(second_linker), _) => {
+                let (symbols, member_offsets) = parse_second_linker(&second_linker, &members)?;
+                (symbols, Some(member_offsets))
+            }
+            (None, Some(first_linker)) => (parse_first_linker(&first_linker, &members)?, None),
+            (None, None) => (Vec::new(), None),
+        };
+
+        Ok(Self {
+            members,
+            symbols,
+            member_offsets,
+        })
+    }
+}
+
+impl ArchiveMember {
+    fn parse<R: Read>(reader: &mut R, header_offset: u64) -> Result<Self, ParseError> {
+        let header = read_bytes::<R, 60>(reader)?;
+        if header[58..] != *b"`\n" {
+            return Err(ParseError::InvalidValue {
+                value: header[58..].into(),
+            });
+        }
+
+        let field = |range: std::ops::Range<usize>| {
+            String::from_utf8_lossy(&header[range])
+                .trim_end()
+                .to_string()
+        };
+        let number = |range: std::ops::Range<usize>, radix: u32| {
+            let value = field(range.clone());
+            u64::from_str_radix(&value, radix).map_err(|_| ParseError::InvalidValue {
+                value: header[range].into(),
+            })
+        };
+
+        // Names which aren't special end with a slash in both formats
+        let name = field(0..16);
+        let name = match name.as_str() {
+            "/" | "//" => name,
+            _ if name.starts_with('/') => name,
+            _ => name.strip_suffix('/').map(String::from).unwrap_or(name),
+        };
+
+        Ok(Self {
+            name,
+            timestamp: number(16..28, 10).unwrap_or(0),
+            user_id: number(28..34, 10).ok().map(|value| value as u32),
+            group_id: number(34..40, 10).ok().map(|value| value as u32),
+            mode: number(40..48, 8).unwrap_or(0) as u32,
+            header_offset,
+            data_offset: header_offset + HEADER_SIZE,
+            size: number(48..58, 10)?,
+        })
+    }
+}
+
+/// Reads the first linker member, which lists each symbol along with the
+/// big endian offset of the member defining it.
+fn parse_first_linker(
+    data: &[u8],
+    members: &[ArchiveMember],
+) -> Result<Vec<ArchiveSymbol>, ParseError> {
+    let mut reader = data;
+    let count = u32::parse_from_endianess(&mut reader, Endianess::Big)?;
+    let offsets = (0..count)
+        .map(|_| u32::parse_from_endianess(&mut reader, Endianess::Big))
+        .collect::<Result<Vec<_>, _>>()?;
+    let names = parse_names(reader, offsets.len())?;
+
+    Ok(names
+        .into_iter()
+        .zip(offsets)
+        .map(|(name, offset)| ArchiveSymbol {
+            name,
+            member: member_at(members, offset),
+        })
+        .collect())
+}
+
+/// Reads the second linker member, which lists the offsets of the members
+/// and then the sorted symbols, by the one based index of their offset.
+fn parse_second_linker(
+    data: &[u8],
+    members: &[ArchiveMember],
+) -> Result<(Vec<ArchiveSymbol>, Vec<u32>), ParseError> {
+    let mut reader = data;
+    let member_count = u32::parse_from_endianess(&mut reader, Endianess::Little)?;
+    let member_offsets = (0..member_count)
+        .map(|_| u32::parse_from_endianess(&mut reader, Endianess::Little))
+        .collect::<Result<Vec<_>, _>>()?;
+    let symbol_count = u32::parse_from_endianess(&mut reader, Endianess::Little)?;
+    let indices = (0..symbol_count)
+        .map(|_| u16::parse_from_endianess(&mut reader, Endianess::Little))
+        .collect::<Result<Vec<_>, _>>()?;
+    let names = parse_names(reader, indices.len())?;
+
+    let symbols = names
+        .into_iter()
+        .zip(indices)
+        .map(|(name, index)| ArchiveSymbol {
+            name,
+            member: usize::from(index)
+                .checked_sub(1)
+                .and_then(|index| member_offsets.get(index))
+                .and_then(|offset| member_at(members, *offset)),
+        })
+        .collect();
+    Ok((symbols, member_offsets))
+}
+
+fn parse_names(data: &[u8], count: usize) -> Result<Vec<String>, ParseError> {
+    let names = data
+        .split(|byte| *byte == 0)
+        .take(count)
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect::<Vec<_>>();
+    if names.len() < count {
+        return Err(ParseError::MissingData);
+    }
+
+    Ok(names)
+}
+
+fn member_at(members: &[ArchiveMember], offset: u32) -> Option<usize> {
+    members
+        .binary_search_by_key(&u64::from(offset), |member| member.header_offset)
+        .ok()
+}
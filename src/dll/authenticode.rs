@@ -0,0 +1,317 @@
+//! Reads the Authenticode signatures in the certificate table, and computes
+//! the digest of the image which they sign. The signatures themselves aren't
+//! verified, only that they were made over the image as it is.
+
+use crate::dll::digest::Hasher;
+use crate::dll::digest::Sha1;
+use crate::dll::digest::Sha256;
+use crate::dll::DataDirectoryType;
+use crate::dll::PeDescriptor;
+use crate::dll::PeFormat;
+use crate::errors::ParseError;
+use crate::parsing::check_bounds;
+use crate::parsing::read_vec;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+pub const WIN_CERT_TYPE_X509: u16 = 0x0001;
+pub const WIN_CERT_TYPE_PKCS_SIGNED_DATA: u16 = 0x0002;
+
+/// The DER encoded object identifiers of the parts of a signature which are
+/// looked at.
+const OID_SIGNED_DATA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02];
+const OID_SPC_INDIRECT_DATA: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0x82, 0x37, 0x02, 0x01, 0x04];
+const OID_SHA1: &[u8] = &[0x2b, 0x0e, 0x03, 0x02, 0x1a];
+const OID_SHA256: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+
+/// An entry of the certificate table (`WIN_CERTIFICATE`).
+pub struct Certificate {
+    pub revision: u16,
+    pub certificate_type: u16,
+    /// The certificate itself, which for Authenticode signatures is a
+    /// PKCS#7 `SignedData` structure.
+    pub data: Box<[u8]>,
+}
+
+impl Certificate {
+    /// Reads the digest of the image which the signature was made over, for
+    /// PKCS#7 signatures.
+    pub fn signed_digest(&self) -> Result<Option<SignedDigest>, ParseError> {
+        if self.certificate_type != WIN_CERT_TYPE_PKCS_SIGNED_DATA {
+            return Ok(None);
+        }
+
+        // ContentInfo { signedData, [0] SignedData { version, digestAlgorithms,
+        // ContentInfo { spcIndirectData, [0] SpcIndirectDataContent } } }
+        let mut content_info = Der::new(&self.data).sequence()?;
+        if content_info.object_identifier()? != OID_SIGNED_DATA {
+            return Ok(None);
+        }
+        let mut signed_data = content_info.explicit(0)?.sequence()?;
+        signed_data.skip()?;
+        signed_data.skip()?;
+        let mut content_info = signed_data.sequence()?;
+        if content_info.object_identifier()? != OID_SPC_INDIRECT_DATA {
+            return Ok(None);
+        }
+
+        // SpcIndirectDataContent { data, DigestInfo { AlgorithmIdentifier, digest } }
+        let mut indirect_data = content_info.explicit(0)?.sequence()?;
+        indirect_data.skip()?;
+        let mut digest_info = indirect_data.sequence()?;
+        let algorithm = digest_info.sequence()?.object_identifier()?;
+        let digest = digest_info.value(0x04)?;
+
+        Ok(Some(SignedDigest {
+            algorithm: DigestAlgorithm::from(algorithm),
+            digest: digest.into(),
+        }))
+    }
+}
+
+/// The digest of the image held in a signature.
+pub struct SignedDigest {
+    pub algorithm: DigestAlgorithm,
+    pub digest: Box<[u8]>,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum DigestAlgorithm {
+    Sha1,
+    Sha256,
+    /// An algorithm which the digest can't be computed with, by its DER
+    /// encoded object identifier.
+    Other(Box<[u8]>),
+}
+
+impl From<&[u8]> for DigestAlgorithm {
+    fn from(identifier: &[u8]) -> Self {
+        match identifier {
+            OID_SHA1 => Self::Sha1,
+            OID_SHA256 => Self::Sha256,
+            other => Self::Other(other.into()),
+        }
+    }
+}
+
+impl PeDescriptor {
+    /// Reads the entries of the certificate table.
+    pub fn certificates<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Vec<Certificate>, ParseError> {
+        let (start, end) = match self.certificate_table() {
+            Some(table) => table,
+            None => return Ok(Vec::new()),
+        };
+        check_bounds(reader, start, end - start)?;
+
+        // Each entry starts on an eight byte boundary
+        let mut certificates = Vec::new();
+        let mut offset = start;
+        while offset + 8 <= end {
+            reader.seek(SeekFrom::Start(offset))?;
+            let length = u32::parse_from_endianess(reader, Endianess::Little)?;
+            let revision = u16::parse_from_endianess(reader, Endianess::Little)?;
+            let certificate_type = u16::parse_from_endianess(reader, Endianess::Little)?;
+            if length < 8 || offset + u64::from(length) > end {
+                return Err(ParseError::InvalidValue {
+                    value: length.to_le_bytes().into(),
+                });
+            }
+
+            let data = read_vec(reader, length as usize - 8)?;
+            certificates.push(Certificate {
+                revision,
+                certificate_type,
+                data: data.into(),
+            });
+            offset += (u64::from(length) + 7) & !7;
+        }
+
+        Ok(certificates)
+    }
+
+    /// Computes the Authenticode digest of the image, which covers the whole
+    /// file apart from the checksum and the certificate table. The result is
+    /// `None` for algorithms which aren't supported.
+    pub fn authenticode_digest<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        algorithm: &DigestAlgorithm,
+    ) -> Result<Option<Box<[u8]>>, ParseError> {
+        let mut hasher = match algorithm {
+            DigestAlgorithm::Sha1 => Hasher::Sha1(Sha1::new()),
+            DigestAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            DigestAlgorithm::Other(_) => return Ok(None),
+        };
+
+        // The headers, skipping the checksum and the certificate table entry
+        let checksum = self.checksum_offset();
+        let certificate_entry = self.optional_header_offset()
+            + match self.optional_header.format {
+                PeFormat::Pe32 => 96,
+                PeFormat::Pe32Plus => 112,
+            }
+            + DataDirectoryType::Certificate as u64 * 8;
+        let headers_end = u64::from(self.optional_header.headers_size);
+        hash_range(reader, &mut hasher, 0, checksum)?;
+        hash_range(reader, &mut hasher, checksum + 4, certificate_entry)?;
+        hash_range(reader, &mut hasher, certificate_entry + 8, headers_end)?;
+
+        // The sections, in the order they appear in the file
+        let mut sections = self
+            .section_table
+            .entries()
+            .iter()
+            .filter(|section| section.raw_data_size != 0)
+            .map(|section| {
+                let start = u64::from(section.raw_data_offset);
+                (start, start + u64::from(section.raw_data_size))
+            })
+            .collect::<Vec<_>>();
+        sections.sort_unstable();
+        let mut hashed_end = headers_end;
+        for (start, end) in sections {
+            hash_range(reader, &mut hasher, start, end)?;
+            hashed_end = hashed_end.max(end);
+        }
+
+        // Anything after the sections, up to the certificate table
+        let file_size = reader.seek(SeekFrom::End(0))?;
+        let extra_end = match self.certificate_table() {
+            Some((start, _)) if start >= hashed_end => start,
+            _ => file_size,
+        };
+        hash_range(reader, &mut hasher, hashed_end, extra_end)?;
+
+        Ok(Some(hasher.finish()))
+    }
+
+    /// Checks whether the first signature was made over the image as it is.
+    /// The result is `None` when the image isn't signed, or is signed with an
+    /// unsupported algorithm.
+    pub fn check_authenticode_digest<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Option<bool>, ParseError> {
+        let signed_digest = match self.certificates(reader)?.first() {
+            Some(certificate) => certificate.signed_digest()?,
+            None => None,
+        };
+        let signed_digest = match signed_digest {
+            Some(signed_digest) => signed_digest,
+            None => return Ok(None),
+        };
+
+        let digest = self.authenticode_digest(reader, &signed_digest.algorithm)?;
+        Ok(digest.map(|digest| digest == signed_digest.digest))
+    }
+
+    /// Gets the start and end of the certificate table, whose address is an
+    /// offset within the file rather than a relative virtual address.
+    fn certificate_table(&self) -> Option<(u64, u64)> {
+        self.data_directory(DataDirectoryType::Certificate)
+            .map(|directory| {
+                let start = u64::from(directory.virtual_address);
+                (start, start + u64::from(directory.size))
+            })
+    }
+}
+
+fn hash_range<R: Read + Seek>(
+    reader: &mut R,
+    hasher: &mut Hasher,
+    start: u64,
+    end: u64,
+) -> Result<(), ParseError> {
+    if end <= start {
+        return Ok(());
+    }
+
+    check_bounds(reader, start, end - start)?;
+    reader.seek(SeekFrom::Start(start))?;
+    let mut remaining = end - start;
+    let mut buffer = [0; 8192];
+    while remaining > 0 {
+        let chunk = remaining.min(buffer.len() as u64) as usize;
+        reader.read_exact(&mut buffer[..chunk])?;
+        hasher.update(&buffer[..chunk]);
+        remaining -= chunk as u64;
+    }
+
+    Ok(())
+}
+
+/// Walks the DER encoded values of a signature, which are each made of a
+/// tag, a length and the contents.
+struct Der<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Der<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Reads the next value, returning its tag and contents.
+    fn next(&mut self) -> Result<(u8, &'a [u8]), ParseError> {
+        let (&tag, rest) = self.data.split_first().ok_or(ParseError::MissingData)?;
+        let (&length, mut rest) = rest.split_first().ok_or(ParseError::MissingData)?;
+        let length = if length & 0x80 == 0 {
+            usize::from(length)
+        } else {
+            // Long lengths give the number of bytes the length is stored in
+            let size = usize::from(length & 0x7f);
+            if size > 4 || rest.len() < size {
+                return Err(ParseError::InvalidValue {
+                    value: Box::new([length]),
+                });
+            }
+            let (bytes, tail) = rest.split_at(size);
+            rest = tail;
+            bytes
+                .iter()
+                .fold(0, |length, byte| (length << 8) | usize::from(*byte))
+        };
+
+        if rest.len() < length {
+            return Err(ParseError::MissingData);
+        }
+        let (contents, rest) = rest.split_at(length);
+        self.data = rest;
+        Ok((tag, contents))
+    }
+
+    /// Reads the contents of the next value, which must have the provided tag.
+    fn value(&mut self, expected: u8) -> Result<&'a [u8], ParseError> {
+        let (tag, contents) = self.next()?;
+        if tag == expected {
+            Ok(contents)
+        } else {
+            Err(ParseError::InvalidValue {
+                value: Box::new([tag]),
+            })
+        }
+    }
+
+    fn skip(&mut self) -> Result<(), ParseError> {
+        self.next().map(|_| ())
+    }
+
+    fn sequence(&mut self) -> Result<Der<'a>, ParseError> {
+        self.value(0x30).map(Der::new)
+    }
+
+    fn object_identifier(&mut self) -> Result<&'a [u8], ParseError> {
+        self.value(0x06)
+    }
+
+    /// Reads an explicitly tagged, context specific value such as `[0]`.
+    fn explicit(&mut self, number: u8) -> Result<Der<'a>, ParseError> {
+        self.value(0xa0 | number).map(Der::new)
+    }
+}
@@ -0,0 +1,64 @@
+use crate::dll::PeDescriptor;
+use crate::errors::ParseError;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+/// The checksum stored in the optional header, along with the one computed
+/// from the image.
+#[derive(Clone, Copy, PartialEq)]
+pub struct ImageChecksum {
+    pub stored: u32,
+    pub computed: u32,
+}
+
+impl ImageChecksum {
+    /// Checks whether the stored checksum is correct. Images which aren't
+    /// loaded into the kernel or a critical process often leave it as zero.
+    pub fn matches(&self) -> bool {
+        self.stored == self.computed
+    }
+}
+
+impl PeDescriptor {
+    /// Computes the checksum of the image in the same way as
+    /// `CheckSumMappedFile`, which is the sum of the file as 16-bit words
+    /// with the carries folded back in, skipping the checksum itself, plus
+    /// the length of the file.
+    pub fn image_checksum<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<ImageChecksum, ParseError> {
+        let checksum_offset = self.checksum_offset();
+        let length = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(0))?;
+
+        let mut sum = 0u32;
+        let mut offset = 0;
+        let mut buffer = [0; 8192];
+        while offset < length {
+            let chunk = (length - offset).min(buffer.len() as u64) as usize;
+            reader.read_exact(&mut buffer[..chunk])?;
+
+            // The chunks are an even size, so only the last can end with
+            // half of a word, which is padded with zero
+            for (index, word) in buffer[..chunk].chunks(2).enumerate() {
+                let word_offset = offset + index as u64 * 2;
+                if word_offset.wrapping_sub(checksum_offset) < 4 {
+                    continue;
+                }
+
+                let word = u16::from_le_bytes([word[0], word.get(1).copied().unwrap_or(0)]);
+                sum += u32::from(word);
+                sum = (sum & 0xffff) + (sum >> 16);
+            }
+            offset += chunk as u64;
+        }
+
+        let computed = ((sum & 0xffff) + (sum >> 16)).wrapping_add(length as u32);
+        Ok(ImageChecksum {
+            stored: self.optional_header.checksum,
+            computed,
+        })
+    }
+}
@@ -0,0 +1,97 @@
+//! The CLI header of managed (.NET) images, which locates the metadata
+//! describing the assembly in place of native imports and exports.
+
+use crate::dll::DataDirectory;
+use crate::dll::DataDirectoryType;
+use crate::dll::Metadata;
+use crate::dll::PeDescriptor;
+use crate::errors::ParseError;
+use crate::parsing::read_vec;
+use crate::parsing::Parse;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::io::Read;
+use std::io::Seek;
+
+pub const COMIMAGE_FLAGS_ILONLY: u32 = 0x0000_0001;
+pub const COMIMAGE_FLAGS_32BITREQUIRED: u32 = 0x0000_0002;
+pub const COMIMAGE_FLAGS_IL_LIBRARY: u32 = 0x0000_0004;
+pub const COMIMAGE_FLAGS_STRONGNAMESIGNED: u32 = 0x0000_0008;
+pub const COMIMAGE_FLAGS_NATIVE_ENTRYPOINT: u32 = 0x0000_0010;
+pub const COMIMAGE_FLAGS_TRACKDEBUGDATA: u32 = 0x0001_0000;
+pub const COMIMAGE_FLAGS_32BITPREFERRED: u32 = 0x0002_0000;
+
+/// The CLI header (`IMAGE_COR20_HEADER`).
+pub struct ClrHeader {
+    pub size: u32,
+    pub major_runtime_version: u16,
+    pub minor_runtime_version: u16,
+    pub metadata: DataDirectory,
+    pub flags: u32,
+    /// The metadata token of the entry point method or, for images with a
+    /// native entry point, its relative virtual address.
+    pub entry_point: u32,
+    pub resources: DataDirectory,
+    pub strong_name_signature: DataDirectory,
+    pub code_manager_table: DataDirectory,
+    pub vtable_fixups: DataDirectory,
+    pub export_address_table_jumps: DataDirectory,
+    pub managed_native_header: DataDirectory,
+}
+
+impl ClrHeader {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        Ok(Self {
+            size: u32::parse_from_endianess(reader, Endianess::Little)?,
+            major_runtime_version: u16::parse_from_endianess(reader, Endianess::Little)?,
+            minor_runtime_version: u16::parse_from_endianess(reader, Endianess::Little)?,
+            metadata: DataDirectory::parse(reader)?,
+            flags: u32::parse_from_endianess(reader, Endianess::Little)?,
+            entry_point: u32::parse_from_endianess(reader, Endianess::Little)?,
+            resources: DataDirectory::parse(reader)?,
+            strong_name_signature: DataDirectory::parse(reader)?,
+            code_manager_table: DataDirectory::parse(reader)?,
+            vtable_fixups: DataDirectory::parse(reader)?,
+            export_address_table_jumps: DataDirectory::parse(reader)?,
+            managed_native_header: DataDirectory::parse(reader)?,
+        })
+    }
+
+    /// Checks whether the image holds only managed code, rather than being a
+    /// mixed mode image built with C++/CLI.
+    pub fn is_il_only(&self) -> bool {
+        self.flags & COMIMAGE_FLAGS_ILONLY != 0
+    }
+}
+
+impl PeDescriptor {
+    /// Reads the CLI header, which only managed images have.
+    pub fn clr_header<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Option<ClrHeader>, ParseError> {
+        let directory = match self.data_directory(DataDirectoryType::ClrRuntime) {
+            Some(directory) => directory,
+            None => return Ok(None),
+        };
+
+        self.seek_to_rva(reader, directory.virtual_address, 72)?;
+        ClrHeader::parse(reader).map(Some)
+    }
+
+    /// Reads the metadata of a managed image.
+    pub fn clr_metadata<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Option<Metadata>, ParseError> {
+        let clr_header = match self.clr_header(reader)? {
+            Some(clr_header) => clr_header,
+            None => return Ok(None),
+        };
+
+        let metadata = clr_header.metadata;
+        self.seek_to_rva(reader, metadata.virtual_address, metadata.size)?;
+        let data = read_vec(reader, metadata.size as usize)?;
+        Metadata::parse(&data).map(Some)
+    }
+}
@@ -0,0 +1,141 @@
+use crate::errors::ParseError;
+use crate::parsing::Parse;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::io::Read;
+
+pub const IMAGE_FILE_RELOCS_STRIPPED: u16 = 0x0001;
+pub const IMAGE_FILE_EXECUTABLE_IMAGE: u16 = 0x0002;
+pub const IMAGE_FILE_LARGE_ADDRESS_AWARE: u16 = 0x0020;
+pub const IMAGE_FILE_32BIT_MACHINE: u16 = 0x0100;
+pub const IMAGE_FILE_DEBUG_STRIPPED: u16 = 0x0200;
+pub const IMAGE_FILE_SYSTEM: u16 = 0x1000;
+pub const IMAGE_FILE_DLL: u16 = 0x2000;
+
+/// The COFF file header, which follows the PE signature in images and starts
+/// object files.
+pub struct CoffHeader {
+    pub machine: Machine,
+    pub section_count: u16,
+    pub time_date_stamp: u32,
+    pub symbol_table_offset: u32,
+    pub symbol_count: u32,
+    pub optional_header_size: u16,
+    pub characteristics: u16,
+}
+
+impl CoffHeader {
+    /// Whether the image is a DLL rather than an executable.
+    pub fn is_dll(&self) -> bool {
+        self.characteristics & IMAGE_FILE_DLL != 0
+    }
+}
+
+impl Parse for CoffHeader {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        let machine = Machine::parse(reader)?;
+        let section_count = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let time_date_stamp = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let symbol_table_offset = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let symbol_count = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let optional_header_size = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let characteristics = u16::parse_from_endianess(reader, Endianess::Little)?;
+
+        Ok(Self {
+            machine,
+            section_count,
+            time_date_stamp,
+            symbol_table_offset,
+            symbol_count,
+            optional_header_size,
+            characteristics,
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Machine {
+    Unknown,
+    Alpha,
+    Alpha64,
+    Amd64,
+    Arm,
+    Arm64,
+    Arm64Ec,
+    ArmNt,
+    Ebc,
+    I386,
+    Ia64,
+    LoongArch64,
+    Mips16,
+    PowerPc,
+    R4000,
+    RiscV32,
+    RiscV64,
+    Sh3,
+    Sh4,
+    Thumb,
+    Other(u16),
+}
+
+impl Machine {
+    pub fn value(self) -> u16 {
+        match self {
+            Self::Unknown => 0x0000,
+            Self::Alpha => 0x0184,
+            Self::Alpha64 => 0x0284,
+            Self::Amd64 => 0x8664,
+            Self::Arm => 0x01c0,
+            Self::Arm64 => 0xaa64,
+            Self::Arm64Ec => 0xa641,
+            Self::ArmNt => 0x01c4,
+            Self::Ebc => 0x0ebc,
+            Self::I386 => 0x014c,
+            Self::Ia64 => 0x0200,
+            Self::LoongArch64 => 0x6264,
+            Self::Mips16 => 0x0266,
+            Self::PowerPc => 0x01f0,
+            Self::R4000 => 0x0166,
+            Self::RiscV32 => 0x5032,
+            Self::RiscV64 => 0x5064,
+            Self::Sh3 => 0x01a2,
+            Self::Sh4 => 0x01a6,
+            Self::Thumb => 0x01c2,
+            Self::Other(value) => value,
+        }
+    }
+}
+
+impl From<u16> for Machine {
+    fn from(value: u16) -> Self {
+        match value {
+            0x0000 => Self::Unknown,
+            0x0184 => Self::Alpha,
+            0x0284 => Self::Alpha64,
+            0x8664 => Self::Amd64,
+            0x01c0 => Self::Arm,
+            0xaa64 => Self::Arm64,
+            0xa641 => Self::Arm64Ec,
+            0x01c4 => Self::ArmNt,
+            0x0ebc => Self::Ebc,
+            0x014c => Self::I386,
+            0x0200 => Self::Ia64,
+            0x6264 => Self::LoongArch64,
+            0x0266 => Self::Mips16,
+            0x01f0 => Self::PowerPc,
+            0x0166 => Self::R4000,
+            0x5032 => Self::RiscV32,
+            0x5064 => Self::RiscV64,
+            0x01a2 => Self::Sh3,
+            0x01a6 => Self::Sh4,
+            0x01c2 => Self::Thumb,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl Parse for Machine {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        u16::parse_from_endianess(reader, Endianess::Little).map(Self::from)
+    }
+}
@@ -0,0 +1,370 @@
+use crate::dll::DataDirectoryType;
+use crate::dll::PeDescriptor;
+use crate::errors::ParseError;
+use crate::parsing::check_bounds;
+use crate::parsing::read_bytes;
+use crate::parsing::read_vec;
+use crate::parsing::Parse;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::fmt;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+/// An entry of the debug directory, which points at information for
+/// debuggers and other tools.
+pub struct DebugEntry {
+    pub characteristics: u32,
+    pub time_date_stamp: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub debug_type: DebugType,
+    pub data_size: u32,
+    pub data_rva: u32,
+    pub data_offset: u32,
+    /// The decoded data, for the types which are understood.
+    pub data: Option<DebugData>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DebugType {
+    Unknown,
+    Coff,
+    CodeView,
+    Fpo,
+    Misc,
+    Exception,
+    Fixup,
+    OmapToSource,
+    OmapFromSource,
+    Borland,
+    Clsid,
+    VcFeature,
+    Pogo,
+    Iltcg,
+    Mpx,
+    Repro,
+    ExtendedDllCharacteristics,
+    Other(u32),
+}
+
+impl Parse for DebugType {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        let val = u32::parse_from_endianess(reader, Endianess::Little)?;
+        Ok(match val {
+            0 => Self::Unknown,
+            1 => Self::Coff,
+            2 => Self::CodeView,
+            3 => Self::Fpo,
+            4 => Self::Misc,
+            5 => Self::Exception,
+            6 => Self::Fixup,
+            7 => Self::OmapToSource,
+            8 => Self::OmapFromSource,
+            9 => Self::Borland,
+            11 => Self::Clsid,
+            12 => Self::VcFeature,
+            13 => Self::Pogo,
+            14 => Self::Iltcg,
+            15 => Self::Mpx,
+            16 => Self::Repro,
+            20 => Self::ExtendedDllCharacteristics,
+            other => Self::Other(other),
+        })
+    }
+}
+
+pub enum DebugData {
+    CodeView(CodeView),
+    /// The sections the profile guided optimizer laid out, by name.
+    Pogo(Vec<PogoEntry>),
+    /// The hash of the inputs of a reproducible build, which may be empty.
+    Repro(Box<[u8]>),
+    VcFeature(VcFeature),
+}
+
+/// The record which ties the image to its program database (PDB).
+pub enum CodeView {
+    /// The `RSDS` record written by Visual C++ 7.0 and later.
+    Rsds { guid: Guid, age: u32, path: String },
+    /// The `NB10` record written by earlier linkers.
+    Nb10 {
+        signature: u32,
+        age: u32,
+        path: String,
+    },
+}
+
+impl CodeView {
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Rsds { path, .. } | Self::Nb10 { path, .. } => path,
+        }
+    }
+
+    /// The directory which symbol servers store the PDB under, made of its
+    /// signature and age.
+    pub fn pdb_key(&self) -> String {
+        match self {
+            Self::Rsds { guid, age, .. } => format!(
+                "{:08X}{:04X}{:04X}{}{:X}",
+                guid.data1,
+                guid.data2,
+                guid.data3,
+                guid.data4
+                    .iter()
+                    .map(|byte| format!("{:02X}", byte))
+                    .collect::<String>(),
+                age
+            ),
+            Self::Nb10 { signature, age, .. } => format!("{:08X}{:X}", signature, age),
+        }
+    }
+
+    fn parse(reader: &mut &[u8]) -> Result<Self, ParseError> {
+        let signature = read_bytes::<_, 4>(reader)?;
+        match &signature {
+            b"RSDS" => {
+                let guid = Guid::parse(reader)?;
+                let age = u32::parse_from_endianess(reader, Endianess::Little)?;
+                let path = read_path(reader)?;
+                Ok(Self::Rsds { guid, age, path })
+            }
+            b"NB10" => {
+                // The offset is always zero, as the debug information is
+                // in a separate file
+                let _offset = u32::parse_from_endianess(reader, Endianess::Little)?;
+                let signature = u32::parse_from_endianess(reader, Endianess::Little)?;
+                let age = u32::parse_from_endianess(reader, Endianess::Little)?;
+                let path = read_path(reader)?;
+                Ok(Self::Nb10 {
+                    signature,
+                    age,
+                    path,
+                })
+            }
+            _ => Err(ParseError::InvalidValue {
+                value: signature.into(),
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Guid {
+    pub data1: u32,
+    pub data2: u16,
+    pub data3: u16,
+    pub data4: [u8; 8],
+}
+
+impl Parse for Guid {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        let data1 = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let data2 = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let data3 = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let data4 = read_bytes::<R, 8>(reader)?;
+
+        Ok(Self {
+            data1,
+            data2,
+            data3,
+            data4,
+        })
+    }
+}
+
+impl fmt::Display for Guid {
+    /// Formats the GUID as it is written in the registry, without braces.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-",
+            self.data1, self.data2, self.data3, self.data4[0], self.data4[1]
+        )?;
+        self.data4[2..]
+            .iter()
+            .try_for_each(|byte| write!(f, "{:02X}", byte))
+    }
+}
+
+pub struct PogoEntry {
+    pub rva: u32,
+    pub size: u32,
+    pub name: String,
+}
+
+/// How many objects were built by each kind of compiler, and with which of
+/// the security features.
+pub struct VcFeature {
+    pub pre_vc11_count: u32,
+    pub c_cpp_count: u32,
+    pub gs_count: u32,
+    pub sdl_count: u32,
+    pub guard_n_count: u32,
+}
+
+impl PeDescriptor {
+    /// Reads the entries of the debug directory, decoding the data of those
+    /// which are understood.
+    pub fn debug_entries<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Vec<DebugEntry>, ParseError> {
+        let directory = match self.data_directory(DataDirectoryType::Debug) {
+            Some(directory) => directory,
+            None => return Ok(Vec::new()),
+        };
+
+        self.seek_to_rva(reader, directory.virtual_address, directory.size)?;
+        let mut entries = (0..directory.size / 28)
+            .map(|_| {
+                let characteristics = u32::parse_from_endianess(reader, Endianess::Little)?;
+                let time_date_stamp = u32::parse_from_endianess(reader, Endianess::Little)?;
+                let major_version = u16::parse_from_endianess(reader, Endianess::Little)?;
+                let minor_version = u16::parse_from_endianess(reader, Endianess::Little)?;
+                let debug_type = DebugType::parse(reader)?;
+                let data_size = u32::parse_from_endianess(reader, Endianess::Little)?;
+                let data_rva = u32::parse_from_endianess(reader, Endianess::Little)?;
+                let data_offset = u32::parse_from_endianess(reader, Endianess::Little)?;
+
+                Ok(DebugEntry {
+                    characteristics,
+                    time_date_stamp,
+                    major_version,
+                    minor_version,
+                    debug_type,
+                    data_size,
+                    data_rva,
+                    data_offset,
+                    data: None,
+                })
+            })
+            .collect::<Result<Vec<_>, ParseError>>()?;
+
+        for entry in &mut entries {
+            entry.data = self.read_debug_data(reader, entry);
+        }
+
+        Ok(entries)
+    }
+
+    /// Finds the record which ties the image to its PDB, if it has one.
+    pub fn code_view<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Option<CodeView>, ParseError> {
+        let entries = self.debug_entries(reader)?;
+        Ok(entries.into_iter().find_map(|entry| match entry.data {
+            Some(DebugData::CodeView(code_view)) => Some(code_view),
+            _ => None,
+        }))
+    }
+
+    /// The directory which symbol servers store the image under, made of its
+    /// timestamp and size.
+    pub fn symbol_server_key(&self) -> String {
+        format!(
+            "{:08X}{:x}",
+            self.coff_header.time_date_stamp, self.optional_header.image_size
+        )
+    }
+
+    /// Reads and decodes the data of an entry. A malformed entry, whether its
+    /// data is out of bounds or can't be decoded, only loses its own data
+    /// rather than failing the other entries.
+    fn read_debug_data<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        entry: &DebugEntry,
+    ) -> Option<DebugData> {
+        let is_decoded = matches!(
+            entry.debug_type,
+            DebugType::CodeView | DebugType::Pogo | DebugType::Repro | DebugType::VcFeature
+        );
+        if !is_decoded {
+            return None;
+        }
+
+        let data = self.read_debug_record(reader, entry).ok()??;
+        decode_debug_data(entry.debug_type, &mut data.as_slice())
+            .ok()
+            .flatten()
+    }
+
+    /// Reads the raw data of an entry, if the image still has it.
+    fn read_debug_record<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        entry: &DebugEntry,
+    ) -> Result<Option<Vec<u8>>, ParseError> {
+        // The data isn't always mapped into memory, but it is always in the
+        // file unless the image was stripped of it
+        if entry.data_offset != 0 {
+            let offset = entry.data_offset.into();
+            check_bounds(reader, offset, entry.data_size.into())?;
+            reader.seek(SeekFrom::Start(offset))?;
+        } else if entry.data_rva != 0 {
+            self.seek_to_rva(reader, entry.data_rva, entry.data_size)?;
+        } else if entry.data_size != 0 {
+            return Ok(None);
+        }
+
+        read_vec(reader, entry.data_size as usize).map(Some)
+    }
+}
+
+/// Decodes the record of a debug entry whose type is understood.
+fn decode_debug_data(
+    debug_type: DebugType,
+    reader: &mut &[u8],
+) -> Result<Option<DebugData>, ParseError> {
+    Ok(Some(match debug_type {
+        DebugType::CodeView => DebugData::CodeView(CodeView::parse(reader)?),
+        DebugType::Pogo => {
+            // Skip the signature, which tells the kind of optimization
+            let _signature = read_bytes::<_, 4>(reader)?;
+            let mut pogo_entries = Vec::new();
+            while !reader.is_empty() {
+                let rva = u32::parse_from_endianess(reader, Endianess::Little)?;
+                let size = u32::parse_from_endianess(reader, Endianess::Little)?;
+                let name = read_path(reader)?;
+
+                // Names are padded to keep the entries aligned
+                let padding = (4 - (name.len() + 1) % 4) % 4;
+                *reader = reader.get(padding..).unwrap_or_default();
+                pogo_entries.push(PogoEntry { rva, size, name });
+            }
+            DebugData::Pogo(pogo_entries)
+        }
+        DebugType::Repro => {
+            let hash = if reader.is_empty() {
+                Vec::new()
+            } else {
+                let length = u32::parse_from_endianess(reader, Endianess::Little)?;
+                read_vec(reader, length as usize)?
+            };
+            DebugData::Repro(hash.into())
+        }
+        DebugType::VcFeature => DebugData::VcFeature(VcFeature {
+            pre_vc11_count: u32::parse_from_endianess(reader, Endianess::Little)?,
+            c_cpp_count: u32::parse_from_endianess(reader, Endianess::Little)?,
+            gs_count: u32::parse_from_endianess(reader, Endianess::Little)?,
+            sdl_count: u32::parse_from_endianess(reader, Endianess::Little)?,
+            guard_n_count: u32::parse_from_endianess(reader, Endianess::Little)?,
+        }),
+        _ => return Ok(None),
+    }))
+}
+
+/// Reads a null terminated string from a debug record.
+fn read_path(reader: &mut &[u8]) -> Result<String, ParseError> {
+    let end = reader
+        .iter()
+        .position(|byte| *byte == 0)
+        .ok_or(ParseError::MissingData)?;
+    let path = String::from_utf8_lossy(&reader[..end]).into_owned();
+    *reader = &reader[end + 1..];
+    Ok(path)
+}
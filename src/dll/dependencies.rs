@@ -0,0 +1,262 @@
+//! Resolves the tree of DLLs an image needs, following the standard search
+//! order of the Windows loader. Only the files are read, so a copy of a
+//! Windows installation or a Wine prefix can be inspected from any system.
+
+use crate::dll::ApiSetSchema;
+use crate::dll::Machine;
+use crate::dll::PeDescriptor;
+use crate::errors::ParseError;
+use crate::parsing::ParseSeekable;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fs;
+use std::fs::File;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// A DLL needed by an image, along with the DLLs it needs in turn.
+pub struct Dependency {
+    /// The name the DLL was imported by (e.g. `KERNEL32.dll`).
+    pub name: String,
+    /// Where the DLL was found, if it was found at all. For API sets, this
+    /// is the DLL which hosts the set.
+    pub path: Option<PathBuf>,
+    /// Whether the DLL was already loaded for an earlier import, in which
+    /// case its own dependencies are only listed under that import.
+    pub repeated: bool,
+    /// Whether the DLL is only loaded once it is first used, so the image
+    /// can still start when it is missing.
+    pub delay_loaded: bool,
+    pub dependencies: Vec<Dependency>,
+}
+
+pub struct DependencyResolver {
+    /// The directory holding the `Windows` directory, such as a mounted
+    /// system drive or the `drive_c` directory of a Wine prefix.
+    pub sysroot: Option<PathBuf>,
+    /// The directories searched as if they were in `PATH`.
+    pub path: Vec<PathBuf>,
+    /// Maps API set names to the DLLs which host them. When unset, the
+    /// schema is read from `apisetschema.dll` in the system directory.
+    pub api_set_schema: Option<ApiSetSchema>,
+}
+
+impl DependencyResolver {
+    pub fn new() -> Self {
+        Self {
+            sysroot: None,
+            path: Vec::new(),
+            api_set_schema: None,
+        }
+    }
+
+    /// Builds the tree of DLLs which would be loaded alongside the image.
+    pub fn resolve(&self, path: &Path) -> Result<Dependency, ParseError> {
+        let windows = self
+            .sysroot
+            .as_ref()
+            .and_then(|sysroot| find_entry(sysroot, "Windows"));
+        let loaded_schema;
+        let api_set_schema = match &self.api_set_schema {
+            Some(schema) => Some(schema),
+            None => {
+                loaded_schema = windows
+                    .as_ref()
+                    .and_then(|windows| find_entry(windows, "System32"))
+                    .and_then(|system| find_entry(&system, "apisetschema.dll"))
+                    .and_then(|path| File::open(path).ok())
+                    .and_then(|mut file| ApiSetSchema::parse_dll(&mut file).ok());
+                loaded_schema.as_ref()
+            }
+        };
+        let search = Search {
+            application: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            windows,
+            path: &self.path,
+        };
+
+        let image = LoadedImage::load(path)?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut loaded = HashMap::new();
+        loaded.insert(name.to_ascii_lowercase(), path.to_path_buf());
+
+        // Modules are known by their base name alone, so each DLL is only
+        // loaded once however many modules import it
+        let mut nodes = vec![Node {
+            name,
+            path: Some(path.to_path_buf()),
+            repeated: false,
+            delay_loaded: false,
+            image: Some(image),
+            children: Vec::new(),
+        }];
+        let mut queue = VecDeque::new();
+        queue.push_back(0);
+        while let Some(index) = queue.pop_front() {
+            let (imports, machine) = match &nodes[index].image {
+                Some(image) => (image.imports.clone(), image.machine),
+                None => continue,
+            };
+
+            for (name, delay_loaded) in imports {
+                let file_name = match api_set_schema {
+                    Some(schema) if ApiSetSchema::is_api_set(&name) => {
+                        schema.resolve(&name, &nodes[index].name).map(String::from)
+                    }
+                    _ => Some(name.clone()),
+                };
+                let key = file_name.as_ref().map(|name| name.to_ascii_lowercase());
+
+                let child = nodes.len();
+                let node = match key.as_ref().and_then(|key| loaded.get(key)) {
+                    Some(path) => Node {
+                        name,
+                        path: Some(path.clone()),
+                        repeated: true,
+                        delay_loaded,
+                        image: None,
+                        children: Vec::new(),
+                    },
+                    None => {
+                        let found =
+                            file_name.and_then(|file_name| search.find(&file_name, machine));
+                        if let (Some(key), Some((path, _))) = (key, &found) {
+                            loaded.insert(key, path.clone());
+                        }
+                        queue.push_back(child);
+                        Node {
+                            name,
+                            path: found.as_ref().map(|(path, _)| path.clone()),
+                            repeated: false,
+                            delay_loaded,
+                            image: found.map(|(_, image)| image),
+                            children: Vec::new(),
+                        }
+                    }
+                };
+
+                nodes.push(node);
+                nodes[index].children.push(child);
+            }
+        }
+
+        Ok(build_tree(&mut nodes, 0))
+    }
+}
+
+impl Default for DependencyResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The directories searched for DLLs which aren't already loaded.
+struct Search<'a> {
+    application: PathBuf,
+    windows: Option<PathBuf>,
+    path: &'a [PathBuf],
+}
+
+impl<'a> Search<'a> {
+    /// Searches for the DLL `name` on behalf of an image for `machine`, in
+    /// the order used when safe DLL search mode is enabled. The known DLLs
+    /// are listed in the registry, but all of them live in the system
+    /// directory anyway.
+    fn find(&self, name: &str, machine: Machine) -> Option<(PathBuf, LoadedImage)> {
+        let mut directories = vec![self.application.clone()];
+        if let Some(windows) = &self.windows {
+            // 32-bit images on 64-bit Windows are redirected to SysWOW64
+            let system = match machine {
+                Machine::I386 => find_entry(windows, "SysWOW64"),
+                _ => None,
+            };
+            directories.extend(system.or_else(|| find_entry(windows, "System32")));
+            directories.extend(find_entry(windows, "System"));
+            directories.push(windows.clone());
+        }
+        directories.extend(self.path.iter().cloned());
+
+        // DLLs built for another machine are skipped rather than loaded
+        directories
+            .iter()
+            .filter_map(|directory| find_entry(directory, name))
+            .filter_map(|path| LoadedImage::load(&path).ok().map(|image| (path, image)))
+            .find(|(_, image)| image.machine == machine)
+    }
+}
+
+/// The details of an image which affect how its dependencies are found.
+struct LoadedImage {
+    machine: Machine,
+    /// The name of each imported DLL and whether it is delay-loaded.
+    imports: Vec<(String, bool)>,
+}
+
+impl LoadedImage {
+    fn load(path: &Path) -> Result<Self, ParseError> {
+        let mut file = File::open(path)?;
+        let descriptor = PeDescriptor::parse_seekable(&mut file)?;
+        let mut imports = Vec::new();
+        for dll in descriptor.imports(&mut file)? {
+            imports.push((dll.name, false));
+        }
+        for dll in descriptor.delay_load_imports(&mut file)? {
+            imports.push((dll.name, true));
+        }
+
+        Ok(Self {
+            machine: descriptor.coff_header.machine,
+            imports,
+        })
+    }
+}
+
+/// A dependency while the tree is being built.
+struct Node {
+    name: String,
+    path: Option<PathBuf>,
+    repeated: bool,
+    delay_loaded: bool,
+    image: Option<LoadedImage>,
+    children: Vec<usize>,
+}
+
+fn build_tree(nodes: &mut Vec<Node>, index: usize) -> Dependency {
+    let children = std::mem::take(&mut nodes[index].children);
+    let dependencies = children
+        .into_iter()
+        .map(|child| build_tree(nodes, child))
+        .collect();
+    let node = &mut nodes[index];
+
+    Dependency {
+        name: std::mem::take(&mut node.name),
+        path: node.path.take(),
+        repeated: node.repeated,
+        delay_loaded: node.delay_loaded,
+        dependencies,
+    }
+}
+
+/// Finds the entry of `directory` with the provided name, ignoring case as
+/// Windows file systems do.
+fn find_entry(directory: &Path, name: &str) -> Option<PathBuf> {
+    let exact = directory.join(name);
+    if exact.exists() {
+        return Some(exact);
+    }
+
+    fs::read_dir(directory)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .find(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .eq_ignore_ascii_case(name)
+        })
+        .map(|entry| entry.path())
+}
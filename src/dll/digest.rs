@@ -0,0 +1,369 @@
+//! The hash functions which Authenticode signatures are made with, so that
+//! image digests can be checked without any other dependencies.
+
+/// A hash which is fed data in pieces of any size.
+pub(crate) enum Hasher {
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha1(hasher) => {
+                let state = &mut hasher.state;
+                hasher.blocks.update(data, |block| state.compress(block));
+            }
+            Self::Sha256(hasher) => {
+                let state = &mut hasher.state;
+                hasher.blocks.update(data, |block| state.compress(block));
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> Box<[u8]> {
+        match self {
+            Self::Sha1(mut hasher) => {
+                let state = &mut hasher.state;
+                hasher.blocks.finish(|block| state.compress(block));
+                words_to_bytes(&state.0)
+            }
+            Self::Sha256(mut hasher) => {
+                let state = &mut hasher.state;
+                hasher.blocks.finish(|block| state.compress(block));
+                words_to_bytes(&state.0)
+            }
+        }
+    }
+}
+
+/// Writes out the state of a hash as its digest, most significant byte first.
+fn words_to_bytes(words: &[u32]) -> Box<[u8]> {
+    let mut out = Vec::with_capacity(words.len() * 4);
+    for word in words {
+        out.extend_from_slice(&word.to_be_bytes());
+    }
+    out.into()
+}
+
+pub(crate) struct Sha1 {
+    state: Sha1State,
+    blocks: Blocks,
+}
+
+impl Sha1 {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Sha1State([
+                0x6745_2301,
+                0xefcd_ab89,
+                0x98ba_dcfe,
+                0x1032_5476,
+                0xc3d2_e1f0,
+            ]),
+            blocks: Blocks::new(),
+        }
+    }
+}
+
+struct Sha1State([u32; 5]);
+
+impl Sha1State {
+    fn compress(&mut self, block: &[u8; 64]) {
+        let mut schedule = [0u32; 80];
+        for (index, word) in block.chunks_exact(4).enumerate() {
+            schedule[index] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for index in 16..80 {
+            schedule[index] = (schedule[index - 3]
+                ^ schedule[index - 8]
+                ^ schedule[index - 14]
+                ^ schedule[index - 16])
+                .rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = self.0;
+        for (index, word) in schedule.iter().enumerate() {
+            let (f, k) = match index {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (state, value) in self.0.iter_mut().zip([a, b, c, d, e].iter()) {
+            *state = state.wrapping_add(*value);
+        }
+    }
+}
+
+pub(crate) struct Sha256 {
+    state: Sha256State,
+    blocks: Blocks,
+}
+
+impl Sha256 {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Sha256State([
+                0x6a09_e667,
+                0xbb67_ae85,
+                0x3c6e_f372,
+                0xa54f_f53a,
+                0x510e_527f,
+                0x9b05_688c,
+                0x1f83_d9ab,
+                0x5be0_cd19,
+            ]),
+            blocks: Blocks::new(),
+        }
+    }
+}
+
+const SHA256_ROUND_CONSTANTS: [u32; 64] = [
+    0x428a_2f98,
+    0x7137_4491,
+    0xb5c0_fbcf,
+    0xe9b5_dba5,
+    0x3956_c25b,
+    0x59f1_11f1,
+    0x923f_82a4,
+    0xab1c_5ed5,
+    0xd807_aa98,
+    0x1283_5b01,
+    0x2431_85be,
+    0x550c_7dc3,
+    0x72be_5d74,
+    0x80de_b1fe,
+    0x9bdc_06a7,
+    0xc19b_f174,
+    0xe49b_69c1,
+    0xefbe_4786,
+    0x0fc1_9dc6,
+    0x240c_a1cc,
+    0x2de9_2c6f,
+    0x4a74_84aa,
+    0x5cb0_a9dc,
+    0x76f9_88da,
+    0x983e_5152,
+    0xa831_c66d,
+    0xb003_27c8,
+    0xbf59_7fc7,
+    0xc6e0_0bf3,
+    0xd5a7_9147,
+    0x06ca_6351,
+    0x1429_2967,
+    0x27b7_0a85,
+    0x2e1b_2138,
+    0x4d2c_6dfc,
+    0x5338_0d13,
+    0x650a_7354,
+    0x766a_0abb,
+    0x81c2_c92e,
+    0x9272_2c85,
+    0xa2bf_e8a1,
+    0xa81a_664b,
+    0xc24b_8b70,
+    0xc76c_51a3,
+    0xd192_e819,
+    0xd699_0624,
+    0xf40e_3585,
+    0x106a_a070,
+    0x19a4_c116,
+    0x1e37_6c08,
+    0x2748_774c,
+    0x34b0_bcb5,
+    0x391c_0cb3,
+    0x4ed8_aa4a,
+    0x5b9c_ca4f,
+    0x682e_6ff3,
+    0x748f_82ee,
+    0x78a5_636f,
+    0x84c8_7814,
+    0x8cc7_0208,
+    0x90be_fffa,
+    0xa450_6ceb,
+    0xbef9_a3f7,
+    0xc671_78f2,
+];
+
+struct Sha256State([u32; 8]);
+
+impl Sha256State {
+    fn compress(&mut self, block: &[u8; 64]) {
+        let mut schedule = [0u32; 64];
+        for (index, word) in block.chunks_exact(4).enumerate() {
+            schedule[index] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for index in 16..64 {
+            let s0 = schedule[index - 15].rotate_right(7)
+                ^ schedule[index - 15].rotate_right(18)
+                ^ (schedule[index - 15] >> 3);
+            let s1 = schedule[index - 2].rotate_right(17)
+                ^ schedule[index - 2].rotate_right(19)
+                ^ (schedule[index - 2] >> 10);
+            schedule[index] = schedule[index - 16]
+                .wrapping_add(s0)
+                .wrapping_add(schedule[index - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.0;
+        for (word, constant) in schedule.iter().zip(SHA256_ROUND_CONSTANTS.iter()) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(choice)
+                .wrapping_add(*constant)
+                .wrapping_add(*word);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(majority);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+
+        for (state, value) in self.0.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+            *state = state.wrapping_add(*value);
+        }
+    }
+}
+
+/// Splits the data into the 64 byte blocks which both hashes work on, and
+/// pads the last block with the length of the data.
+struct Blocks {
+    buffer: [u8; 64],
+    buffered: usize,
+    length: u64,
+}
+
+impl Blocks {
+    fn new() -> Self {
+        Self {
+            buffer: [0; 64],
+            buffered: 0,
+            length: 0,
+        }
+    }
+
+    fn update<F: FnMut(&[u8; 64])>(&mut self, mut data: &[u8], mut compress: F) {
+        self.length += data.len() as u64;
+        while !data.is_empty() {
+            let taken = data.len().min(64 - self.buffered);
+            self.buffer[self.buffered..self.buffered + taken].copy_from_slice(&data[..taken]);
+            self.buffered += taken;
+            data = &data[taken..];
+
+            if self.buffered == 64 {
+                compress(&self.buffer);
+                self.buffered = 0;
+            }
+        }
+    }
+
+    fn finish<F: FnMut(&[u8; 64])>(mut self, mut compress: F) {
+        let bit_length = self.length.wrapping_mul(8);
+        self.update(&[0x80], &mut compress);
+        while self.buffered != 56 {
+            self.update(&[0], &mut compress);
+        }
+        self.update(&bit_length.to_be_bytes(), &mut compress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hasher;
+    use super::Sha1;
+    use super::Sha256;
+
+    const ALPHABET: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+
+    /// Hashes `data` in pieces of `piece_size` bytes, returning the digest in
+    /// hexadecimal.
+    fn digest(mut hasher: Hasher, data: &[u8], piece_size: usize) -> String {
+        for piece in data.chunks(piece_size.max(1)) {
+            hasher.update(piece);
+        }
+        hasher
+            .finish()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    fn sha1(data: &[u8]) -> String {
+        digest(Hasher::Sha1(Sha1::new()), data, data.len())
+    }
+
+    fn sha256(data: &[u8]) -> String {
+        digest(Hasher::Sha256(Sha256::new()), data, data.len())
+    }
+
+    #[test]
+    fn sha1_vectors() {
+        assert_eq!(sha1(b""), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(sha1(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(sha1(ALPHABET), "84983e441c3bd26ebaae4aa1f95129e5e54670f1");
+        assert_eq!(
+            sha1(&[b'a'; 1_000_000]),
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+        );
+    }
+
+    #[test]
+    fn sha256_vectors() {
+        assert_eq!(
+            sha256(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            sha256(ALPHABET),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(
+            sha256(&[b'a'; 1_000_000]),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+
+    #[test]
+    fn pieces_of_any_size() {
+        // Pieces which straddle the 64 byte blocks give the same digest
+        let data = [b'a'; 1_000_000];
+        for piece_size in &[1, 63, 65, 1000] {
+            assert_eq!(
+                digest(Hasher::Sha1(Sha1::new()), &data, *piece_size),
+                "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+            );
+            assert_eq!(
+                digest(Hasher::Sha256(Sha256::new()), &data, *piece_size),
+                "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+            );
+        }
+    }
+}
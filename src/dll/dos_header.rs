@@ -0,0 +1,83 @@
+use crate::errors::ParseError;
+use crate::parsing::read_bytes;
+use crate::parsing::Parse;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::io::Read;
+
+/// The MS-DOS header at the start of every image, which is mostly kept for
+/// the stub program that tells users the image can't be run in DOS.
+pub struct DosHeader {
+    pub last_page_size: u16,
+    pub page_count: u16,
+    pub relocation_count: u16,
+    pub header_paragraphs: u16,
+    pub min_extra_paragraphs: u16,
+    pub max_extra_paragraphs: u16,
+    pub initial_ss: u16,
+    pub initial_sp: u16,
+    pub checksum: u16,
+    pub initial_ip: u16,
+    pub initial_cs: u16,
+    pub relocation_table_offset: u16,
+    pub overlay_number: u16,
+    pub oem_id: u16,
+    pub oem_info: u16,
+    /// The offset of the PE signature (`e_lfanew`).
+    pub new_header_offset: u32,
+}
+
+impl DosHeader {
+    /// The size of the header, which is followed by the DOS stub.
+    pub const SIZE: u32 = 64;
+}
+
+impl Parse for DosHeader {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        // Ensure magic number is present
+        const MAGIC_NUMBER: [u8; 2] = *b"MZ";
+        if read_bytes::<R, 2>(reader)? != MAGIC_NUMBER {
+            return Err(ParseError::MissingData);
+        }
+
+        let last_page_size = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let page_count = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let relocation_count = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let header_paragraphs = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let min_extra_paragraphs = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let max_extra_paragraphs = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let initial_ss = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let initial_sp = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let checksum = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let initial_ip = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let initial_cs = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let relocation_table_offset = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let overlay_number = u16::parse_from_endianess(reader, Endianess::Little)?;
+        // reserved
+        read_bytes::<R, 8>(reader)?;
+        let oem_id = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let oem_info = u16::parse_from_endianess(reader, Endianess::Little)?;
+        // reserved
+        read_bytes::<R, 20>(reader)?;
+        let new_header_offset = u32::parse_from_endianess(reader, Endianess::Little)?;
+
+        Ok(Self {
+            last_page_size,
+            page_count,
+            relocation_count,
+            header_paragraphs,
+            min_extra_paragraphs,
+            max_extra_paragraphs,
+            initial_ss,
+            initial_sp,
+            checksum,
+            initial_ip,
+            initial_cs,
+            relocation_table_offset,
+            overlay_number,
+            oem_id,
+            oem_info,
+            new_header_offset,
+        })
+    }
+}
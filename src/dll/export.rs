@@ -0,0 +1,237 @@
+use crate::dll::DataDirectory;
+use crate::dll::DataDirectoryType;
+use crate::dll::PeDescriptor;
+use crate::errors::ParseError;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::io::Read;
+use std::io::Seek;
+
+/// The header of the export data directory (`.edata`).
+pub struct ExportDirectory {
+    pub characteristics: u32,
+    pub time_date_stamp: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    /// The name the DLL was linked as, which may differ from its file name.
+    pub name: Option<String>,
+    /// The ordinal of the first entry in the address table.
+    pub ordinal_base: u32,
+    pub address_count: u32,
+    pub name_count: u32,
+    pub address_table: u32,
+    pub name_table: u32,
+    pub ordinal_table: u32,
+}
+
+/// A function or variable made available to other images.
+pub struct Export {
+    /// The name of the export, which is absent for exports that can only be
+    /// imported by ordinal.
+    pub name: Option<String>,
+    pub ordinal: u32,
+    /// The address of the export, relative to the image base.
+    pub rva: u32,
+    /// The export of another DLL which this one is resolved to, such as
+    /// `NTDLL.RtlAllocateHeap` or `NTDLL.#12` for an ordinal.
+    pub forwarder: Option<String>,
+}
+
+impl Export {
+    pub fn is_forwarded(&self) -> bool {
+        self.forwarder.is_some()
+    }
+}
+
+impl PeDescriptor {
+    /// Reads the header of the export directory, if the image has one.
+    pub fn export_directory<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Option<ExportDirectory>, ParseError> {
+        let directory = match self.data_directory(DataDirectoryType::Export) {
+            Some(directory) => directory,
+            None => return Ok(None),
+        };
+
+        self.seek_to_rva(reader, directory.virtual_address, 40)?;
+        let characteristics = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let time_date_stamp = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let major_version = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let minor_version = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let name = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let ordinal_base = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let address_count = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let name_count = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let address_table = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let name_table = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let ordinal_table = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let name = match name {
+            0 => None,
+            name => Some(self.read_string(reader, name)?),
+        };
+
+        Ok(Some(ExportDirectory {
+            characteristics,
+            time_date_stamp,
+            major_version,
+            minor_version,
+            name,
+            ordinal_base,
+            address_count,
+            name_count,
+            address_table,
+            name_table,
+            ordinal_table,
+        }))
+    }
+
+    /// Iterates over the functions and variables which this image makes
+    /// available to other images.
+    pub fn exported_symbols<'a, R: Read + Seek>(
+        &'a self,
+        reader: &'a mut R,
+    ) -> Result<Exports<'a, R>, ParseError> {
+        Exports::new(reader, self)
+    }
+}
+
+pub struct Exports<'a, R> {
+    reader: &'a mut R,
+    descriptor: &'a PeDescriptor,
+    directory: Option<DataDirectory>,
+    ordinal_base: u32,
+    address_table: u32,
+    /// The names of each entry in the address table, since several names
+    /// may be given to the same export.
+    names: HashMap<u32, Vec<u32>>,
+    pending: VecDeque<Export>,
+    index: u32,
+    count: u32,
+}
+
+impl<'a, R: Read + Seek> Exports<'a, R> {
+    fn new(reader: &'a mut R, descriptor: &'a PeDescriptor) -> Result<Self, ParseError> {
+        let mut exports = Self {
+            reader,
+            descriptor,
+            directory: descriptor.data_directory(DataDirectoryType::Export),
+            ordinal_base: 0,
+            address_table: 0,
+            names: HashMap::new(),
+            pending: VecDeque::new(),
+            index: 0,
+            count: 0,
+        };
+
+        let export_directory = match descriptor.export_directory(exports.reader)? {
+            Some(export_directory) => export_directory,
+            None => return Ok(exports),
+        };
+
+        // The name table is paired with the ordinal table, which holds the
+        // index into the address table rather than the ordinal itself
+        let name_count = export_directory.name_count;
+        let out_of_bounds = |rva: u32, entry_size: u64| ParseError::OutOfBounds {
+            offset: rva.into(),
+            size: u64::from(name_count) * entry_size,
+        };
+        let name_table_size = name_count
+            .checked_mul(4)
+            .ok_or_else(|| out_of_bounds(export_directory.name_table, 4))?;
+        descriptor.seek_to_rva(exports.reader, export_directory.name_table, name_table_size)?;
+        let name_table = (0..name_count)
+            .map(|_| u32::parse_from_endianess(exports.reader, Endianess::Little))
+            .collect::<Result<Vec<_>, _>>()?;
+        let ordinal_table_size = name_count
+            .checked_mul(2)
+            .ok_or_else(|| out_of_bounds(export_directory.ordinal_table, 2))?;
+        descriptor.seek_to_rva(
+            exports.reader,
+            export_directory.ordinal_table,
+            ordinal_table_size,
+        )?;
+        for name in name_table {
+            let index = u16::parse_from_endianess(exports.reader, Endianess::Little)?;
+            exports.names.entry(index.into()).or_default().push(name);
+        }
+
+        exports.ordinal_base = export_directory.ordinal_base;
+        exports.address_table = export_directory.address_table;
+        exports.count = export_directory.address_count;
+        Ok(exports)
+    }
+
+    /// Reads the entry at `index` of the address table once for each of its
+    /// names, queueing the exports to be returned.
+    fn read_exports(&mut self, index: u32) -> Result<(), ParseError> {
+        let descriptor = self.descriptor;
+        let entry = index
+            .checked_mul(4)
+            .and_then(|offset| self.address_table.checked_add(offset))
+            .ok_or(ParseError::OutOfBounds {
+                offset: self.address_table.into(),
+                size: (u64::from(index) + 1) * 4,
+            })?;
+        let ordinal = self
+            .ordinal_base
+            .checked_add(index)
+            .ok_or(ParseError::InvalidValue {
+                value: self.ordinal_base.to_le_bytes().into(),
+            })?;
+        descriptor.seek_to_rva(self.reader, entry, 4)?;
+        let rva = u32::parse_from_endianess(self.reader, Endianess::Little)?;
+
+        // Gaps between ordinals are left empty
+        if rva == 0 {
+            return Ok(());
+        }
+
+        let names = match self.names.remove(&index) {
+            Some(names) => names
+                .into_iter()
+                .map(|name| descriptor.read_string(self.reader, name).map(Some))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => vec![None],
+        };
+
+        // Forwarders are stored in place of the code, within the directory
+        let is_forwarder = matches!(
+            self.directory,
+            Some(directory) if rva.wrapping_sub(directory.virtual_address) < directory.size
+        );
+        let forwarder = if is_forwarder {
+            Some(descriptor.read_string(self.reader, rva)?)
+        } else {
+            None
+        };
+
+        for name in names {
+            self.pending.push_back(Export {
+                name,
+                ordinal,
+                rva,
+                forwarder: forwarder.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<'a, R: Read + Seek> Iterator for Exports<'a, R> {
+    type Item = Result<Export, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() && self.index < self.count {
+            let index = self.index;
+            self.index += 1;
+            if let Err(error) = self.read_exports(index) {
+                return Some(Err(error));
+            }
+        }
+
+        self.pending.pop_front().map(Ok)
+    }
+}
@@ -0,0 +1,166 @@
+use crate::dll::add_rva;
+use crate::dll::DataDirectoryType;
+use crate::dll::PeDescriptor;
+use crate::dll::PeFormat;
+use crate::errors::ParseError;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::io::Read;
+use std::io::Seek;
+
+/// The delay-load descriptors of old linkers hold virtual addresses instead
+/// of relative ones, which this attribute tells apart.
+const DLATTR_RVA: u32 = 0x1;
+
+/// A DLL which the image needs, along with what it uses from it.
+pub struct ImportedDll {
+    pub name: String,
+    /// Whether the DLL is only loaded once one of its imports is first
+    /// called, so the image can still start without it.
+    pub delay_loaded: bool,
+    pub time_date_stamp: u32,
+    pub imports: Vec<Import>,
+}
+
+pub enum Import {
+    /// An export which is looked up by its ordinal alone.
+    Ordinal(u16),
+    /// An export which is looked up by name, starting from the index of the
+    /// name table hinted at by the linker.
+    Name { hint: u16, name: String },
+}
+
+impl PeDescriptor {
+    /// Reads the DLLs which are loaded alongside the image, from the import
+    /// directory.
+    pub fn imports<R: Read + Seek>(&self, reader: &mut R) -> Result<Vec<ImportedDll>, ParseError> {
+        let directory = match self.data_directory(DataDirectoryType::Import) {
+            Some(directory) => directory,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut dlls = Vec::new();
+        let mut rva = directory.virtual_address;
+        loop {
+            self.seek_to_rva(reader, rva, 20)?;
+            let lookup_table = u32::parse_from_endianess(reader, Endianess::Little)?;
+            let time_date_stamp = u32::parse_from_endianess(reader, Endianess::Little)?;
+            let _forwarder_chain = u32::parse_from_endianess(reader, Endianess::Little)?;
+            let name = u32::parse_from_endianess(reader, Endianess::Little)?;
+            let address_table = u32::parse_from_endianess(reader, Endianess::Little)?;
+
+            // The table ends with an empty descriptor
+            if name == 0 && lookup_table == 0 && address_table == 0 {
+                break;
+            }
+
+            // Some linkers leave out the lookup table, in which case the
+            // address table holds the same entries until the image is bound
+            let thunks = if lookup_table != 0 {
+                lookup_table
+            } else {
+                address_table
+            };
+            dlls.push(ImportedDll {
+                name: self.read_string(reader, name)?,
+                delay_loaded: false,
+                time_date_stamp,
+                imports: self.read_thunks(reader, thunks)?,
+            });
+            rva = add_rva(rva, 20)?;
+        }
+
+        Ok(dlls)
+    }
+
+    /// Reads the DLLs which are loaded on first use, from the delay-load
+    /// import directory.
+    pub fn delay_load_imports<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Vec<ImportedDll>, ParseError> {
+        let directory = match self.data_directory(DataDirectoryType::DelayImport) {
+            Some(directory) => directory,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut dlls = Vec::new();
+        let mut rva = directory.virtual_address;
+        loop {
+            self.seek_to_rva(reader, rva, 32)?;
+            let attributes = u32::parse_from_endianess(reader, Endianess::Little)?;
+            let name = u32::parse_from_endianess(reader, Endianess::Little)?;
+            let _module_handle = u32::parse_from_endianess(reader, Endianess::Little)?;
+            let _address_table = u32::parse_from_endianess(reader, Endianess::Little)?;
+            let name_table = u32::parse_from_endianess(reader, Endianess::Little)?;
+            let _bound_address_table = u32::parse_from_endianess(reader, Endianess::Little)?;
+            let _unload_table = u32::parse_from_endianess(reader, Endianess::Little)?;
+            let time_date_stamp = u32::parse_from_endianess(reader, Endianess::Little)?;
+
+            if name == 0 {
+                break;
+            }
+
+            let to_rva = |address: u32| {
+                if attributes & DLATTR_RVA != 0 {
+                    address
+                } else {
+                    let image_base = u64::from(self.optional_header.image_base);
+                    u64::from(address).wrapping_sub(image_base) as u32
+                }
+            };
+            dlls.push(ImportedDll {
+                name: self.read_string(reader, to_rva(name))?,
+                delay_loaded: true,
+                time_date_stamp,
+                imports: self.read_thunks(reader, to_rva(name_table))?,
+            });
+            rva = add_rva(rva, 32)?;
+        }
+
+        Ok(dlls)
+    }
+
+    /// Reads a table of thunks, which describe an import each and are as
+    /// wide as an address.
+    fn read_thunks<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        rva: u32,
+    ) -> Result<Vec<Import>, ParseError> {
+        let (size, ordinal_flag) = match self.optional_header.format {
+            PeFormat::Pe32 => (4, 1 << 31),
+            PeFormat::Pe32Plus => (8, 1 << 63),
+        };
+
+        let mut thunks = Vec::new();
+        let mut thunk_rva = rva;
+        loop {
+            self.seek_to_rva(reader, thunk_rva, size)?;
+            let thunk = match self.optional_header.format {
+                PeFormat::Pe32 => u32::parse_from_endianess(reader, Endianess::Little)?.into(),
+                PeFormat::Pe32Plus => u64::parse_from_endianess(reader, Endianess::Little)?,
+            };
+            if thunk == 0 {
+                break;
+            }
+            thunks.push(thunk);
+            thunk_rva = add_rva(thunk_rva, size)?;
+        }
+
+        thunks
+            .into_iter()
+            .map(|thunk| {
+                if thunk & ordinal_flag != 0 {
+                    return Ok(Import::Ordinal(thunk as u16));
+                }
+
+                let hint_name = (thunk & 0x7fff_ffff) as u32;
+                self.seek_to_rva(reader, hint_name, 2)?;
+                let hint = u16::parse_from_endianess(reader, Endianess::Little)?;
+                let name = self.read_string(reader, add_rva(hint_name, 2)?)?;
+                Ok(Import::Name { hint, name })
+            })
+            .collect()
+    }
+}
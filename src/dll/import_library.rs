@@ -0,0 +1,435 @@
+//! Writes import libraries in the archive format shared by the Microsoft and
+//! GNU linkers. Each export is a short import object, alongside the three
+//! objects which build the import descriptor for the DLL.
+
+use crate::dll::coff_header::IMAGE_FILE_32BIT_MACHINE;
+use crate::dll::object::IMAGE_SYM_CLASS_EXTERNAL;
+use crate::dll::object::IMAGE_SYM_CLASS_SECTION;
+use crate::dll::object::IMAGE_SYM_CLASS_STATIC;
+use crate::dll::object::IMPORT_OBJECT_CODE;
+use crate::dll::object::IMPORT_OBJECT_DATA;
+use crate::dll::object::IMPORT_OBJECT_NAME;
+use crate::dll::object::IMPORT_OBJECT_NAME_NO_PREFIX;
+use crate::dll::object::IMPORT_OBJECT_ORDINAL;
+use crate::dll::DefinitionExport;
+use crate::dll::Machine;
+use crate::dll::ModuleDefinition;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+/// `IMAGE_SCN_CNT_INITIALIZED_DATA | IMAGE_SCN_MEM_READ | IMAGE_SCN_MEM_WRITE`
+const IDATA_CHARACTERISTICS: u32 = 0xc000_0040;
+const IMAGE_SCN_ALIGN_2BYTES: u32 = 0x0020_0000;
+const IMAGE_SCN_ALIGN_4BYTES: u32 = 0x0030_0000;
+const IMAGE_SCN_ALIGN_8BYTES: u32 = 0x0040_0000;
+
+impl ModuleDefinition {
+    /// Writes an import library for linking against the DLL, such as a
+    /// `.lib` or `.dll.a` file.
+    pub fn write_import_library<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let library = Path::new(&self.name).file_stem().map_or_else(
+            || self.name.clone(),
+            |stem| stem.to_string_lossy().into_owned(),
+        );
+        let descriptor_symbol = format!("__IMPORT_DESCRIPTOR_{}", library);
+        let null_descriptor_symbol = "__NULL_IMPORT_DESCRIPTOR".to_string();
+        let null_thunk_symbol = format!("\x7f{}_NULL_THUNK_DATA", library);
+
+        let mut members = vec![
+            (
+                self.import_descriptor(
+                    &descriptor_symbol,
+                    &null_descriptor_symbol,
+                    &null_thunk_symbol,
+                ),
+                vec![descriptor_symbol],
+            ),
+            (
+                self.null_import_descriptor(&null_descriptor_symbol),
+                vec![null_descriptor_symbol],
+            ),
+            (self.null_thunk(&null_thunk_symbol), vec![null_thunk_symbol]),
+        ];
+        for export in &self.exports {
+            members.push(self.short_import(export));
+        }
+
+        Archive::new(&self.name, members).write(writer)
+    }
+
+    fn is_64_bit(&self) -> bool {
+        matches!(
+            self.machine,
+            Machine::Amd64 | Machine::Arm64 | Machine::Arm64Ec | Machine::Ia64
+        )
+    }
+
+    fn object(&self) -> CoffObject {
+        let characteristics = if self.is_64_bit() {
+            0
+        } else {
+            IMAGE_FILE_32BIT_MACHINE
+        };
+        CoffObject::new(self.machine, characteristics)
+    }
+
+    /// The object holding the import descriptor of the DLL, which refers to
+    /// the name of the DLL and the starts of its lookup and address tables.
+    fn import_descriptor(
+        &self,
+        descriptor_symbol: &str,
+        null_descriptor_symbol: &str,
+        null_thunk_symbol: &str,
+    ) -> Vec<u8> {
+        // The relocations of the name, lookup table and address table fields
+        let relocation_type = match self.machine {
+            Machine::I386 => 7,
+            Machine::Amd64 => 3,
+            _ => 2,
+        };
+        let relocations = vec![
+            (12, 2, relocation_type),
+            (0, 3, relocation_type),
+            (16, 4, relocation_type),
+        ];
+        let mut name = self.name.as_bytes().to_vec();
+        name.push(0);
+
+        let mut object = self.object();
+        object.section(".idata$2", IMAGE_SCN_ALIGN_4BYTES, vec![0; 20], relocations);
+        object.section(".idata$6", IMAGE_SCN_ALIGN_2BYTES, name, Vec::new());
+        object.symbol(descriptor_symbol, 1, IMAGE_SYM_CLASS_EXTERNAL);
+        object.symbol(".idata$2", 1, IMAGE_SYM_CLASS_SECTION);
+        object.symbol(".idata$6", 2, IMAGE_SYM_CLASS_STATIC);
+        object.symbol(".idata$4", 0, IMAGE_SYM_CLASS_SECTION);
+        object.symbol(".idata$5", 0, IMAGE_SYM_CLASS_SECTION);
+        object.symbol(null_descriptor_symbol, 0, IMAGE_SYM_CLASS_EXTERNAL);
+        object.symbol(null_thunk_symbol, 0, IMAGE_SYM_CLASS_EXTERNAL);
+        object.finish()
+    }
+
+    /// The object holding the null entry which ends the import directory,
+    /// which is shared by every import library.
+    fn null_import_descriptor(&self, null_descriptor_symbol: &str) -> Vec<u8> {
+        let mut object = self.object();
+        object.section(".idata$3", IMAGE_SCN_ALIGN_4BYTES, vec![0; 20], Vec::new());
+        object.symbol(null_descriptor_symbol, 1, IMAGE_SYM_CLASS_EXTERNAL);
+        object.finish()
+    }
+
+    /// The object holding the null entries which end the lookup and address
+    /// tables of the DLL.
+    fn null_thunk(&self, null_thunk_symbol: &str) -> Vec<u8> {
+        let (size, alignment) = if self.is_64_bit() {
+            (8, IMAGE_SCN_ALIGN_8BYTES)
+        } else {
+            (4, IMAGE_SCN_ALIGN_4BYTES)
+        };
+
+        let mut object = self.object();
+        object.section(".idata$5", alignment, vec![0; size], Vec::new());
+        object.section(".idata$4", alignment, vec![0; size], Vec::new());
+        object.symbol(null_thunk_symbol, 1, IMAGE_SYM_CLASS_EXTERNAL);
+        object.finish()
+    }
+
+    /// The short import object of an export, along with the symbols it
+    /// defines.
+    fn short_import(&self, export: &DefinitionExport) -> (Vec<u8>, Vec<String>) {
+        // 32-bit x86 symbols are decorated with an underscore, which isn't
+        // part of the exported name, apart from C++ and fastcall names
+        let decorated = self.machine == Machine::I386
+            && !export.name.starts_with('?')
+            && !export.name.starts_with('@');
+        let symbol = if decorated {
+            format!("_{}", export.name)
+        } else {
+            export.name.clone()
+        };
+        let name_type = if export.no_name {
+            IMPORT_OBJECT_ORDINAL
+        } else if decorated {
+            IMPORT_OBJECT_NAME_NO_PREFIX
+        } else {
+            IMPORT_OBJECT_NAME
+        };
+        let import_type = if export.data {
+            IMPORT_OBJECT_DATA
+        } else {
+            IMPORT_OBJECT_CODE
+        };
+
+        let size = symbol.len() + self.name.len() + 2;
+        let mut object = Vec::with_capacity(20 + size);
+        object.extend_from_slice(&0u16.to_le_bytes());
+        object.extend_from_slice(&0xffffu16.to_le_bytes());
+        object.extend_from_slice(&0u16.to_le_bytes());
+        object.extend_from_slice(&self.machine.value().to_le_bytes());
+        object.extend_from_slice(&0u32.to_le_bytes());
+        object.extend_from_slice(&(size as u32).to_le_bytes());
+        object.extend_from_slice(&(export.ordinal.unwrap_or(0) as u16).to_le_bytes());
+        object.extend_from_slice(&(import_type | name_type << 2).to_le_bytes());
+        object.extend_from_slice(symbol.as_bytes());
+        object.push(0);
+        object.extend_from_slice(self.name.as_bytes());
+        object.push(0);
+
+        // Variables can only be reached through the import address table
+        let mut symbols = vec![format!("__imp_{}", symbol)];
+        if !export.data {
+            symbols.push(symbol);
+        }
+        (object, symbols)
+    }
+}
+
+/// A COFF object file whose sections and symbols are added in order.
+struct CoffObject {
+    machine: Machine,
+    characteristics: u16,
+    sections: Vec<CoffSection>,
+    symbols: Vec<u8>,
+    symbol_count: u32,
+    strings: Vec<u8>,
+}
+
+struct CoffSection {
+    name: &'static str,
+    characteristics: u32,
+    data: Vec<u8>,
+    /// The address, symbol index and type of each relocation.
+    relocations: Vec<(u32, u32, u16)>,
+}
+
+impl CoffObject {
+    fn new(machine: Machine, characteristics: u16) -> Self {
+        Self {
+            machine,
+            characteristics,
+            sections: Vec::new(),
+            symbols: Vec::new(),
+            symbol_count: 0,
+            strings: Vec::new(),
+        }
+    }
+
+    fn section(
+        &mut self,
+        name: &'static str,
+        alignment: u32,
+        data: Vec<u8>,
+        relocations: Vec<(u32, u32, u16)>,
+    ) {
+        self.sections.push(CoffSection {
+            name,
+            characteristics: IDATA_CHARACTERISTICS | alignment,
+            data,
+            relocations,
+        });
+    }
+
+    fn symbol(&mut self, name: &str, section: i16, class: u8) {
+        // Names longer than eight bytes are kept in the string table, after
+        // the four bytes which hold its size
+        if name.len() > 8 {
+            let offset = 4 + self.strings.len() as u32;
+            self.symbols.extend_from_slice(&0u32.to_le_bytes());
+            self.symbols.extend_from_slice(&offset.to_le_bytes());
+            self.strings.extend_from_slice(name.as_bytes());
+            self.strings.push(0);
+        } else {
+            let mut short_name = [0; 8];
+            short_name[..name.len()].copy_from_slice(name.as_bytes());
+            self.symbols.extend_from_slice(&short_name);
+        }
+
+        self.symbols.extend_from_slice(&0u32.to_le_bytes());
+        self.symbols.extend_from_slice(&section.to_le_bytes());
+        self.symbols.extend_from_slice(&0u16.to_le_bytes());
+        self.symbols.push(class);
+        self.symbols.push(0);
+        self.symbol_count += 1;
+    }
+
+    fn finish(self) -> Vec<u8> {
+        // The section data and relocations follow the headers, and are
+        // followed by the symbol and string tables
+        let mut offset = 20 + 40 * self.sections.len() as u32;
+        let mut headers = Vec::new();
+        let mut contents = Vec::new();
+        for section in &self.sections {
+            let mut short_name = [0; 8];
+            short_name[..section.name.len()].copy_from_slice(section.name.as_bytes());
+            let data_offset = offset;
+            let relocations_offset = match section.relocations.len() {
+                0 => 0,
+                _ => data_offset + section.data.len() as u32,
+            };
+            offset += (section.data.len() + section.relocations.len() * 10) as u32;
+
+            headers.extend_from_slice(&short_name);
+            headers.extend_from_slice(&0u32.to_le_bytes());
+            headers.extend_from_slice(&0u32.to_le_bytes());
+            headers.extend_from_slice(&(section.data.len() as u32).to_le_bytes());
+            headers.extend_from_slice(&data_offset.to_le_bytes());
+            headers.extend_from_slice(&relocations_offset.to_le_bytes());
+            headers.extend_from_slice(&0u32.to_le_bytes());
+            headers.extend_from_slice(&(section.relocations.len() as u16).to_le_bytes());
+            headers.extend_from_slice(&0u16.to_le_bytes());
+            headers.extend_from_slice(&section.characteristics.to_le_bytes());
+
+            contents.extend_from_slice(&section.data);
+            for (address, symbol, relocation_type) in &section.relocations {
+                contents.extend_from_slice(&address.to_le_bytes());
+                contents.extend_from_slice(&symbol.to_le_bytes());
+                contents.extend_from_slice(&relocation_type.to_le_bytes());
+            }
+        }
+
+        let mut object = Vec::new();
+        object.extend_from_slice(&self.machine.value().to_le_bytes());
+        object.extend_from_slice(&(self.sections.len() as u16).to_le_bytes());
+        object.extend_from_slice(&0u32.to_le_bytes());
+        object.extend_from_slice(&offset.to_le_bytes());
+        object.extend_from_slice(&self.symbol_count.to_le_bytes());
+        object.extend_from_slice(&0u16.to_le_bytes());
+        object.extend_from_slice(&self.characteristics.to_le_bytes());
+        object.extend_from_slice(&headers);
+        object.extend_from_slice(&contents);
+        object.extend_from_slice(&self.symbols);
+        object.extend_from_slice(&(4 + self.strings.len() as u32).to_le_bytes());
+        object.extend_from_slice(&self.strings);
+        object
+    }
+}
+
+/// An archive in the Microsoft format, whose two linker members index the
+/// symbols defined by each of the other members.
+struct Archive<'a> {
+    name: &'a str,
+    members: Vec<(Vec<u8>, Vec<String>)>,
+}
+
+impl<'a> Archive<'a> {
+    fn new(name: &'a str, members: Vec<(Vec<u8>, Vec<String>)>) -> Self {
+        Self { name, members }
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        // The second linker member refers to members by a 16-bit index
+        if self.members.len() > usize::from(u16::MAX) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "too many exports for the members of an import library to be indexed",
+            ));
+        }
+
+        // Every member is named after the DLL, which needs the long names
+        // member when it doesn't fit in the header
+        let (member_name, long_names) = if self.name.len() < 16 {
+            (format!("{}/", self.name), None)
+        } else {
+            let mut long_names = self.name.as_bytes().to_vec();
+            long_names.push(0);
+            ("/0".to_string(), Some(long_names))
+        };
+
+        let symbols = self
+            .members
+            .iter()
+            .enumerate()
+            .flat_map(|(index, (_, symbols))| symbols.iter().map(move |symbol| (index, symbol)))
+            .collect::<Vec<_>>();
+        let symbol_names_size = symbols
+            .iter()
+            .map(|(_, symbol)| symbol.len() + 1)
+            .sum::<usize>();
+
+        // The offsets of the members depend on the size of the linker
+        // members before them
+        let first_linker_size = 4 + 4 * symbols.len() + symbol_names_size;
+        let second_linker_size =
+            4 + 4 * self.members.len() + 4 + 2 * symbols.len() + symbol_names_size;
+        let mut offset = 8 + member_size(first_linker_size) + member_size(second_linker_size);
+        if let Some(long_names) = &long_names {
+            offset += member_size(long_names.len());
+        }
+        let member_offsets = self
+            .members
+            .iter()
+            .map(|(data, _)| {
+                let member_offset = offset as u32;
+                offset += member_size(data.len());
+                member_offset
+            })
+            .collect::<Vec<_>>();
+
+        // The first linker member lists the symbols in member order, with
+        // big endian offsets
+        let mut first_linker = Vec::with_capacity(first_linker_size);
+        first_linker.extend_from_slice(&(symbols.len() as u32).to_be_bytes());
+        for (index, _) in &symbols {
+            first_linker.extend_from_slice(&member_offsets[*index].to_be_bytes());
+        }
+        for (_, symbol) in &symbols {
+            first_linker.extend_from_slice(symbol.as_bytes());
+            first_linker.push(0);
+        }
+
+        // The second lists them in sorted order, by the one based index of
+        // their member
+        let mut sorted_symbols = symbols.clone();
+        sorted_symbols.sort_by(|(_, a), (_, b)| a.as_bytes().cmp(b.as_bytes()));
+        let mut second_linker = Vec::with_capacity(second_linker_size);
+        second_linker.extend_from_slice(&(self.members.len() as u32).to_le_bytes());
+        for member_offset in &member_offsets {
+            second_linker.extend_from_slice(&member_offset.to_le_bytes());
+        }
+        second_linker.extend_from_slice(&(sorted_symbols.len() as u32).to_le_bytes());
+        for (index, _) in &sorted_symbols {
+            second_linker.extend_from_slice(&((*index + 1) as u16).to_le_bytes());
+        }
+        for (_, symbol) in &sorted_symbols {
+            second_linker.extend_from_slice(symbol.as_bytes());
+            second_linker.push(0);
+        }
+
+        writer.write_all(b"!<arch>\n")?;
+        write_member(writer, "/", "0", &first_linker)?;
+        write_member(writer, "/", "0", &second_linker)?;
+        if let Some(long_names) = &long_names {
+            write_member(writer, "//", "0", long_names)?;
+        }
+        for (data, _) in &self.members {
+            write_member(writer, &member_name, "644", data)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The size of a member, including its header and the padding which keeps
+/// the next member at an even offset.
+fn member_size(size: usize) -> usize {
+    60 + size + size % 2
+}
+
+fn write_member<W: Write>(writer: &mut W, name: &str, mode: &str, data: &[u8]) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`",
+        name,
+        0,
+        0,
+        0,
+        mode,
+        data.len()
+    )?;
+    writer.write_all(data)?;
+    if data.len() % 2 == 1 {
+        writer.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,245 @@
+//! The load configuration directory, which holds the settings the loader
+//! uses to harden the image, such as the security cookie and the table of
+//! valid indirect call targets for Control Flow Guard.
+
+use crate::dll::add_rva;
+use crate::dll::DataDirectoryType;
+use crate::dll::PeDescriptor;
+use crate::dll::PeFormat;
+use crate::errors::ParseError;
+use crate::parsing::read_vec;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::io::Read;
+use std::io::Seek;
+
+pub const IMAGE_GUARD_CF_INSTRUMENTED: u32 = 0x0000_0100;
+pub const IMAGE_GUARD_CFW_INSTRUMENTED: u32 = 0x0000_0200;
+pub const IMAGE_GUARD_CF_FUNCTION_TABLE_PRESENT: u32 = 0x0000_0400;
+pub const IMAGE_GUARD_SECURITY_COOKIE_UNUSED: u32 = 0x0000_0800;
+pub const IMAGE_GUARD_PROTECT_DELAYLOAD_IAT: u32 = 0x0000_1000;
+pub const IMAGE_GUARD_DELAYLOAD_IAT_IN_ITS_OWN_SECTION: u32 = 0x0000_2000;
+pub const IMAGE_GUARD_CF_EXPORT_SUPPRESSION_INFO_PRESENT: u32 = 0x0000_4000;
+pub const IMAGE_GUARD_CF_ENABLE_EXPORT_SUPPRESSION: u32 = 0x0000_8000;
+pub const IMAGE_GUARD_CF_LONGJUMP_TABLE_PRESENT: u32 = 0x0001_0000;
+pub const IMAGE_GUARD_RF_INSTRUMENTED: u32 = 0x0002_0000;
+pub const IMAGE_GUARD_RF_ENABLE: u32 = 0x0004_0000;
+pub const IMAGE_GUARD_RF_STRICT: u32 = 0x0008_0000;
+pub const IMAGE_GUARD_RETPOLINE_PRESENT: u32 = 0x0010_0000;
+pub const IMAGE_GUARD_EH_CONTINUATION_TABLE_PRESENT: u32 = 0x0040_0000;
+pub const IMAGE_GUARD_XFG_ENABLED: u32 = 0x0080_0000;
+pub const IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK: u32 = 0xf000_0000;
+pub const IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT: u32 = 28;
+
+pub const IMAGE_GUARD_FLAG_FID_SUPPRESSED: u8 = 0x01;
+pub const IMAGE_GUARD_FLAG_EXPORT_SUPPRESSED: u8 = 0x02;
+pub const IMAGE_GUARD_FLAG_FID_LANGEXCPTHANDLER: u8 = 0x04;
+pub const IMAGE_GUARD_FLAG_FID_XFG: u8 = 0x08;
+
+/// The largest load configuration which is decoded, in PE32+ images, ending
+/// with the guard flags.
+const DECODED_SIZE: usize = 148;
+
+/// The fields of the load configuration directory up to the guard flags.
+/// The directory has grown with each release of Windows, so the fields an
+/// image was built without are zero, as they are to the loader. Addresses
+/// are virtual rather than relative.
+pub struct LoadConfig {
+    pub size: u32,
+    pub time_date_stamp: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub global_flags_clear: u32,
+    pub global_flags_set: u32,
+    pub critical_section_default_timeout: u32,
+    pub decommit_free_block_threshold: u64,
+    pub decommit_total_free_threshold: u64,
+    pub lock_prefix_table: u64,
+    pub maximum_allocation_size: u64,
+    pub virtual_memory_threshold: u64,
+    pub process_affinity_mask: u64,
+    pub process_heap_flags: u32,
+    pub csd_version: u16,
+    pub dependent_load_flags: u16,
+    pub edit_list: u64,
+    pub security_cookie: u64,
+    pub se_handler_table: u64,
+    pub se_handler_count: u64,
+    pub guard_cf_check_function_pointer: u64,
+    pub guard_cf_dispatch_function_pointer: u64,
+    pub guard_cf_function_table: u64,
+    pub guard_cf_function_count: u64,
+    pub guard_flags: u32,
+}
+
+impl LoadConfig {
+    fn parse(reader: &mut &[u8], format: PeFormat) -> Result<Self, ParseError> {
+        let native = |reader: &mut &[u8]| format.parse_native(reader).map(u64::from);
+        let size = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let time_date_stamp = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let major_version = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let minor_version = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let global_flags_clear = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let global_flags_set = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let critical_section_default_timeout =
+            u32::parse_from_endianess(reader, Endianess::Little)?;
+        let decommit_free_block_threshold = native(reader)?;
+        let decommit_total_free_threshold = native(reader)?;
+        let lock_prefix_table = native(reader)?;
+        let maximum_allocation_size = native(reader)?;
+        let virtual_memory_threshold = native(reader)?;
+
+        // The heap flags come first in PE32 images
+        let (process_affinity_mask, process_heap_flags) = match format {
+            PeFormat::Pe32 => {
+                let process_heap_flags = u32::parse_from_endianess(reader, Endianess::Little)?;
+                (native(reader)?, process_heap_flags)
+            }
+            PeFormat::Pe32Plus => {
+                let process_affinity_mask = native(reader)?;
+                let process_heap_flags = u32::parse_from_endianess(reader, Endianess::Little)?;
+                (process_affinity_mask, process_heap_flags)
+            }
+        };
+
+        Ok(Self {
+            size,
+            time_date_stamp,
+            major_version,
+            minor_version,
+            global_flags_clear,
+            global_flags_set,
+            critical_section_default_timeout,
+            decommit_free_block_threshold,
+            decommit_total_free_threshold,
+            lock_prefix_table,
+            maximum_allocation_size,
+            virtual_memory_threshold,
+            process_affinity_mask,
+            process_heap_flags,
+            csd_version: u16::parse_from_endianess(reader, Endianess::Little)?,
+            dependent_load_flags: u16::parse_from_endianess(reader, Endianess::Little)?,
+            edit_list: native(reader)?,
+            security_cookie: native(reader)?,
+            se_handler_table: native(reader)?,
+            se_handler_count: native(reader)?,
+            guard_cf_check_function_pointer: native(reader)?,
+            guard_cf_dispatch_function_pointer: native(reader)?,
+            guard_cf_function_table: native(reader)?,
+            guard_cf_function_count: native(reader)?,
+            guard_flags: u32::parse_from_endianess(reader, Endianess::Little)?,
+        })
+    }
+
+    /// Checks whether the image was built with Control Flow Guard.
+    pub fn is_cf_instrumented(&self) -> bool {
+        self.guard_flags & IMAGE_GUARD_CF_INSTRUMENTED != 0
+    }
+
+    /// The number of metadata bytes following each entry of the guard
+    /// function table.
+    pub fn guard_cf_function_table_stride(&self) -> u32 {
+        (self.guard_flags & IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK)
+            >> IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT
+    }
+}
+
+/// An entry of the Control Flow Guard function table, which is a valid
+/// target of an indirect call.
+#[derive(Clone, Copy, PartialEq)]
+pub struct GuardFunction {
+    pub rva: u32,
+    /// The `IMAGE_GUARD_FLAG_*` flags of the function, if the table has
+    /// room for them.
+    pub flags: u8,
+}
+
+impl PeDescriptor {
+    /// Reads the load configuration directory, if the image has one.
+    pub fn load_config<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Option<LoadConfig>, ParseError> {
+        let directory = match self.data_directory(DataDirectoryType::LoadConfig) {
+            Some(directory) => directory,
+            None => return Ok(None),
+        };
+
+        // The directory starts with its own size, which older linkers didn't
+        // always match in the data directory
+        self.seek_to_rva(reader, directory.virtual_address, 4)?;
+        let size = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let read_size = (size as usize).clamp(4, DECODED_SIZE);
+        self.seek_to_rva(reader, directory.virtual_address, read_size as u32)?;
+        let mut data = read_vec(reader, read_size)?;
+        data.resize(DECODED_SIZE, 0);
+
+        LoadConfig::parse(&mut data.as_slice(), self.optional_header.format).map(Some)
+    }
+
+    /// Iterates over the functions in the Control Flow Guard function table.
+    pub fn guard_functions<'a, R: Read + Seek>(
+        &'a self,
+        reader: &'a mut R,
+    ) -> Result<GuardFunctions<'a, R>, ParseError> {
+        let mut guard_functions = GuardFunctions {
+            reader,
+            descriptor: self,
+            rva: 0,
+            stride: 0,
+            remaining: 0,
+        };
+
+        let load_config = match self.load_config(guard_functions.reader)? {
+            Some(load_config) => load_config,
+            None => return Ok(guard_functions),
+        };
+        if let Some(rva) = self.rva_of_va(load_config.guard_cf_function_table) {
+            guard_functions.rva = rva;
+            guard_functions.stride = 4 + load_config.guard_cf_function_table_stride();
+            guard_functions.remaining = load_config.guard_cf_function_count;
+        }
+
+        Ok(guard_functions)
+    }
+}
+
+pub struct GuardFunctions<'a, R> {
+    reader: &'a mut R,
+    descriptor: &'a PeDescriptor,
+    rva: u32,
+    stride: u32,
+    remaining: u64,
+}
+
+impl<'a, R: Read + Seek> GuardFunctions<'a, R> {
+    fn read_function(&mut self) -> Result<GuardFunction, ParseError> {
+        self.descriptor
+            .seek_to_rva(self.reader, self.rva, self.stride)?;
+        let rva = u32::parse_from_endianess(self.reader, Endianess::Little)?;
+        let flags = match self.stride {
+            4 => 0,
+            _ => u8::parse_from_endianess(self.reader, Endianess::Little)?,
+        };
+
+        self.rva = add_rva(self.rva, self.stride)?;
+        Ok(GuardFunction { rva, flags })
+    }
+}
+
+impl<'a, R: Read + Seek> Iterator for GuardFunctions<'a, R> {
+    type Item = Result<GuardFunction, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let function = self.read_function();
+        self.remaining = match function {
+            Ok(_) => self.remaining - 1,
+            Err(_) => 0,
+        };
+        Some(function)
+    }
+}
@@ -0,0 +1,593 @@
+//! The metadata of managed images, as described by ECMA-335 (partition II,
+//! chapters 22 and 24). Only the tables needed to describe the assembly, its
+//! references and its types are read.
+
+use crate::dll::digest::Hasher;
+use crate::dll::digest::Sha1;
+use crate::dll::Guid;
+use crate::errors::ParseError;
+use crate::parsing::Parse;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::fmt;
+
+pub const TD_VISIBILITY_MASK: u32 = 0x0000_0007;
+pub const TD_NOT_PUBLIC: u32 = 0x0000_0000;
+pub const TD_PUBLIC: u32 = 0x0000_0001;
+pub const TD_NESTED_PUBLIC: u32 = 0x0000_0002;
+pub const TD_INTERFACE: u32 = 0x0000_0020;
+pub const TD_ABSTRACT: u32 = 0x0000_0080;
+pub const TD_SEALED: u32 = 0x0000_0100;
+
+pub const AFA_PUBLIC_KEY: u32 = 0x0000_0001;
+pub const AFA_RETARGETABLE: u32 = 0x0000_0100;
+
+const METADATA_SIGNATURE: u32 = 0x424a_5342;
+
+const MODULE: usize = 0x00;
+const TYPE_REF: usize = 0x01;
+const TYPE_DEF: usize = 0x02;
+const FIELD: usize = 0x04;
+const METHOD_DEF: usize = 0x06;
+const PARAM: usize = 0x08;
+const INTERFACE_IMPL: usize = 0x09;
+const MEMBER_REF: usize = 0x0a;
+const DECL_SECURITY: usize = 0x0e;
+const STAND_ALONE_SIG: usize = 0x11;
+const EVENT: usize = 0x14;
+const PROPERTY: usize = 0x17;
+const MODULE_REF: usize = 0x1a;
+const TYPE_SPEC: usize = 0x1b;
+const ASSEMBLY: usize = 0x20;
+const ASSEMBLY_REF: usize = 0x23;
+const FILE: usize = 0x26;
+const EXPORTED_TYPE: usize = 0x27;
+const MANIFEST_RESOURCE: usize = 0x28;
+const GENERIC_PARAM: usize = 0x2a;
+const METHOD_SPEC: usize = 0x2b;
+const GENERIC_PARAM_CONSTRAINT: usize = 0x2c;
+
+/// Stands in for the tags of coded indexes which aren't used.
+const UNUSED: usize = usize::MAX;
+
+const TYPE_DEF_OR_REF: &[usize] = &[TYPE_DEF, TYPE_REF, TYPE_SPEC];
+const HAS_CONSTANT: &[usize] = &[FIELD, PARAM, PROPERTY];
+const HAS_CUSTOM_ATTRIBUTE: &[usize] = &[
+    METHOD_DEF,
+    FIELD,
+    TYPE_REF,
+    TYPE_DEF,
+    PARAM,
+    INTERFACE_IMPL,
+    MEMBER_REF,
+    MODULE,
+    DECL_SECURITY,
+    PROPERTY,
+    EVENT,
+    STAND_ALONE_SIG,
+    MODULE_REF,
+    TYPE_SPEC,
+    ASSEMBLY,
+    ASSEMBLY_REF,
+    FILE,
+    EXPORTED_TYPE,
+    MANIFEST_RESOURCE,
+    GENERIC_PARAM,
+    GENERIC_PARAM_CONSTRAINT,
+    METHOD_SPEC,
+];
+const HAS_FIELD_MARSHAL: &[usize] = &[FIELD, PARAM];
+const HAS_DECL_SECURITY: &[usize] = &[TYPE_DEF, METHOD_DEF, ASSEMBLY];
+const MEMBER_REF_PARENT: &[usize] = &[TYPE_DEF, TYPE_REF, MODULE_REF, METHOD_DEF, TYPE_SPEC];
+const HAS_SEMANTICS: &[usize] = &[EVENT, PROPERTY];
+const METHOD_DEF_OR_REF: &[usize] = &[METHOD_DEF, MEMBER_REF];
+const MEMBER_FORWARDED: &[usize] = &[FIELD, METHOD_DEF];
+const IMPLEMENTATION: &[usize] = &[FILE, ASSEMBLY_REF, EXPORTED_TYPE];
+const CUSTOM_ATTRIBUTE_TYPE: &[usize] = &[UNUSED, UNUSED, METHOD_DEF, MEMBER_REF, UNUSED];
+const RESOLUTION_SCOPE: &[usize] = &[MODULE, MODULE_REF, ASSEMBLY_REF, TYPE_REF];
+const TYPE_OR_METHOD_DEF: &[usize] = &[TYPE_DEF, METHOD_DEF];
+
+#[derive(Clone, Copy)]
+enum Column {
+    U16,
+    U32,
+    /// Offsets into the heaps, which are four bytes wide once a heap grows
+    /// past 64 KiB.
+    StringHeap,
+    GuidHeap,
+    BlobHeap,
+    /// An index into a single table.
+    Table(usize),
+    /// An index into one of several tables, which are told apart by the low
+    /// bits of the index.
+    Coded(&'static [usize]),
+}
+
+use self::Column::*;
+
+/// The columns of each table which the rows of later tables are found with.
+/// The one byte type of the Constant table is followed by a byte of padding.
+const SCHEMA: [&[Column]; 45] = [
+    &[U16, StringHeap, GuidHeap, GuidHeap, GuidHeap],
+    &[Coded(RESOLUTION_SCOPE), StringHeap, StringHeap],
+    &[
+        U32,
+        StringHeap,
+        StringHeap,
+        Coded(TYPE_DEF_OR_REF),
+        Table(FIELD),
+        Table(METHOD_DEF),
+    ],
+    &[Table(FIELD)],
+    &[U16, StringHeap, BlobHeap],
+    &[Table(METHOD_DEF)],
+    &[U32, U16, U16, StringHeap, BlobHeap, Table(PARAM)],
+    &[Table(PARAM)],
+    &[U16, U16, StringHeap],
+    &[Table(TYPE_DEF), Coded(TYPE_DEF_OR_REF)],
+    &[Coded(MEMBER_REF_PARENT), StringHeap, BlobHeap],
+    &[U16, Coded(HAS_CONSTANT), BlobHeap],
+    &[
+        Coded(HAS_CUSTOM_ATTRIBUTE),
+        Coded(CUSTOM_ATTRIBUTE_TYPE),
+        BlobHeap,
+    ],
+    &[Coded(HAS_FIELD_MARSHAL), BlobHeap],
+    &[U16, Coded(HAS_DECL_SECURITY), BlobHeap],
+    &[U16, U32, Table(TYPE_DEF)],
+    &[U32, Table(FIELD)],
+    &[BlobHeap],
+    &[Table(TYPE_DEF), Table(EVENT)],
+    &[Table(EVENT)],
+    &[U16, StringHeap, Coded(TYPE_DEF_OR_REF)],
+    &[Table(TYPE_DEF), Table(PROPERTY)],
+    &[Table(PROPERTY)],
+    &[U16, StringHeap, BlobHeap],
+    &[U16, Table(METHOD_DEF), Coded(HAS_SEMANTICS)],
+    &[
+        Table(TYPE_DEF),
+        Coded(METHOD_DEF_OR_REF),
+        Coded(METHOD_DEF_OR_REF),
+    ],
+    &[StringHeap],
+    &[BlobHeap],
+    &[U16, Coded(MEMBER_FORWARDED), StringHeap, Table(MODULE_REF)],
+    &[U32, Table(FIELD)],
+    &[U32, U32],
+    &[U32],
+    &[
+        U32, U16, U16, U16, U16, U32, BlobHeap, StringHeap, StringHeap,
+    ],
+    &[U32],
+    &[U32, U32, U32],
+    &[
+        U16, U16, U16, U16, U32, BlobHeap, StringHeap, StringHeap, BlobHeap,
+    ],
+    &[U32, Table(ASSEMBLY_REF)],
+    &[U32, U32, U32, Table(ASSEMBLY_REF)],
+    &[U32, StringHeap, BlobHeap],
+    &[U32, U32, StringHeap, StringHeap, Coded(IMPLEMENTATION)],
+    &[U32, U32, StringHeap, Coded(IMPLEMENTATION)],
+    &[Table(TYPE_DEF), Table(TYPE_DEF)],
+    &[U16, U16, Coded(TYPE_OR_METHOD_DEF), StringHeap],
+    &[Coded(METHOD_DEF_OR_REF), BlobHeap],
+    &[Table(GENERIC_PARAM), Coded(TYPE_DEF_OR_REF)],
+];
+
+/// The metadata of a managed image, as far as it describes the assembly.
+pub struct Metadata {
+    pub major_version: u16,
+    pub minor_version: u16,
+    /// The version of the runtime the image was built against, such as
+    /// `v4.0.30319`.
+    pub version: String,
+    pub streams: Vec<MetadataStream>,
+    /// The number of rows in each table, by table number.
+    pub row_counts: [u32; 64],
+    pub module_name: String,
+    pub module_version_id: Option<Guid>,
+    /// The assembly defined by the image, which modules that are only part
+    /// of an assembly don't have.
+    pub assembly: Option<AssemblyName>,
+    pub assembly_references: Vec<AssemblyName>,
+    pub type_definitions: Vec<TypeDefinition>,
+}
+
+pub struct MetadataStream {
+    pub name: String,
+    /// The offset of the stream from the start of the metadata.
+    pub offset: u32,
+    pub size: u32,
+}
+
+/// The identity of an assembly, which is also how it is referred to.
+pub struct AssemblyName {
+    pub name: String,
+    /// The major, minor, build and revision numbers.
+    pub version: [u16; 4],
+    pub culture: String,
+    pub flags: u32,
+    /// The public key the assembly was signed with or, for references, more
+    /// often the token of it.
+    pub public_key: Box<[u8]>,
+}
+
+impl AssemblyName {
+    /// Gets the token of the public key, which is the last eight bytes of its
+    /// SHA-1 hash in reverse order.
+    pub fn public_key_token(&self) -> Option<[u8; 8]> {
+        let mut token = [0; 8];
+        match self.public_key.len() {
+            0 => return None,
+            8 => token.copy_from_slice(&self.public_key),
+            _ => {
+                let mut hasher = Hasher::Sha1(Sha1::new());
+                hasher.update(&self.public_key);
+                let hash = hasher.finish();
+                token.copy_from_slice(&hash[hash.len() - 8..]);
+                token.reverse();
+            }
+        }
+
+        Some(token)
+    }
+}
+
+/// Formats the name as it is displayed by the runtime, for example
+/// `System.Runtime, Version=4.2.2.0, Culture=neutral, PublicKeyToken=b03f5f7f11d50a3a`.
+impl fmt::Display for AssemblyName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [major, minor, build, revision] = self.version;
+        write!(
+            f,
+            "{}, Version={}.{}.{}.{}, Culture={}, PublicKeyToken=",
+            self.name,
+            major,
+            minor,
+            build,
+            revision,
+            if self.culture.is_empty() {
+                "neutral"
+            } else {
+                &self.culture
+            }
+        )?;
+
+        match self.public_key_token() {
+            Some(token) => token.iter().try_for_each(|byte| write!(f, "{:02x}", byte)),
+            None => write!(f, "null"),
+        }
+    }
+}
+
+pub struct TypeDefinition {
+    pub flags: u32,
+    pub name: String,
+    pub namespace: String,
+}
+
+impl TypeDefinition {
+    /// Checks whether the type is visible outside of the assembly at the top
+    /// level, leaving out nested types.
+    pub fn is_public(&self) -> bool {
+        self.flags & TD_VISIBILITY_MASK == TD_PUBLIC
+    }
+
+    pub fn full_name(&self) -> String {
+        if self.namespace.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.namespace, self.name)
+        }
+    }
+}
+
+impl Metadata {
+    /// Parses the metadata root, which begins the metadata the CLI header
+    /// points to, and the streams it lists.
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        let mut reader = data;
+        let signature = u32::parse_from_endianess(&mut reader, Endianess::Little)?;
+        if signature != METADATA_SIGNATURE {
+            return Err(ParseError::InvalidValue {
+                value: signature.to_le_bytes().into(),
+            });
+        }
+
+        let major_version = u16::parse_from_endianess(&mut reader, Endianess::Little)?;
+        let minor_version = u16::parse_from_endianess(&mut reader, Endianess::Little)?;
+        u32::parse_from_endianess(&mut reader, Endianess::Little)?;
+        let version_length = u32::parse_from_endianess(&mut reader, Endianess::Little)?;
+        let version = take(&mut reader, version_length as usize)?;
+        let version = null_terminated(version);
+        u16::parse_from_endianess(&mut reader, Endianess::Little)?;
+        let stream_count = u16::parse_from_endianess(&mut reader, Endianess::Little)?;
+
+        // Stream names are padded to four bytes, including their terminator
+        let streams = (0..stream_count)
+            .map(|_| {
+                let offset = u32::parse_from_endianess(&mut reader, Endianess::Little)?;
+                let size = u32::parse_from_endianess(&mut reader, Endianess::Little)?;
+                let length = reader
+                    .iter()
+                    .position(|byte| *byte == 0)
+                    .ok_or(ParseError::MissingData)?;
+                let name = null_terminated(take(&mut reader, (length + 4) & !3)?);
+                Ok(MetadataStream { name, offset, size })
+            })
+            .collect::<Result<Vec<_>, ParseError>>()?;
+
+        let stream = |names: &[&str]| -> Result<&[u8], ParseError> {
+            match streams
+                .iter()
+                .find(|stream| names.contains(&stream.name.as_str()))
+            {
+                Some(stream) => {
+                    let start = stream.offset as usize;
+                    data.get(start..start + stream.size as usize)
+                        .ok_or(ParseError::OutOfBounds {
+                            offset: stream.offset.into(),
+                            size: stream.size.into(),
+                        })
+                }
+                None => Ok(&[]),
+            }
+        };
+        let heaps = Heaps {
+            strings: stream(&["#Strings"])?,
+            blobs: stream(&["#Blob"])?,
+            guids: stream(&["#GUID"])?,
+        };
+
+        // The uncompressed tables stream is laid out in the same way
+        let tables = Tables::parse(stream(&["#~", "#-"])?)?;
+        let module_name = match tables.row_counts[MODULE] {
+            0 => String::new(),
+            _ => heaps.string(tables.cell(MODULE, 0, 1)?)?,
+        };
+        let module_version_id = match tables.row_counts[MODULE] {
+            0 => None,
+            _ => heaps.guid(tables.cell(MODULE, 0, 2)?)?,
+        };
+
+        let assembly = match tables.row_counts[ASSEMBLY] {
+            0 => None,
+            _ => Some(AssemblyName {
+                version: [
+                    tables.cell(ASSEMBLY, 0, 1)? as u16,
+                    tables.cell(ASSEMBLY, 0, 2)? as u16,
+                    tables.cell(ASSEMBLY, 0, 3)? as u16,
+                    tables.cell(ASSEMBLY, 0, 4)? as u16,
+                ],
+                flags: tables.cell(ASSEMBLY, 0, 5)?,
+                public_key: heaps.blob(tables.cell(ASSEMBLY, 0, 6)?)?.into(),
+                name: heaps.string(tables.cell(ASSEMBLY, 0, 7)?)?,
+                culture: heaps.string(tables.cell(ASSEMBLY, 0, 8)?)?,
+            }),
+        };
+
+        let assembly_references = (0..tables.row_counts[ASSEMBLY_REF])
+            .map(|row| {
+                Ok(AssemblyName {
+                    version: [
+                        tables.cell(ASSEMBLY_REF, row, 0)? as u16,
+                        tables.cell(ASSEMBLY_REF, row, 1)? as u16,
+                        tables.cell(ASSEMBLY_REF, row, 2)? as u16,
+                        tables.cell(ASSEMBLY_REF, row, 3)? as u16,
+                    ],
+                    flags: tables.cell(ASSEMBLY_REF, row, 4)?,
+                    public_key: heaps.blob(tables.cell(ASSEMBLY_REF, row, 5)?)?.into(),
+                    name: heaps.string(tables.cell(ASSEMBLY_REF, row, 6)?)?,
+                    culture: heaps.string(tables.cell(ASSEMBLY_REF, row, 7)?)?,
+                })
+            })
+            .collect::<Result<Vec<_>, ParseError>>()?;
+
+        let type_definitions = (0..tables.row_counts[TYPE_DEF])
+            .map(|row| {
+                Ok(TypeDefinition {
+                    flags: tables.cell(TYPE_DEF, row, 0)?,
+                    name: heaps.string(tables.cell(TYPE_DEF, row, 1)?)?,
+                    namespace: heaps.string(tables.cell(TYPE_DEF, row, 2)?)?,
+                })
+            })
+            .collect::<Result<Vec<_>, ParseError>>()?;
+
+        Ok(Self {
+            major_version,
+            minor_version,
+            version,
+            streams,
+            row_counts: tables.row_counts,
+            module_name,
+            module_version_id,
+            assembly,
+            assembly_references,
+            type_definitions,
+        })
+    }
+}
+
+/// The heaps which the tables refer to by offset, or by index for GUIDs.
+struct Heaps<'a> {
+    strings: &'a [u8],
+    blobs: &'a [u8],
+    guids: &'a [u8],
+}
+
+impl<'a> Heaps<'a> {
+    fn string(&self, offset: u32) -> Result<String, ParseError> {
+        let string = self
+            .strings
+            .get(offset as usize..)
+            .ok_or(ParseError::OutOfBounds {
+                offset: offset.into(),
+                size: 1,
+            })?;
+        Ok(null_terminated(string))
+    }
+
+    /// Reads a blob, whose length is compressed into one, two or four bytes
+    /// as marked by its top bits.
+    fn blob(&self, offset: u32) -> Result<&'a [u8], ParseError> {
+        let mut blob = self.blobs.get(offset as usize..).unwrap_or_default();
+        let first = *blob.first().ok_or(ParseError::OutOfBounds {
+            offset: offset.into(),
+            size: 1,
+        })?;
+        let length = if first & 0x80 == 0 {
+            take(&mut blob, 1)?;
+            u32::from(first)
+        } else if first & 0xc0 == 0x80 {
+            u32::from(u16::parse_from_endianess(&mut blob, Endianess::Big)? & 0x3fff)
+        } else {
+            u32::parse_from_endianess(&mut blob, Endianess::Big)? & 0x1fff_ffff
+        };
+
+        take(&mut blob, length as usize)
+    }
+
+    /// Reads a GUID by its one based index, where zero means there is none.
+    fn guid(&self, index: u32) -> Result<Option<Guid>, ParseError> {
+        let offset = match (index as usize).checked_sub(1) {
+            Some(index) => index * 16,
+            None => return Ok(None),
+        };
+
+        let mut guid = self.guids.get(offset..).unwrap_or_default();
+        Guid::parse(&mut guid).map(Some)
+    }
+}
+
+/// The tables stream, whose rows are a fixed size once the sizes of the
+/// heaps and the number of rows in each table are known.
+struct Tables<'a> {
+    data: &'a [u8],
+    heap_sizes: u8,
+    row_counts: [u32; 64],
+    /// The offset of the first row of each of the known tables.
+    offsets: [usize; SCHEMA.len()],
+}
+
+impl<'a> Tables<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        let mut tables = Self {
+            data,
+            heap_sizes: 0,
+            row_counts: [0; 64],
+            offsets: [0; SCHEMA.len()],
+        };
+        if data.is_empty() {
+            return Ok(tables);
+        }
+
+        let mut reader = data;
+        take(&mut reader, 6)?;
+        tables.heap_sizes = u8::parse(&mut reader)?;
+        take(&mut reader, 1)?;
+        let valid = u64::parse_from_endianess(&mut reader, Endianess::Little)?;
+        u64::parse_from_endianess(&mut reader, Endianess::Little)?;
+        for table in 0..64 {
+            if valid & (1 << table) != 0 {
+                tables.row_counts[table] =
+                    u32::parse_from_endianess(&mut reader, Endianess::Little)?;
+            }
+        }
+
+        // Uncompressed streams may hold four bytes of extra data
+        if tables.heap_sizes & 0x40 != 0 {
+            take(&mut reader, 4)?;
+        }
+
+        // Tables past the known ones come after them, so can be ignored
+        let mut offset = data.len() - reader.len();
+        for table in 0..SCHEMA.len() {
+            tables.offsets[table] = offset;
+            offset += tables.row_size(table) * tables.row_counts[table] as usize;
+        }
+
+        Ok(tables)
+    }
+
+    fn column_size(&self, column: Column) -> usize {
+        let wide_heap = |flag: u8| if self.heap_sizes & flag != 0 { 4 } else { 2 };
+        match column {
+            U16 => 2,
+            U32 => 4,
+            StringHeap => wide_heap(0x01),
+            GuidHeap => wide_heap(0x02),
+            BlobHeap => wide_heap(0x04),
+            Table(table) => {
+                if self.row_counts[table] < 1 << 16 {
+                    2
+                } else {
+                    4
+                }
+            }
+            Coded(tables) => {
+                let tag_bits =
+                    (std::mem::size_of::<usize>() * 8) as u32 - (tables.len() - 1).leading_zeros();
+                let max_rows = tables
+                    .iter()
+                    .filter(|table| **table != UNUSED)
+                    .map(|table| self.row_counts[*table])
+                    .max()
+                    .unwrap_or(0);
+                if max_rows < 1 << (16 - tag_bits) {
+                    2
+                } else {
+                    4
+                }
+            }
+        }
+    }
+
+    fn row_size(&self, table: usize) -> usize {
+        SCHEMA[table]
+            .iter()
+            .map(|column| self.column_size(*column))
+            .sum()
+    }
+
+    /// Reads a column of a row, counting rows from zero.
+    fn cell(&self, table: usize, row: u32, column: usize) -> Result<u32, ParseError> {
+        let columns = SCHEMA[table];
+        let start = self.offsets[table]
+            + self.row_size(table) * row as usize
+            + columns[..column]
+                .iter()
+                .map(|column| self.column_size(*column))
+                .sum::<usize>();
+        let size = self.column_size(columns[column]);
+
+        let mut cell = self
+            .data
+            .get(start..start + size)
+            .ok_or(ParseError::OutOfBounds {
+                offset: start as u64,
+                size: size as u64,
+            })?;
+        match size {
+            2 => u16::parse_from_endianess(&mut cell, Endianess::Little).map(u32::from),
+            _ => u32::parse_from_endianess(&mut cell, Endianess::Little),
+        }
+    }
+}
+
+fn take<'a>(reader: &mut &'a [u8], size: usize) -> Result<&'a [u8], ParseError> {
+    if reader.len() < size {
+        return Err(ParseError::MissingData);
+    }
+
+    let (taken, rest) = reader.split_at(size);
+    *reader = rest;
+    Ok(taken)
+}
+
+fn null_terminated(data: &[u8]) -> String {
+    let length = data
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..length]).into_owned()
+}
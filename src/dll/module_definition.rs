@@ -0,0 +1,108 @@
+//! Module-definition (`.def`) files, which describe the exports of a DLL to
+//! linkers and to tools which create import libraries.
+
+use crate::dll::section_table::IMAGE_SCN_MEM_EXECUTE;
+use crate::dll::Machine;
+use crate::dll::PeDescriptor;
+use crate::errors::ParseError;
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Read;
+use std::io::Seek;
+
+pub struct ModuleDefinition {
+    /// The name of the DLL, as recorded in its export directory.
+    pub name: String,
+    pub machine: Machine,
+    pub exports: Vec<DefinitionExport>,
+}
+
+pub struct DefinitionExport {
+    pub name: String,
+    /// The ordinal of the export, which is only given for the first name of
+    /// an export with several names.
+    pub ordinal: Option<u32>,
+    /// Whether the export can only be imported by ordinal. Its name is made
+    /// up, since module-definition files must give every export a name.
+    pub no_name: bool,
+    /// Whether the export is a variable rather than a function.
+    pub data: bool,
+    pub forwarder: Option<String>,
+}
+
+impl PeDescriptor {
+    /// Describes the exports of the image as a module-definition file, if it
+    /// has an export directory.
+    pub fn module_definition<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Option<ModuleDefinition>, ParseError> {
+        let name = match self.export_directory(reader)? {
+            Some(export_directory) => export_directory.name.unwrap_or_default(),
+            None => return Ok(None),
+        };
+
+        let mut ordinals = HashSet::new();
+        let mut exports = Vec::new();
+        for export in self.exported_symbols(reader)? {
+            let export = export?;
+            let ordinal = Some(export.ordinal).filter(|ordinal| ordinals.insert(*ordinal));
+
+            // Exports from sections which can't be run are assumed to be
+            // variables, since there is no other record of them
+            let data = !export.is_forwarded()
+                && !self.section_table.entries().iter().any(|section| {
+                    section.characteristics & IMAGE_SCN_MEM_EXECUTE != 0
+                        && export.rva.wrapping_sub(section.virtual_address)
+                            < section.virtual_size.max(section.raw_data_size)
+                });
+
+            let export_ordinal = export.ordinal;
+            exports.push(DefinitionExport {
+                no_name: export.name.is_none(),
+                name: export
+                    .name
+                    .unwrap_or_else(|| format!("ordinal{}", export_ordinal)),
+                ordinal,
+                data,
+                forwarder: export.forwarder,
+            });
+        }
+
+        Ok(Some(ModuleDefinition {
+            name,
+            machine: self.coff_header.machine,
+            exports,
+        }))
+    }
+}
+
+impl fmt::Display for ModuleDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.name.contains(char::is_whitespace) {
+            writeln!(f, "LIBRARY \"{}\"", self.name)?;
+        } else {
+            writeln!(f, "LIBRARY {}", self.name)?;
+        }
+
+        writeln!(f, "EXPORTS")?;
+        for export in &self.exports {
+            write!(f, "    {}", export.name)?;
+            if let Some(forwarder) = &export.forwarder {
+                write!(f, " = {}", forwarder)?;
+            }
+            if let Some(ordinal) = export.ordinal {
+                write!(f, " @{}", ordinal)?;
+                if export.no_name {
+                    write!(f, " NONAME")?;
+                }
+            }
+            if export.data {
+                write!(f, " DATA")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,391 @@
+//! COFF object files, along with the short import objects which stand in
+//! for them in import libraries.
+
+use crate::dll::CoffHeader;
+use crate::dll::Machine;
+use crate::dll::SectionTable;
+use crate::errors::ParseError;
+use crate::parsing::check_bounds;
+use crate::parsing::read_bytes;
+use crate::parsing::read_vec;
+use crate::parsing::Parse;
+use crate::parsing::ParseFromEndianess;
+use crate::parsing::ParseSeekable;
+use crate::shared::Endianess;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+pub const IMAGE_SYM_UNDEFINED: i32 = 0;
+pub const IMAGE_SYM_ABSOLUTE: i32 = -1;
+pub const IMAGE_SYM_DEBUG: i32 = -2;
+
+pub const IMAGE_SYM_CLASS_EXTERNAL: u8 = 2;
+pub const IMAGE_SYM_CLASS_STATIC: u8 = 3;
+pub const IMAGE_SYM_CLASS_SECTION: u8 = 104;
+pub const IMAGE_SYM_CLASS_WEAK_EXTERNAL: u8 = 105;
+
+pub const IMPORT_OBJECT_CODE: u16 = 0;
+pub const IMPORT_OBJECT_DATA: u16 = 1;
+pub const IMPORT_OBJECT_CONST: u16 = 2;
+
+pub const IMPORT_OBJECT_ORDINAL: u16 = 0;
+pub const IMPORT_OBJECT_NAME: u16 = 1;
+pub const IMPORT_OBJECT_NAME_NO_PREFIX: u16 = 2;
+pub const IMPORT_OBJECT_NAME_UNDECORATE: u16 = 3;
+pub const IMPORT_OBJECT_NAME_EXPORTAS: u16 = 4;
+
+/// The size of an entry of the symbol table, which is also the size of
+/// each of the auxiliary records following a symbol.
+const SYMBOL_SIZE: u64 = 18;
+const BIG_SYMBOL_SIZE: u64 = 20;
+
+/// The size of a section table entry.
+const SECTION_SIZE: u64 = 40;
+
+/// Import objects and anonymous objects start with an unknown machine and
+/// 0xffff sections, and are told apart by the version which follows.
+const ANONYMOUS_SIGNATURE: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// The class ID of the anonymous objects written with `/bigobj`, which can
+/// hold more sections than fit in a COFF header.
+const BIG_OBJECT_CLASS_ID: [u8; 16] = [
+    0xc7, 0xa1, 0xba, 0xd1, 0xee, 0xba, 0xa9, 0x4b, 0xaf, 0x20, 0xfa, 0xf6, 0x6a, 0xa4, 0xdc, 0xb8,
+];
+
+/// Either kind of object which can be a member of a `.lib` archive.
+pub enum Object {
+    Coff(ObjectFile),
+    Import(ImportObject),
+}
+
+impl Object {
+    /// Gets the names of the external symbols which the object defines.
+    pub fn defined_symbols(&self) -> Vec<String> {
+        match self {
+            Self::Coff(object_file) => object_file
+                .symbols
+                .iter()
+                .filter(|symbol| symbol.is_external() && symbol.is_defined())
+                .map(|symbol| symbol.name.clone())
+                .collect(),
+            Self::Import(import_object) => import_object.defined_symbols(),
+        }
+    }
+}
+
+impl ParseSeekable for Object {
+    fn parse_seekable<R: Read + Seek>(reader: &mut R) -> Result<Self, ParseError> {
+        // Import objects share their signature with the other anonymous
+        // objects, such as those written with `/bigobj`, but have version 0
+        reader.seek(SeekFrom::Start(0))?;
+        let header = read_bytes::<R, 6>(reader)?;
+        if header[..4] == ANONYMOUS_SIGNATURE && header[4..] == [0, 0] {
+            reader.seek(SeekFrom::Start(0))?;
+            ImportObject::parse(reader).map(Self::Import)
+        } else {
+            ObjectFile::parse_seekable(reader).map(Self::Coff)
+        }
+    }
+}
+
+/// A COFF object file (`.obj`), as produced by compilers and assemblers.
+pub struct ObjectFile {
+    /// The header, which for big objects is converted from their own header
+    /// with a section count that saturates at `u16::MAX`.
+    pub coff_header: CoffHeader,
+    /// Whether the object was written with `/bigobj`, whose symbols can
+    /// refer to more sections.
+    pub big_object: bool,
+    pub section_table: SectionTable,
+    pub symbols: Vec<Symbol>,
+}
+
+impl ParseSeekable for ObjectFile {
+    fn parse_seekable<R: Read + Seek>(reader: &mut R) -> Result<Self, ParseError> {
+        reader.seek(SeekFrom::Start(0))?;
+        let big_object = read_bytes::<R, 4>(reader)? == ANONYMOUS_SIGNATURE;
+        reader.seek(SeekFrom::Start(0))?;
+        let (coff_header, section_count) = if big_object {
+            parse_big_object_header(reader)?
+        } else {
+            let coff_header = CoffHeader::parse(reader)?;
+            read_vec(reader, coff_header.optional_header_size as usize)?;
+            let section_count = coff_header.section_count.into();
+            (coff_header, section_count)
+        };
+        let symbol_size = if big_object {
+            BIG_SYMBOL_SIZE
+        } else {
+            SYMBOL_SIZE
+        };
+
+        let section_table_offset = reader.stream_position()?;
+        check_bounds(
+            reader,
+            section_table_offset,
+            u64::from(section_count) * SECTION_SIZE,
+        )?;
+        reader.seek(SeekFrom::Start(section_table_offset))?;
+        let mut section_table = SectionTable::parse_entries(reader, section_count)?;
+
+        // The string table immediately follows the symbol table, and starts
+        // with its own size
+        let symbol_table_offset = u64::from(coff_header.symbol_table_offset);
+        let symbol_table_size = u64::from(coff_header.symbol_count) * symbol_size;
+        let strings = if symbol_table_offset == 0 {
+            Vec::new()
+        } else {
+            check_bounds(reader, symbol_table_offset, symbol_table_size + 4)?;
+            reader.seek(SeekFrom::Start(symbol_table_offset + symbol_table_size))?;
+            let size = u32::parse_from_endianess(reader, Endianess::Little)?;
+            read_vec(reader, (size as usize).saturating_sub(4))?
+        };
+
+        let mut symbols = Vec::new();
+        if symbol_table_offset != 0 {
+            reader.seek(SeekFrom::Start(symbol_table_offset))?;
+            let mut index = 0;
+            while index < coff_header.symbol_count {
+                let symbol = Symbol::parse(reader, &strings, big_object)?;
+                read_vec(reader, usize::from(symbol.aux_count) * symbol_size as usize)?;
+                index += 1 + u32::from(symbol.aux_count);
+                symbols.push(symbol);
+            }
+        }
+
+        // Long section names are stored as their offset in the string table
+        for section in section_table.entries_mut() {
+            let name = section
+                .name
+                .strip_prefix('/')
+                .and_then(|offset| offset.parse::<u32>().ok())
+                .and_then(|offset| string_at(&strings, offset));
+            if let Some(name) = name {
+                section.name = name;
+            }
+        }
+
+        Ok(Self {
+            coff_header,
+            big_object,
+            section_table,
+            symbols,
+        })
+    }
+}
+
+/// Parses the header of an object written with `/bigobj`, returning it as a
+/// COFF header along with the full section count. Other anonymous objects,
+/// such as the intermediate code written with `/GL`, are rejected.
+fn parse_big_object_header<R: Read>(reader: &mut R) -> Result<(CoffHeader, u32), ParseError> {
+    read_bytes::<R, 4>(reader)?;
+    let version = u16::parse_from_endianess(reader, Endianess::Little)?;
+    let machine = Machine::parse(reader)?;
+    let time_date_stamp = u32::parse_from_endianess(reader, Endianess::Little)?;
+    let class_id = read_bytes::<R, 16>(reader)?;
+    if version < 2 || class_id != BIG_OBJECT_CLASS_ID {
+        return Err(ParseError::InvalidValue {
+            value: class_id.into(),
+        });
+    }
+
+    // The size of the data, flags and metadata are only used by the other
+    // anonymous objects
+    read_bytes::<R, 16>(reader)?;
+    let section_count = u32::parse_from_endianess(reader, Endianess::Little)?;
+    let symbol_table_offset = u32::parse_from_endianess(reader, Endianess::Little)?;
+    let symbol_count = u32::parse_from_endianess(reader, Endianess::Little)?;
+
+    let coff_header = CoffHeader {
+        machine,
+        section_count: section_count.min(u16::MAX.into()) as u16,
+        time_date_stamp,
+        symbol_table_offset,
+        symbol_count,
+        optional_header_size: 0,
+        characteristics: 0,
+    };
+    Ok((coff_header, section_count))
+}
+
+/// An entry of the symbol table of an object file.
+pub struct Symbol {
+    pub name: String,
+    pub value: u32,
+    /// The one based index of the section which defines the symbol, or one
+    /// of the `IMAGE_SYM_*` values.
+    pub section_number: i32,
+    pub symbol_type: u16,
+    pub storage_class: u8,
+    /// The number of auxiliary records which follow the symbol.
+    pub aux_count: u8,
+}
+
+impl Symbol {
+    /// Parses a symbol, whose section number is widened to 32 bits in big
+    /// objects.
+    fn parse<R: Read>(
+        reader: &mut R,
+        strings: &[u8],
+        big_object: bool,
+    ) -> Result<Self, ParseError> {
+        // Names longer than eight bytes are stored in the string table, with
+        // the first four bytes left zero
+        let name = read_bytes::<R, 8>(reader)?;
+        let name = if name[..4] == [0; 4] {
+            let offset = u32::from_le_bytes([name[4], name[5], name[6], name[7]]);
+            string_at(strings, offset).ok_or(ParseError::OutOfBounds {
+                offset: offset.into(),
+                size: 1,
+            })?
+        } else {
+            let length = name.iter().position(|byte| *byte == 0).unwrap_or(8);
+            String::from_utf8_lossy(&name[..length]).into_owned()
+        };
+
+        let value = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let section_number = if big_object {
+            u32::parse_from_endianess(reader, Endianess::Little)? as i32
+        } else {
+            (u16::parse_from_endianess(reader, Endianess::Little)? as i16).into()
+        };
+
+        Ok(Self {
+            name,
+            value,
+            section_number,
+            symbol_type: u16::parse_from_endianess(reader, Endianess::Little)?,
+            storage_class: u8::parse(reader)?,
+            aux_count: u8::parse(reader)?,
+        })
+    }
+
+    pub fn is_external(&self) -> bool {
+        self.storage_class == IMAGE_SYM_CLASS_EXTERNAL
+            || self.storage_class == IMAGE_SYM_CLASS_WEAK_EXTERNAL
+    }
+
+    /// Checks whether the object defines the symbol, which includes common
+    /// symbols, whose value is their size rather than an address.
+    pub fn is_defined(&self) -> bool {
+        match self.section_number {
+            IMAGE_SYM_UNDEFINED => {
+                self.value != 0 || self.storage_class == IMAGE_SYM_CLASS_WEAK_EXTERNAL
+            }
+            IMAGE_SYM_DEBUG => false,
+            _ => true,
+        }
+    }
+}
+
+/// Reads the null terminated string at an offset of the string table, which
+/// counts the four bytes of its size.
+fn string_at(strings: &[u8], offset: u32) -> Option<String> {
+    let string = strings.get((offset as usize).checked_sub(4)?..)?;
+    let length = string.iter().position(|byte| *byte == 0)?;
+    Some(String::from_utf8_lossy(&string[..length]).into_owned())
+}
+
+/// A short import object, which describes a single export of a DLL in
+/// place of the objects the linker creates from it.
+pub struct ImportObject {
+    pub version: u16,
+    pub machine: Machine,
+    pub time_date_stamp: u32,
+    /// The ordinal of the export, or the hint for the index of its name in
+    /// the export name table.
+    pub ordinal_hint: u16,
+    /// One of the `IMPORT_OBJECT_*` types, such as code or data.
+    pub import_type: u16,
+    /// One of the `IMPORT_OBJECT_*` name types, which describe how the
+    /// exported name is found from the symbol.
+    pub name_type: u16,
+    pub symbol: String,
+    pub dll: String,
+    /// The exported name, for the `IMPORT_OBJECT_NAME_EXPORTAS` name type.
+    pub export_name: Option<String>,
+}
+
+impl ImportObject {
+    /// Gets the symbols the linker creates for the import, which are the
+    /// import address table entry and, for code, a thunk which jumps to it.
+    pub fn defined_symbols(&self) -> Vec<String> {
+        let mut symbols = vec![format!("__imp_{}", self.symbol)];
+        if self.import_type == IMPORT_OBJECT_CODE {
+            symbols.push(self.symbol.clone());
+        }
+        symbols
+    }
+
+    /// Gets the name the export is imported by, or `None` for imports by
+    /// ordinal.
+    pub fn imported_name(&self) -> Option<String> {
+        let strip_prefix = |symbol: &str| {
+            symbol
+                .strip_prefix(|prefix| prefix == '?' || prefix == '@' || prefix == '_')
+                .unwrap_or(symbol)
+                .to_string()
+        };
+
+        match self.name_type {
+            IMPORT_OBJECT_ORDINAL => None,
+            IMPORT_OBJECT_NAME_NO_PREFIX => Some(strip_prefix(&self.symbol)),
+            IMPORT_OBJECT_NAME_UNDECORATE => {
+                let name = strip_prefix(&self.symbol);
+                Some(name.split('@').next().unwrap_or_default().to_string())
+            }
+            IMPORT_OBJECT_NAME_EXPORTAS => self.export_name.clone(),
+            _ => Some(self.symbol.clone()),
+        }
+    }
+}
+
+impl Parse for ImportObject {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        let signature = read_bytes::<R, 4>(reader)?;
+        if signature != ANONYMOUS_SIGNATURE {
+            return Err(ParseError::InvalidValue {
+                value: signature.into(),
+            });
+        }
+
+        let version = u16::parse_from_endianess(reader, Endianess::Little)?;
+        if version != 0 {
+            return Err(ParseError::InvalidValue {
+                value: version.to_le_bytes().into(),
+            });
+        }
+        let machine = Machine::parse(reader)?;
+        let time_date_stamp = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let data_size = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let ordinal_hint = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let types = u16::parse_from_endianess(reader, Endianess::Little)?;
+
+        // The data holds the symbol and the name of the DLL, along with the
+        // exported name for the EXPORTAS name type
+        let data = read_vec(reader, data_size as usize)?;
+        let mut strings = data
+            .split(|byte| *byte == 0)
+            .map(|string| String::from_utf8_lossy(string).into_owned());
+        let symbol = strings.next().unwrap_or_default();
+        let dll = strings.next().ok_or(ParseError::MissingData)?;
+        let name_type = (types >> 2) & 0x7;
+        let export_name = match name_type {
+            IMPORT_OBJECT_NAME_EXPORTAS => strings.next(),
+            _ => None,
+        };
+
+        Ok(Self {
+            version,
+            machine,
+            time_date_stamp,
+            ordinal_hint,
+            import_type: types & 0x3,
+            name_type,
+            symbol,
+            dll,
+            export_name,
+        })
+    }
+}
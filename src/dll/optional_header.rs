@@ -0,0 +1,270 @@
+use crate::errors::ParseError;
+use crate::parsing::Parse;
+use crate::parsing::ParseFromEndianess;
+use crate::parsing::To2Bytes;
+use crate::shared::Endianess;
+use crate::shared::NativeInteger;
+use std::io::Read;
+
+pub const IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA: u16 = 0x0020;
+pub const IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE: u16 = 0x0040;
+pub const IMAGE_DLLCHARACTERISTICS_FORCE_INTEGRITY: u16 = 0x0080;
+pub const IMAGE_DLLCHARACTERISTICS_NX_COMPAT: u16 = 0x0100;
+pub const IMAGE_DLLCHARACTERISTICS_NO_SEH: u16 = 0x0400;
+pub const IMAGE_DLLCHARACTERISTICS_APPCONTAINER: u16 = 0x1000;
+pub const IMAGE_DLLCHARACTERISTICS_GUARD_CF: u16 = 0x4000;
+
+/// The header which describes how the image is loaded. Despite the name, it
+/// is required in images and only optional in object files.
+pub struct OptionalHeader {
+    pub format: PeFormat,
+    pub major_linker_version: u8,
+    pub minor_linker_version: u8,
+    pub code_size: u32,
+    pub initialized_data_size: u32,
+    pub uninitialized_data_size: u32,
+    pub entry_point: u32,
+    pub base_of_code: u32,
+    /// Only present in PE32 images.
+    pub base_of_data: Option<u32>,
+    pub image_base: NativeInteger,
+    pub section_alignment: u32,
+    pub file_alignment: u32,
+    pub major_os_version: u16,
+    pub minor_os_version: u16,
+    pub major_image_version: u16,
+    pub minor_image_version: u16,
+    pub major_subsystem_version: u16,
+    pub minor_subsystem_version: u16,
+    pub win32_version: u32,
+    pub image_size: u32,
+    pub headers_size: u32,
+    pub checksum: u32,
+    pub subsystem: Subsystem,
+    pub dll_characteristics: u16,
+    pub stack_reserve_size: NativeInteger,
+    pub stack_commit_size: NativeInteger,
+    pub heap_reserve_size: NativeInteger,
+    pub heap_commit_size: NativeInteger,
+    pub loader_flags: u32,
+    pub data_directories: Vec<DataDirectory>,
+}
+
+impl OptionalHeader {
+    /// Gets the data directory of the provided type, if the image has one.
+    pub fn data_directory(&self, directory_type: DataDirectoryType) -> Option<DataDirectory> {
+        self.data_directories
+            .get(directory_type as usize)
+            .copied()
+            .filter(|directory| directory.virtual_address != 0 || directory.size != 0)
+    }
+}
+
+impl Parse for OptionalHeader {
+    /// Parses the header from exactly its own bytes, since the number of data
+    /// directories is limited by both the header size and the count it holds.
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        let format = PeFormat::parse(reader)?;
+        let major_linker_version = u8::parse(reader)?;
+        let minor_linker_version = u8::parse(reader)?;
+        let code_size = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let initialized_data_size = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let uninitialized_data_size = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let entry_point = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let base_of_code = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let base_of_data = match format {
+            PeFormat::Pe32 => Some(u32::parse_from_endianess(reader, Endianess::Little)?),
+            PeFormat::Pe32Plus => None,
+        };
+        let image_base = format.parse_native(reader)?;
+        let section_alignment = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let file_alignment = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let major_os_version = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let minor_os_version = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let major_image_version = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let minor_image_version = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let major_subsystem_version = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let minor_subsystem_version = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let win32_version = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let image_size = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let headers_size = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let checksum = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let subsystem = Subsystem::parse(reader)?;
+        let dll_characteristics = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let stack_reserve_size = format.parse_native(reader)?;
+        let stack_commit_size = format.parse_native(reader)?;
+        let heap_reserve_size = format.parse_native(reader)?;
+        let heap_commit_size = format.parse_native(reader)?;
+        let loader_flags = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let directory_count = u32::parse_from_endianess(reader, Endianess::Little)?;
+
+        // Some linkers claim more directories than the header has room for
+        let mut data_directories = Vec::new();
+        for _ in 0..directory_count {
+            match DataDirectory::parse(reader) {
+                Ok(directory) => data_directories.push(directory),
+                Err(ParseError::MissingData) => break,
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(Self {
+            format,
+            major_linker_version,
+            minor_linker_version,
+            code_size,
+            initialized_data_size,
+            uninitialized_data_size,
+            entry_point,
+            base_of_code,
+            base_of_data,
+            image_base,
+            section_alignment,
+            file_alignment,
+            major_os_version,
+            minor_os_version,
+            major_image_version,
+            minor_image_version,
+            major_subsystem_version,
+            minor_subsystem_version,
+            win32_version,
+            image_size,
+            headers_size,
+            checksum,
+            subsystem,
+            dll_characteristics,
+            stack_reserve_size,
+            stack_commit_size,
+            heap_reserve_size,
+            heap_commit_size,
+            loader_flags,
+            data_directories,
+        })
+    }
+}
+
+/// Whether the image uses 32-bit (PE32) or 64-bit (PE32+) addresses.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PeFormat {
+    Pe32,
+    Pe32Plus,
+}
+
+impl PeFormat {
+    /// The size of an address in this format.
+    pub(crate) fn native_size(self) -> u32 {
+        match self {
+            Self::Pe32 => 4,
+            Self::Pe32Plus => 8,
+        }
+    }
+
+    pub(crate) fn parse_native<R: Read>(self, reader: &mut R) -> Result<NativeInteger, ParseError> {
+        match self {
+            Self::Pe32 => {
+                u32::parse_from_endianess(reader, Endianess::Little).map(|res| res.into())
+            }
+            Self::Pe32Plus => {
+                u64::parse_from_endianess(reader, Endianess::Little).map(|res| res.into())
+            }
+        }
+    }
+}
+
+impl Parse for PeFormat {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        let val = u16::parse_from_endianess(reader, Endianess::Little)?;
+        Ok(match val {
+            0x010b => Self::Pe32,
+            0x020b => Self::Pe32Plus,
+            other => {
+                return Err(ParseError::InvalidValue {
+                    value: other.to_bytes(Endianess::Little).into(),
+                })
+            }
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Subsystem {
+    Unknown,
+    Native,
+    WindowsGui,
+    WindowsCui,
+    Os2Cui,
+    PosixCui,
+    NativeWindows,
+    WindowsCeGui,
+    EfiApplication,
+    EfiBootServiceDriver,
+    EfiRuntimeDriver,
+    EfiRom,
+    Xbox,
+    WindowsBootApplication,
+    Other(u16),
+}
+
+impl Parse for Subsystem {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        let val = u16::parse_from_endianess(reader, Endianess::Little)?;
+        Ok(match val {
+            0 => Self::Unknown,
+            1 => Self::Native,
+            2 => Self::WindowsGui,
+            3 => Self::WindowsCui,
+            5 => Self::Os2Cui,
+            7 => Self::PosixCui,
+            8 => Self::NativeWindows,
+            9 => Self::WindowsCeGui,
+            10 => Self::EfiApplication,
+            11 => Self::EfiBootServiceDriver,
+            12 => Self::EfiRuntimeDriver,
+            13 => Self::EfiRom,
+            14 => Self::Xbox,
+            16 => Self::WindowsBootApplication,
+            other => Self::Other(other),
+        })
+    }
+}
+
+/// The location and size of one of the tables the loader uses. The address
+/// is relative to the image base, except for the certificate table whose
+/// address is an offset within the file.
+#[derive(Clone, Copy)]
+pub struct DataDirectory {
+    pub virtual_address: u32,
+    pub size: u32,
+}
+
+impl Parse for DataDirectory {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        let virtual_address = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let size = u32::parse_from_endianess(reader, Endianess::Little)?;
+
+        Ok(Self {
+            virtual_address,
+            size,
+        })
+    }
+}
+
+/// The data directories, in the order they appear in the optional header.
+#[derive(Clone, Copy, PartialEq)]
+pub enum DataDirectoryType {
+    Export,
+    Import,
+    Resource,
+    Exception,
+    Certificate,
+    BaseRelocation,
+    Debug,
+    Architecture,
+    GlobalPointer,
+    Tls,
+    LoadConfig,
+    BoundImport,
+    ImportAddressTable,
+    DelayImport,
+    ClrRuntime,
+}
@@ -0,0 +1,122 @@
+use crate::dll::add_rva;
+use crate::dll::DataDirectoryType;
+use crate::dll::PeDescriptor;
+use crate::errors::ParseError;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::io::Read;
+use std::io::Seek;
+
+/// A block of the base relocation table, which covers a single 4K page.
+pub struct RelocationBlock {
+    pub page_rva: u32,
+    pub relocations: Vec<Relocation>,
+}
+
+/// A location which must be adjusted when the image isn't loaded at its
+/// preferred base address.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Relocation {
+    pub relocation_type: RelocationType,
+    pub rva: u32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RelocationType {
+    /// Padding, which is skipped by the loader.
+    Absolute,
+    High,
+    Low,
+    HighLow,
+    /// The high 16 bits of an address, along with the low 16 bits which are
+    /// stored in the following entry.
+    HighAdj(u16),
+    Dir64,
+    /// The types whose meaning depends on the machine, such as `ARM_MOV32`.
+    Other(u8),
+}
+
+impl PeDescriptor {
+    /// Iterates over the blocks of the base relocation table.
+    pub fn base_relocations<'a, R: Read + Seek>(&'a self, reader: &'a mut R) -> Relocations<'a, R> {
+        let (rva, end) = match self.data_directory(DataDirectoryType::BaseRelocation) {
+            Some(directory) => (
+                directory.virtual_address,
+                directory.virtual_address.saturating_add(directory.size),
+            ),
+            None => (0, 0),
+        };
+
+        Relocations {
+            reader,
+            descriptor: self,
+            rva,
+            end,
+        }
+    }
+}
+
+pub struct Relocations<'a, R> {
+    reader: &'a mut R,
+    descriptor: &'a PeDescriptor,
+    rva: u32,
+    end: u32,
+}
+
+impl<'a, R: Read + Seek> Relocations<'a, R> {
+    fn read_block(&mut self) -> Result<RelocationBlock, ParseError> {
+        self.descriptor.seek_to_rva(self.reader, self.rva, 8)?;
+        let page_rva = u32::parse_from_endianess(self.reader, Endianess::Little)?;
+        let size = u32::parse_from_endianess(self.reader, Endianess::Little)?;
+        if size < 8 || size > self.end - self.rva {
+            return Err(ParseError::InvalidValue {
+                value: size.to_le_bytes().into(),
+            });
+        }
+
+        // Each entry holds the type in its top 4 bits, and the offset into
+        // the page in the rest
+        let mut entries = (0..(size - 8) / 2)
+            .map(|_| u16::parse_from_endianess(self.reader, Endianess::Little))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter();
+        let mut relocations = Vec::with_capacity(entries.len());
+        while let Some(entry) = entries.next() {
+            let relocation_type = match (entry >> 12) as u8 {
+                0 => RelocationType::Absolute,
+                1 => RelocationType::High,
+                2 => RelocationType::Low,
+                3 => RelocationType::HighLow,
+                4 => RelocationType::HighAdj(entries.next().ok_or(ParseError::MissingData)?),
+                10 => RelocationType::Dir64,
+                other => RelocationType::Other(other),
+            };
+            relocations.push(Relocation {
+                relocation_type,
+                rva: add_rva(page_rva, u32::from(entry & 0x0fff))?,
+            });
+        }
+
+        self.rva += size;
+        Ok(RelocationBlock {
+            page_rva,
+            relocations,
+        })
+    }
+}
+
+impl<'a, R: Read + Seek> Iterator for Relocations<'a, R> {
+    type Item = Result<RelocationBlock, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.end - self.rva < 8 {
+            return None;
+        }
+
+        let block = self.read_block();
+        if block.is_err() {
+            self.rva = self.end;
+        }
+        Some(block)
+    }
+}
@@ -0,0 +1,392 @@
+//! Reads the resource tree (`.rsrc`), which holds data such as icons,
+//! dialogs and the version information shown by Windows Explorer.
+
+use crate::dll::add_rva;
+use crate::dll::DataDirectoryType;
+use crate::dll::PeDescriptor;
+use crate::errors::ParseError;
+use crate::parsing::read_vec;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::io::Read;
+use std::io::Seek;
+
+pub const RT_CURSOR: u32 = 1;
+pub const RT_BITMAP: u32 = 2;
+pub const RT_ICON: u32 = 3;
+pub const RT_MENU: u32 = 4;
+pub const RT_DIALOG: u32 = 5;
+pub const RT_STRING: u32 = 6;
+pub const RT_FONTDIR: u32 = 7;
+pub const RT_FONT: u32 = 8;
+pub const RT_ACCELERATOR: u32 = 9;
+pub const RT_RCDATA: u32 = 10;
+pub const RT_MESSAGETABLE: u32 = 11;
+pub const RT_GROUP_CURSOR: u32 = 12;
+pub const RT_GROUP_ICON: u32 = 14;
+pub const RT_VERSION: u32 = 16;
+pub const RT_DLGINCLUDE: u32 = 17;
+pub const RT_PLUGPLAY: u32 = 19;
+pub const RT_VXD: u32 = 20;
+pub const RT_ANICURSOR: u32 = 21;
+pub const RT_ANIICON: u32 = 22;
+pub const RT_HTML: u32 = 23;
+pub const RT_MANIFEST: u32 = 24;
+
+/// The signature which starts `VS_FIXEDFILEINFO`.
+const FIXED_FILE_INFO_SIGNATURE: u32 = 0xfeef_04bd;
+
+/// A leaf of the resource tree, which is found by its type, name and
+/// language in turn.
+pub struct Resource {
+    pub resource_type: ResourceId,
+    pub name: ResourceId,
+    pub language: ResourceId,
+    /// The address of the data, relative to the image base.
+    pub rva: u32,
+    pub size: u32,
+    pub code_page: u32,
+}
+
+/// Identifies a level of the resource tree, either by number (e.g.
+/// `RT_VERSION` for a type) or by name.
+#[derive(Clone, PartialEq)]
+pub enum ResourceId {
+    Id(u32),
+    Name(String),
+}
+
+/// The decoded `VS_VERSIONINFO` resource.
+pub struct VersionInfo {
+    pub fixed: Option<FixedFileInfo>,
+    /// The text values, such as `CompanyName`, for each language.
+    pub strings: Vec<VersionStrings>,
+    /// The language and code page pairs which the file supports.
+    pub translations: Vec<(u16, u16)>,
+}
+
+impl VersionInfo {
+    /// Finds a text value in the first language which has it.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings.iter().find_map(|strings| {
+            strings
+                .values
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.as_str())
+        })
+    }
+}
+
+/// The language independent part of the version information. Versions are
+/// held as their four parts, most significant first.
+pub struct FixedFileInfo {
+    pub file_version: [u16; 4],
+    pub product_version: [u16; 4],
+    pub file_flags_mask: u32,
+    pub file_flags: u32,
+    pub file_os: u32,
+    pub file_type: u32,
+    pub file_subtype: u32,
+    pub file_date: u64,
+}
+
+/// The text values of the version information in one language.
+pub struct VersionStrings {
+    /// The language and code page, written as eight hex digits (e.g.
+    /// `040904b0` for US English in Unicode).
+    pub key: String,
+    pub values: Vec<(String, String)>,
+}
+
+impl PeDescriptor {
+    /// Reads every leaf of the resource tree.
+    pub fn resources<R: Read + Seek>(&self, reader: &mut R) -> Result<Vec<Resource>, ParseError> {
+        let directory = match self.data_directory(DataDirectoryType::Resource) {
+            Some(directory) => directory,
+            None => return Ok(Vec::new()),
+        };
+
+        // Offsets within the tree are relative to its root
+        let root = directory.virtual_address;
+        let mut resources = Vec::new();
+        for (resource_type, names) in self.read_resource_directory(reader, root, 0)? {
+            let names = match names {
+                ResourceEntry::Directory(offset) => offset,
+                ResourceEntry::Data(_) => continue,
+            };
+            for (name, languages) in self.read_resource_directory(reader, root, names)? {
+                let languages = match languages {
+                    ResourceEntry::Directory(offset) => offset,
+                    ResourceEntry::Data(_) => continue,
+                };
+                for (language, data) in self.read_resource_directory(reader, root, languages)? {
+                    let data = match data {
+                        ResourceEntry::Data(offset) => offset,
+                        ResourceEntry::Directory(_) => continue,
+                    };
+
+                    self.seek_to_rva(reader, add_rva(root, data)?, 16)?;
+                    let rva = u32::parse_from_endianess(reader, Endianess::Little)?;
+                    let size = u32::parse_from_endianess(reader, Endianess::Little)?;
+                    let code_page = u32::parse_from_endianess(reader, Endianess::Little)?;
+                    resources.push(Resource {
+                        resource_type: resource_type.clone(),
+                        name: name.clone(),
+                        language,
+                        rva,
+                        size,
+                        code_page,
+                    });
+                }
+            }
+        }
+
+        Ok(resources)
+    }
+
+    /// Reads the contents of a resource.
+    pub fn resource_data<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        resource: &Resource,
+    ) -> Result<Vec<u8>, ParseError> {
+        self.seek_to_rva(reader, resource.rva, resource.size)?;
+        read_vec(reader, resource.size as usize)
+    }
+
+    /// Reads and decodes the version information, if the image has any.
+    pub fn version_info<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Option<VersionInfo>, ParseError> {
+        let resource = self
+            .resources(reader)?
+            .into_iter()
+            .find(|resource| resource.resource_type == ResourceId::Id(RT_VERSION));
+        let resource = match resource {
+            Some(resource) => resource,
+            None => return Ok(None),
+        };
+
+        let data = self.resource_data(reader, &resource)?;
+        let root = Block::parse(&data, 0)?;
+        let fixed = match root.value {
+            Some(value) if value.len() >= 52 => parse_fixed_file_info(value)?,
+            _ => None,
+        };
+
+        let mut info = VersionInfo {
+            fixed,
+            strings: Vec::new(),
+            translations: Vec::new(),
+        };
+        for child in root.children(&data)? {
+            match child.key.as_str() {
+                "StringFileInfo" => {
+                    for table in child.children(&data)? {
+                        let values = table
+                            .children(&data)?
+                            .into_iter()
+                            .map(|string| {
+                                let value = string.value.map(utf16_string).unwrap_or_default();
+                                (string.key, value)
+                            })
+                            .collect();
+                        info.strings.push(VersionStrings {
+                            key: table.key,
+                            values,
+                        });
+                    }
+                }
+                "VarFileInfo" => {
+                    for var in child.children(&data)? {
+                        let value = var.value.unwrap_or_default();
+                        info.translations.extend(value.chunks_exact(4).map(|pair| {
+                            (
+                                u16::from_le_bytes([pair[0], pair[1]]),
+                                u16::from_le_bytes([pair[2], pair[3]]),
+                            )
+                        }));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Some(info))
+    }
+
+    /// Reads the entries of the directory at `offset` within the tree.
+    fn read_resource_directory<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        root: u32,
+        offset: u32,
+    ) -> Result<Vec<(ResourceId, ResourceEntry)>, ParseError> {
+        let directory = add_rva(root, offset)?;
+        self.seek_to_rva(reader, directory, 16)?;
+        let _characteristics = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let _time_date_stamp = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let _major_version = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let _minor_version = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let named_count = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let id_count = u16::parse_from_endianess(reader, Endianess::Little)?;
+
+        let count = u32::from(named_count) + u32::from(id_count);
+        self.seek_to_rva(reader, add_rva(directory, 16)?, count * 8)?;
+        let raw_entries = (0..count)
+            .map(|_| {
+                let name = u32::parse_from_endianess(reader, Endianess::Little)?;
+                let data = u32::parse_from_endianess(reader, Endianess::Little)?;
+                Ok((name, data))
+            })
+            .collect::<Result<Vec<_>, ParseError>>()?;
+
+        // The high bit tells names from numbers and directories from data
+        const HIGH_BIT: u32 = 0x8000_0000;
+        raw_entries
+            .into_iter()
+            .map(|(name, data)| {
+                let id = if name & HIGH_BIT != 0 {
+                    let name_rva = add_rva(root, name & !HIGH_BIT)?;
+                    self.seek_to_rva(reader, name_rva, 2)?;
+                    let length = u16::parse_from_endianess(reader, Endianess::Little)?;
+                    let name = read_vec(reader, usize::from(length) * 2)?;
+                    ResourceId::Name(utf16_string(&name))
+                } else {
+                    ResourceId::Id(name)
+                };
+                let entry = if data & HIGH_BIT != 0 {
+                    ResourceEntry::Directory(data & !HIGH_BIT)
+                } else {
+                    ResourceEntry::Data(data)
+                };
+                Ok((id, entry))
+            })
+            .collect()
+    }
+}
+
+/// What an entry of a resource directory points to, as an offset within
+/// the tree.
+enum ResourceEntry {
+    Directory(u32),
+    Data(u32),
+}
+
+/// A node of the version information, which is made of a key, a value and
+/// child nodes that each start on a 32-bit boundary.
+struct Block<'a> {
+    key: String,
+    value: Option<&'a [u8]>,
+    children_start: usize,
+    end: usize,
+}
+
+impl<'a> Block<'a> {
+    fn parse(data: &'a [u8], start: usize) -> Result<Self, ParseError> {
+        let word = |offset: usize| -> Result<usize, ParseError> {
+            let bytes = data
+                .get(offset..offset + 2)
+                .ok_or(ParseError::MissingData)?;
+            Ok(usize::from(u16::from_le_bytes([bytes[0], bytes[1]])))
+        };
+        let length = word(start)?;
+        let value_length = word(start + 2)?;
+        let is_text = word(start + 4)? == 1;
+        if length < 6 {
+            return Err(ParseError::InvalidValue {
+                value: (length as u16).to_le_bytes().into(),
+            });
+        }
+        let end = start + length;
+        if end > data.len() {
+            return Err(ParseError::MissingData);
+        }
+
+        let key_start = start + 6;
+        let key_units = data[key_start..end]
+            .chunks_exact(2)
+            .take_while(|unit| unit != &[0, 0])
+            .count();
+        let key = utf16_string(&data[key_start..key_start + key_units * 2]);
+        let value_start = align(key_start + key_units * 2 + 2).min(end);
+
+        // Text lengths are counted in characters, but are unreliable enough
+        // that text values are taken to run to the end of the block
+        let value_end = if is_text && value_length > 0 {
+            end
+        } else {
+            (value_start + value_length).min(end)
+        };
+        let value = if value_length > 0 {
+            Some(&data[value_start..value_end])
+        } else {
+            None
+        };
+
+        Ok(Self {
+            key,
+            value,
+            children_start: align(value_end),
+            end,
+        })
+    }
+
+    fn children(&self, data: &'a [u8]) -> Result<Vec<Block<'a>>, ParseError> {
+        let mut children = Vec::new();
+        let mut offset = self.children_start;
+        while offset + 6 <= self.end {
+            let child = Block::parse(&data[..self.end], offset)?;
+            offset = align(child.end);
+            children.push(child);
+        }
+
+        Ok(children)
+    }
+}
+
+fn parse_fixed_file_info(value: &[u8]) -> Result<Option<FixedFileInfo>, ParseError> {
+    let reader = &mut &value[..];
+    let mut field = || u32::parse_from_endianess(reader, Endianess::Little);
+    if field()? != FIXED_FILE_INFO_SIGNATURE {
+        return Ok(None);
+    }
+    let _struct_version = field()?;
+    let split = |high: u32, low: u32| {
+        [
+            (high >> 16) as u16,
+            high as u16,
+            (low >> 16) as u16,
+            low as u16,
+        ]
+    };
+    let file_version = split(field()?, field()?);
+    let product_version = split(field()?, field()?);
+
+    Ok(Some(FixedFileInfo {
+        file_version,
+        product_version,
+        file_flags_mask: field()?,
+        file_flags: field()?,
+        file_os: field()?,
+        file_type: field()?,
+        file_subtype: field()?,
+        file_date: (u64::from(field()?) << 32) | u64::from(field()?),
+    }))
+}
+
+/// Decodes UTF-16 text, stopping at the first null character.
+fn utf16_string(bytes: &[u8]) -> String {
+    let units = bytes
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .take_while(|unit| *unit != 0)
+        .collect::<Vec<_>>();
+    String::from_utf16_lossy(&units)
+}
+
+fn align(offset: usize) -> usize {
+    (offset + 3) & !3
+}
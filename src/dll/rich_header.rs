@@ -0,0 +1,117 @@
+//! The undocumented "Rich" header which the Microsoft linker places between
+//! the DOS stub and the PE signature. It counts the objects each tool of the
+//! toolchain contributed, masked with a key which doubles as a checksum.
+
+use crate::dll::PeDescriptor;
+use crate::errors::ParseError;
+use crate::parsing::read_vec;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+const RICH_SIGNATURE: u32 = u32::from_le_bytes(*b"Rich");
+const DANS_SIGNATURE: u32 = u32::from_le_bytes(*b"DanS");
+
+pub struct RichHeader {
+    /// The offset of the start of the header within the file.
+    pub offset: u32,
+    /// The key the header was masked with, which is also the checksum of the
+    /// DOS header, the DOS stub and the entries.
+    pub key: u32,
+    /// The checksum computed from the image, which should match the key.
+    pub checksum: u32,
+    pub entries: Vec<RichEntry>,
+}
+
+impl RichHeader {
+    /// Checks whether the checksum matches the key, which it won't if the
+    /// header or the DOS stub was changed after linking.
+    pub fn is_valid(&self) -> bool {
+        self.checksum == self.key
+    }
+
+    /// Decodes the header from the bytes before the PE signature.
+    fn parse(data: &[u8]) -> Option<Self> {
+        let words = data
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+            .collect::<Vec<_>>();
+
+        // The end is marked in plain text, followed by the key, while the
+        // start is only recognizable once unmasked
+        let end = words.iter().rposition(|word| *word == RICH_SIGNATURE)?;
+        let key = *words.get(end + 1)?;
+        let start = words[..end]
+            .iter()
+            .rposition(|word| word ^ key == DANS_SIGNATURE)?;
+
+        // The signature at the start is followed by three words of padding
+        let entries = words
+            .get(start + 4..end)?
+            .chunks_exact(2)
+            .map(|entry| RichEntry::new(entry[0] ^ key, entry[1] ^ key))
+            .collect::<Vec<_>>();
+
+        let offset = start as u32 * 4;
+        let checksum = Self::checksum(&data[..offset as usize], &entries);
+        Some(Self {
+            offset,
+            key,
+            checksum,
+            entries,
+        })
+    }
+
+    fn checksum(header: &[u8], entries: &[RichEntry]) -> u32 {
+        // The offset of the PE signature is left out, since it is written
+        // after the header
+        let header_checksum = header
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !(0x3c..0x40).contains(index))
+            .fold(header.len() as u32, |checksum, (index, byte)| {
+                checksum.wrapping_add(u32::from(*byte).rotate_left(index as u32))
+            });
+
+        entries.iter().fold(header_checksum, |checksum, entry| {
+            checksum.wrapping_add(entry.comp_id().rotate_left(entry.count))
+        })
+    }
+}
+
+/// The number of objects built by one version of one tool.
+#[derive(Clone, Copy, PartialEq)]
+pub struct RichEntry {
+    /// The tool, such as the C compiler or the linker, of a particular
+    /// release of Visual Studio.
+    pub product_id: u16,
+    /// The build number of the tool.
+    pub build: u16,
+    pub count: u32,
+}
+
+impl RichEntry {
+    fn new(comp_id: u32, count: u32) -> Self {
+        Self {
+            product_id: (comp_id >> 16) as u16,
+            build: comp_id as u16,
+            count,
+        }
+    }
+
+    fn comp_id(&self) -> u32 {
+        u32::from(self.product_id) << 16 | u32::from(self.build)
+    }
+}
+
+impl PeDescriptor {
+    /// Reads the Rich header, if the image has one.
+    pub fn rich_header<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Option<RichHeader>, ParseError> {
+        reader.seek(SeekFrom::Start(0))?;
+        let data = read_vec(reader, self.dos_header.new_header_offset as usize)?;
+        Ok(RichHeader::parse(&data))
+    }
+}
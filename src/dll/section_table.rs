@@ -0,0 +1,91 @@
+use crate::errors::ParseError;
+use crate::parsing::read_bytes;
+use crate::parsing::Parse;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::io::Read;
+
+pub const IMAGE_SCN_CNT_CODE: u32 = 0x0000_0020;
+pub const IMAGE_SCN_CNT_INITIALIZED_DATA: u32 = 0x0000_0040;
+pub const IMAGE_SCN_CNT_UNINITIALIZED_DATA: u32 = 0x0000_0080;
+pub const IMAGE_SCN_MEM_DISCARDABLE: u32 = 0x0200_0000;
+pub const IMAGE_SCN_MEM_SHARED: u32 = 0x1000_0000;
+pub const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+pub const IMAGE_SCN_MEM_READ: u32 = 0x4000_0000;
+pub const IMAGE_SCN_MEM_WRITE: u32 = 0x8000_0000;
+
+pub struct SectionTable {
+    entries: Vec<SectionTableEntry>,
+}
+
+impl SectionTable {
+    /// Parses the `count` entries which follow the optional header.
+    pub(crate) fn parse_entries<R: Read>(reader: &mut R, count: u32) -> Result<Self, ParseError> {
+        let entries = (0..count)
+            .map(|_| SectionTableEntry::parse(reader))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { entries })
+    }
+
+    pub fn entries(&self) -> &[SectionTableEntry] {
+        &self.entries
+    }
+
+    pub(crate) fn entries_mut(&mut self) -> &mut [SectionTableEntry] {
+        &mut self.entries
+    }
+
+    /// Finds the first section with the provided name.
+    pub fn by_name(&self, name: &str) -> Option<&SectionTableEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+}
+
+pub struct SectionTableEntry {
+    /// The name of the section. Object files refer to names longer than
+    /// eight bytes by their offset in the string table (e.g. `/4`).
+    pub name: String,
+    pub virtual_size: u32,
+    pub virtual_address: u32,
+    pub raw_data_size: u32,
+    pub raw_data_offset: u32,
+    pub relocations_offset: u32,
+    pub line_numbers_offset: u32,
+    pub relocation_count: u16,
+    pub line_number_count: u16,
+    pub characteristics: u32,
+}
+
+impl Parse for SectionTableEntry {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        let name = read_bytes::<R, 8>(reader)?;
+        let name_length = name
+            .iter()
+            .position(|byte| *byte == 0)
+            .unwrap_or(name.len());
+        let name = String::from_utf8_lossy(&name[..name_length]).into_owned();
+        let virtual_size = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let virtual_address = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let raw_data_size = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let raw_data_offset = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let relocations_offset = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let line_numbers_offset = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let relocation_count = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let line_number_count = u16::parse_from_endianess(reader, Endianess::Little)?;
+        let characteristics = u32::parse_from_endianess(reader, Endianess::Little)?;
+
+        Ok(Self {
+            name,
+            virtual_size,
+            virtual_address,
+            raw_data_size,
+            raw_data_offset,
+            relocations_offset,
+            line_numbers_offset,
+            relocation_count,
+            line_number_count,
+            characteristics,
+        })
+    }
+}
@@ -0,0 +1,101 @@
+use crate::dll::add_rva;
+use crate::dll::DataDirectoryType;
+use crate::dll::PeDescriptor;
+use crate::errors::ParseError;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::io::Read;
+use std::io::Seek;
+
+/// The thread local storage directory. Unlike most of the image, it holds
+/// virtual addresses rather than relative ones.
+pub struct TlsDirectory {
+    pub raw_data_start: u64,
+    pub raw_data_end: u64,
+    pub index_address: u64,
+    pub callbacks_address: u64,
+    pub zero_fill_size: u32,
+    pub characteristics: u32,
+}
+
+impl PeDescriptor {
+    /// Reads the thread local storage directory, if the image has one.
+    pub fn tls_directory<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Option<TlsDirectory>, ParseError> {
+        let directory = match self.data_directory(DataDirectoryType::Tls) {
+            Some(directory) => directory,
+            None => return Ok(None),
+        };
+
+        let format = self.optional_header.format;
+        let size = 4 * format.native_size() + 8;
+        self.seek_to_rva(reader, directory.virtual_address, size)?;
+        let raw_data_start = format.parse_native(reader)?.into();
+        let raw_data_end = format.parse_native(reader)?.into();
+        let index_address = format.parse_native(reader)?.into();
+        let callbacks_address = format.parse_native(reader)?.into();
+        let zero_fill_size = u32::parse_from_endianess(reader, Endianess::Little)?;
+        let characteristics = u32::parse_from_endianess(reader, Endianess::Little)?;
+
+        Ok(Some(TlsDirectory {
+            raw_data_start,
+            raw_data_end,
+            index_address,
+            callbacks_address,
+            zero_fill_size,
+            characteristics,
+        }))
+    }
+
+    /// Iterates over the virtual addresses of the TLS callbacks, which are
+    /// run before the entry point.
+    pub fn tls_callbacks<'a, R: Read + Seek>(
+        &'a self,
+        reader: &'a mut R,
+    ) -> Result<TlsCallbacks<'a, R>, ParseError> {
+        let callbacks_address = match self.tls_directory(reader)? {
+            Some(tls_directory) => tls_directory.callbacks_address,
+            None => 0,
+        };
+
+        Ok(TlsCallbacks {
+            rva: self.rva_of_va(callbacks_address),
+            reader,
+            descriptor: self,
+        })
+    }
+}
+
+pub struct TlsCallbacks<'a, R> {
+    reader: &'a mut R,
+    descriptor: &'a PeDescriptor,
+    /// The address of the next entry, which is `None` once the null entry
+    /// ending the array has been reached.
+    rva: Option<u32>,
+}
+
+impl<'a, R: Read + Seek> TlsCallbacks<'a, R> {
+    fn read_callback(&mut self, rva: u32) -> Result<Option<u64>, ParseError> {
+        let format = self.descriptor.optional_header.format;
+        let size = format.native_size();
+        self.descriptor.seek_to_rva(self.reader, rva, size)?;
+        let callback = u64::from(format.parse_native(self.reader)?);
+        if callback == 0 {
+            return Ok(None);
+        }
+
+        self.rva = Some(add_rva(rva, size)?);
+        Ok(Some(callback))
+    }
+}
+
+impl<'a, R: Read + Seek> Iterator for TlsCallbacks<'a, R> {
+    type Item = Result<u64, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rva = self.rva.take()?;
+        self.read_callback(rva).transpose()
+    }
+}
@@ -1,21 +1,71 @@
 //! Types for representing an ELF object file.
 //! Reference pulled from [here](https://en.wikipedia.org/wiki/Executable_and_Linkable_Format#File_header)
 
+mod dependencies;
+mod dynamic;
 mod file_header;
+mod hash;
+mod ld_cache;
+mod ld_so_conf;
 mod parsing;
 mod program_header;
 mod section_header;
+mod string_table;
+mod symbol;
+mod version;
 
+pub use self::dependencies::Dependency;
+pub use self::dependencies::DependencyResolver;
+pub use self::dynamic::DynamicEntry;
+pub use self::dynamic::DF_1_DIRECT;
+pub use self::dynamic::DF_1_GLOBAL;
+pub use self::dynamic::DF_1_GROUP;
+pub use self::dynamic::DF_1_INITFIRST;
+pub use self::dynamic::DF_1_INTERPOSE;
+pub use self::dynamic::DF_1_LOADFLTR;
+pub use self::dynamic::DF_1_NODEFLIB;
+pub use self::dynamic::DF_1_NODELETE;
+pub use self::dynamic::DF_1_NODUMP;
+pub use self::dynamic::DF_1_NOOPEN;
+pub use self::dynamic::DF_1_NOW;
+pub use self::dynamic::DF_1_ORIGIN;
+pub use self::dynamic::DF_1_PIE;
+pub use self::dynamic::DF_BIND_NOW;
+pub use self::dynamic::DF_ORIGIN;
+pub use self::dynamic::DF_STATIC_TLS;
+pub use self::dynamic::DF_SYMBOLIC;
+pub use self::dynamic::DF_TEXTREL;
 pub use self::file_header::FileHeader;
+pub use self::hash::gnu_hash;
+pub use self::hash::sysv_hash;
+pub use self::ld_cache::CacheArchitecture;
+pub use self::ld_cache::CacheEntry;
+pub use self::ld_cache::LdCache;
+pub use self::ld_cache::SystemLibraries;
+pub use self::ld_so_conf::LdSoConf;
 use self::parsing::ParseFromWorkingContext;
 pub use self::program_header::ProgramHeader;
 pub use self::section_header::SectionHeader;
+pub use self::string_table::StringTable;
+pub use self::symbol::Binding;
+pub use self::symbol::Symbol;
+pub use self::symbol::SymbolType;
+pub use self::symbol::Symbols;
+pub use self::symbol::Visibility;
+pub use self::version::RequiredVersion;
+pub use self::version::SymbolVersion;
+pub use self::version::VersionDefinition;
+pub use self::version::VersionRequirement;
+use self::version::VersionTable;
 use crate::errors::ParseError;
 use crate::parsing::Parse;
+use crate::parsing::ParseSeekable;
 use crate::parsing::ToByte;
 use crate::shared::Endianess;
 use crate::shared::NativeInteger;
 use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
 
 pub struct ElfDescriptor {
     pub file_header: FileHeader,
@@ -47,7 +97,186 @@ impl Parse for ElfDescriptor {
     }
 }
 
-#[derive(Clone, Copy)]
+impl ParseSeekable for ElfDescriptor {
+    fn parse_seekable<R: Read + Seek>(reader: &mut R) -> Result<Self, ParseError> {
+        reader.seek(SeekFrom::Start(0))?;
+        let file_header = FileHeader::parse(reader)?;
+        let program_header = ProgramHeader::parse_table(reader, &file_header)?;
+        let section_header = SectionHeader::parse_table(reader, &file_header)?;
+
+        let mut descriptor = Self {
+            file_header,
+            program_header,
+            section_header,
+        };
+        descriptor.load_section_names(reader)?;
+
+        Ok(descriptor)
+    }
+}
+
+impl ElfDescriptor {
+    /// Loads the section names string table and names each of the sections.
+    pub fn load_section_names<R: Read + Seek>(&mut self, reader: &mut R) -> Result<(), ParseError> {
+        // Section index 0 holds the real index when it doesn't fit in the file header
+        const SHN_UNDEF: u16 = 0x0000;
+        const SHN_XINDEX: u16 = 0xffff;
+        let index = match self.file_header.section_names_entry_index {
+            _ if self.section_header.entries().is_empty() => return Ok(()),
+            SHN_UNDEF => return Ok(()),
+            SHN_XINDEX => match self.section_header.entries().first() {
+                Some(entry) => entry.associated_section as usize,
+                None => return Err(ParseError::MissingData),
+            },
+            index => index as usize,
+        };
+
+        let names_section = self
+            .section_header
+            .entries()
+            .get(index)
+            .ok_or(ParseError::MissingData)?;
+        let names = StringTable::parse_from_section(reader, names_section)?;
+        self.section_header.resolve_names(&names);
+
+        Ok(())
+    }
+
+    /// Converts a virtual address into an offset within the file, using the
+    /// loadable segments or, for files without any, the section addresses.
+    pub fn offset_of_address(&self, address: u64) -> Option<u64> {
+        let segments = self
+            .program_header
+            .entries()
+            .iter()
+            .filter(|entry| matches!(entry.entry_type, program_header::EntryType::Load))
+            .map(|entry| {
+                let start = u64::from(entry.virtual_address);
+                (
+                    start,
+                    u64::from(entry.segment_file_size),
+                    entry.offset.into(),
+                )
+            });
+        let sections = self
+            .section_header
+            .entries()
+            .iter()
+            .filter(|entry| {
+                !matches!(
+                    entry.entry_type,
+                    section_header::EntryType::ProgramSpaceNoData
+                )
+            })
+            .map(|entry| (entry.addr.into(), entry.size.into(), entry.offset.into()));
+
+        segments
+            .chain(sections)
+            .find(|(start, size, _)| *start <= address && address - *start < *size)
+            .map(|(start, _, offset): (u64, u64, u64)| offset + (address - start))
+    }
+
+    /// Iterates over the full symbol table (`.symtab`), which is absent from
+    /// stripped files.
+    pub fn symbols<'a, R: Read + Seek>(
+        &self,
+        reader: &'a mut R,
+    ) -> Result<Symbols<'a, R>, ParseError> {
+        let section = self
+            .section_header
+            .entries()
+            .iter()
+            .find(|entry| matches!(entry.entry_type, section_header::EntryType::SymbolTable));
+        Symbols::new(
+            reader,
+            &self.file_header,
+            &self.section_header,
+            section,
+            None,
+        )
+    }
+
+    /// Iterates over the symbols used by the dynamic linker (`.dynsym`),
+    /// along with their versions.
+    pub fn dynamic_symbols<'a, R: Read + Seek>(
+        &self,
+        reader: &'a mut R,
+    ) -> Result<Symbols<'a, R>, ParseError> {
+        let section = self.section_header.entries().iter().find(|entry| {
+            matches!(
+                entry.entry_type,
+                section_header::EntryType::DynamicLinkerSymbolTable
+            )
+        });
+        let versions = VersionTable::parse(reader, &self.file_header, &self.section_header)?;
+        Symbols::new(
+            reader,
+            &self.file_header,
+            &self.section_header,
+            section,
+            versions,
+        )
+    }
+
+    /// Iterates over the dynamic symbols which this object makes available
+    /// to other objects.
+    pub fn exported_symbols<'a, R: Read + Seek>(
+        &self,
+        reader: &'a mut R,
+    ) -> Result<impl Iterator<Item = Result<Symbol, ParseError>> + 'a, ParseError> {
+        let symbols = self.dynamic_symbols(reader)?;
+        Ok(symbols.filter(|symbol| symbol.as_ref().map_or(true, Symbol::is_exported)))
+    }
+
+    /// Iterates over the dynamic symbols which must be provided by another
+    /// object when this one is loaded.
+    pub fn imported_symbols<'a, R: Read + Seek>(
+        &self,
+        reader: &'a mut R,
+    ) -> Result<impl Iterator<Item = Result<Symbol, ParseError>> + 'a, ParseError> {
+        let symbols = self.dynamic_symbols(reader)?;
+        Ok(symbols.filter(|symbol| symbol.as_ref().map_or(true, Symbol::is_imported)))
+    }
+
+    /// Reads the versions this file requires from each of its dependencies.
+    pub fn version_requirements<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Vec<VersionRequirement>, ParseError> {
+        version::parse_requirements(reader, &self.file_header, &self.section_header)
+    }
+
+    /// Reads the versions this file defines for its own symbols.
+    pub fn version_definitions<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Vec<VersionDefinition>, ParseError> {
+        version::parse_definitions(reader, &self.file_header, &self.section_header)
+    }
+
+    /// Finds the newest version with the provided prefix that this file
+    /// requires from its dependencies, such as the newest `GLIBC_` version
+    /// which decides the oldest glibc the file can run against.
+    pub fn highest_required_version<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        prefix: &str,
+    ) -> Result<Option<String>, ParseError> {
+        let highest = self
+            .version_requirements(reader)?
+            .into_iter()
+            .flat_map(|requirement| requirement.versions)
+            .filter_map(|version| version.name)
+            .filter_map(|name| {
+                version::version_numbers(&name, prefix).map(|numbers| (numbers, name))
+            })
+            .max();
+
+        Ok(highest.map(|(_, name)| name))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
 pub enum ArchitectureWidth {
     ThirtyTwo,
     SixtyFour,
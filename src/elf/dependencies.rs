@@ -0,0 +1,341 @@
+//! Resolves the tree of shared objects a file needs, following the search
+//! order of the glibc dynamic linker. Files are only ever parsed, never
+//! executed, so this is safe to use on untrusted files and foreign sysroots.
+
+use crate::elf::dynamic::DF_1_NODEFLIB;
+use crate::elf::file_header::InstructionSetArch;
+use crate::elf::ArchitectureWidth;
+use crate::elf::CacheArchitecture;
+use crate::elf::DynamicEntry;
+use crate::elf::ElfDescriptor;
+use crate::elf::SystemLibraries;
+use crate::errors::ParseError;
+use crate::parsing::ParseSeekable;
+use crate::shared::rooted;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::env;
+use std::fs::File;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// A shared object needed by a file, along with the objects it needs in turn.
+pub struct Dependency {
+    /// The name the object was requested by (e.g. `libc.so.6`).
+    pub name: String,
+    /// Where the object was found, if it was found at all.
+    pub path: Option<PathBuf>,
+    /// Whether the object was already loaded for an earlier request, in
+    /// which case its own dependencies are only listed under that request.
+    pub repeated: bool,
+    pub dependencies: Vec<Dependency>,
+}
+
+pub struct DependencyResolver {
+    /// A directory which all absolute paths are taken relative to.
+    pub sysroot: Option<PathBuf>,
+    /// The directories searched as if they were in `LD_LIBRARY_PATH`.
+    pub library_path: Vec<PathBuf>,
+}
+
+impl DependencyResolver {
+    pub fn new() -> Self {
+        Self {
+            sysroot: None,
+            library_path: Vec::new(),
+        }
+    }
+
+    /// Creates a resolver which searches the directories in the
+    /// `LD_LIBRARY_PATH` of the current process.
+    pub fn from_env() -> Self {
+        let library_path = env::var_os("LD_LIBRARY_PATH")
+            .map(|paths| env::split_paths(&paths).collect())
+            .unwrap_or_default();
+
+        Self {
+            sysroot: None,
+            library_path,
+        }
+    }
+
+    /// Builds the tree of objects which would be loaded alongside the file.
+    pub fn resolve(&self, path: &Path) -> Result<Dependency, ParseError> {
+        // `$ORIGIN` stands for the directory of the object, which a bare file
+        // name wouldn't have
+        let path = &env::current_dir()?.join(path);
+        let root = self
+            .sysroot
+            .as_ref()
+            .map_or(Path::new("/"), PathBuf::as_path);
+        let system_libraries = SystemLibraries::load(root)?;
+
+        let object = LoadedObject::load(path)?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut loaded = HashMap::new();
+        if let Some(soname) = &object.soname {
+            loaded.insert(soname.clone(), path.to_path_buf());
+        }
+
+        // Objects are loaded breadth first, which decides the search paths
+        // used when several objects need the same library
+        let mut nodes = vec![Node {
+            name,
+            path: Some(path.to_path_buf()),
+            repeated: false,
+            parent: None,
+            object: Some(object),
+            children: Vec::new(),
+        }];
+        let mut queue = VecDeque::new();
+        queue.push_back(0);
+        while let Some(index) = queue.pop_front() {
+            let needed = match &nodes[index].object {
+                Some(object) => object.needed.clone(),
+                None => continue,
+            };
+
+            for name in needed {
+                let child = nodes.len();
+                let node = match loaded.get(&name) {
+                    Some(path) => Node {
+                        name,
+                        path: Some(path.clone()),
+                        repeated: true,
+                        parent: Some(index),
+                        object: None,
+                        children: Vec::new(),
+                    },
+                    None => {
+                        let found = self.search(&nodes, index, &name, &system_libraries);
+                        let path = found.as_ref().map(|(path, _)| path.clone());
+                        if let Some((path, object)) = &found {
+                            loaded.insert(name.clone(), path.clone());
+                            if let Some(soname) = &object.soname {
+                                loaded.insert(soname.clone(), path.clone());
+                            }
+                        }
+                        queue.push_back(child);
+                        Node {
+                            name,
+                            path,
+                            repeated: false,
+                            parent: Some(index),
+                            object: found.map(|(_, object)| object),
+                            children: Vec::new(),
+                        }
+                    }
+                };
+
+                nodes.push(node);
+                nodes[index].children.push(child);
+            }
+        }
+
+        Ok(build_tree(&mut nodes, 0))
+    }
+
+    /// Searches for the library `name` on behalf of the object at `requester`.
+    fn search(
+        &self,
+        nodes: &[Node],
+        requester: usize,
+        name: &str,
+        system_libraries: &SystemLibraries,
+    ) -> Option<(PathBuf, LoadedObject)> {
+        let object = nodes[requester].object.as_ref()?;
+
+        let sysroot = self.sysroot.as_deref();
+        if name.contains('/') {
+            return self.try_load(&rooted(sysroot, Path::new(name)), object);
+        }
+
+        // DT_RPATH is ignored for any object which also has a DT_RUNPATH, but
+        // otherwise the paths of the objects which loaded it are searched too
+        let mut directories = Vec::new();
+        if object.run_path.is_empty() {
+            let mut ancestor = Some(requester);
+            while let Some(index) = ancestor {
+                if let Some(loader) = &nodes[index].object {
+                    if loader.run_path.is_empty() {
+                        directories.extend(loader.expand(&loader.r_path, sysroot));
+                    }
+                }
+                ancestor = nodes[index].parent;
+            }
+        }
+        directories.extend(
+            self.library_path
+                .iter()
+                .map(|directory| rooted(sysroot, directory)),
+        );
+        directories.extend(object.expand(&object.run_path, sysroot));
+
+        let found = directories
+            .iter()
+            .find_map(|directory| self.try_load(&directory.join(name), object));
+        if found.is_some() || object.no_default_libraries {
+            return found;
+        }
+
+        CacheArchitecture::from_file_header(&object.descriptor.file_header)
+            .and_then(|architecture| system_libraries.locate(name, architecture))
+            .and_then(|path| self.try_load(&path, object))
+            .or_else(|| {
+                default_directories(object.descriptor.file_header.width)
+                    .iter()
+                    .map(|directory| rooted(sysroot, &Path::new(directory).join(name)))
+                    .find_map(|path| self.try_load(&path, object))
+            })
+    }
+
+    /// Loads the object at `path` if it can be loaded into the same process as
+    /// `requester`.
+    fn try_load(&self, path: &Path, requester: &LoadedObject) -> Option<(PathBuf, LoadedObject)> {
+        let object = LoadedObject::load(path).ok()?;
+        let header = &object.descriptor.file_header;
+        let requester_header = &requester.descriptor.file_header;
+        if header.width == requester_header.width
+            && header.instruction_set_arch == requester_header.instruction_set_arch
+        {
+            Some((path.to_path_buf(), object))
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for DependencyResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The details of an object which affect how its dependencies are found.
+struct LoadedObject {
+    descriptor: ElfDescriptor,
+    origin: PathBuf,
+    soname: Option<String>,
+    needed: Vec<String>,
+    r_path: Vec<String>,
+    run_path: Vec<String>,
+    no_default_libraries: bool,
+}
+
+impl LoadedObject {
+    fn load(path: &Path) -> Result<Self, ParseError> {
+        let mut file = File::open(path)?;
+        let descriptor = ElfDescriptor::parse_seekable(&mut file)?;
+        let entries = descriptor.dynamic_entries(&mut file)?;
+
+        let mut object = Self {
+            descriptor,
+            origin: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            soname: None,
+            needed: Vec::new(),
+            r_path: Vec::new(),
+            run_path: Vec::new(),
+            no_default_libraries: false,
+        };
+        for entry in entries {
+            match entry {
+                DynamicEntry::Needed(Some(name)) => object.needed.push(name),
+                DynamicEntry::SharedObjectName(name) => object.soname = name,
+                DynamicEntry::RPath(Some(paths)) => object.r_path.extend(split_search_path(&paths)),
+                DynamicEntry::RunPath(Some(paths)) => {
+                    object.run_path.extend(split_search_path(&paths))
+                }
+                DynamicEntry::Flags1(flags) => {
+                    object.no_default_libraries = flags & DF_1_NODEFLIB != 0
+                }
+                _ => {}
+            }
+        }
+
+        Ok(object)
+    }
+
+    /// Substitutes the dynamic string tokens (`$ORIGIN`, `$LIB` and
+    /// `$PLATFORM`) in each of the search paths. Paths which don't start at
+    /// `$ORIGIN`, which is already within the sysroot, are placed inside it.
+    fn expand(&self, paths: &[String], sysroot: Option<&Path>) -> Vec<PathBuf> {
+        let header = &self.descriptor.file_header;
+        let lib = match header.width {
+            ArchitectureWidth::ThirtyTwo => "lib",
+            ArchitectureWidth::SixtyFour => "lib64",
+        };
+        let platform = match header.instruction_set_arch {
+            InstructionSetArch::X86 => "i686",
+            InstructionSetArch::Amd64 => "x86_64",
+            InstructionSetArch::Arm => "arm",
+            InstructionSetArch::Arm64 => "aarch64",
+            InstructionSetArch::PowerPc64 => "ppc64",
+            InstructionSetArch::RiscV => "riscv",
+            _ => "",
+        };
+        let origin = self.origin.to_string_lossy();
+
+        paths
+            .iter()
+            .map(|path| {
+                let literal = !path.starts_with("$ORIGIN") && !path.starts_with("${ORIGIN}");
+                let path = [("ORIGIN", &*origin), ("LIB", lib), ("PLATFORM", platform)]
+                    .iter()
+                    .fold(path.clone(), |path, (token, value)| {
+                        path.replace(&format!("${{{}}}", token), value)
+                            .replace(&format!("${}", token), value)
+                    });
+                if literal {
+                    rooted(sysroot, Path::new(&path))
+                } else {
+                    PathBuf::from(path)
+                }
+            })
+            .collect()
+    }
+}
+
+/// A dependency while the tree is being built.
+struct Node {
+    name: String,
+    path: Option<PathBuf>,
+    repeated: bool,
+    parent: Option<usize>,
+    object: Option<LoadedObject>,
+    children: Vec<usize>,
+}
+
+fn build_tree(nodes: &mut Vec<Node>, index: usize) -> Dependency {
+    let children = std::mem::take(&mut nodes[index].children);
+    let dependencies = children
+        .into_iter()
+        .map(|child| build_tree(nodes, child))
+        .collect();
+    let node = &mut nodes[index];
+
+    Dependency {
+        name: std::mem::take(&mut node.name),
+        path: node.path.take(),
+        repeated: node.repeated,
+        dependencies,
+    }
+}
+
+fn split_search_path(paths: &str) -> impl Iterator<Item = String> + '_ {
+    paths
+        .split(':')
+        .filter(|path| !path.is_empty())
+        .map(String::from)
+}
+
+/// The directories searched after the cache, which glibc fixes when it is
+/// built.
+fn default_directories(width: ArchitectureWidth) -> &'static [&'static str] {
+    match width {
+        ArchitectureWidth::ThirtyTwo => &["/lib", "/usr/lib"],
+        ArchitectureWidth::SixtyFour => &["/lib64", "/usr/lib64"],
+    }
+}
@@ -0,0 +1,272 @@
+use crate::elf::parsing::ParseFromContext;
+use crate::elf::program_header;
+use crate::elf::section_header;
+use crate::elf::ArchitectureWidth;
+use crate::elf::ElfDescriptor;
+use crate::elf::Endianess;
+use crate::elf::NativeInteger;
+use crate::elf::StringTable;
+use crate::errors::ParseError;
+use crate::parsing::check_bounds;
+use crate::parsing::read_vec;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+/// Values of the `Flags` entry.
+pub const DF_ORIGIN: u64 = 0x1;
+pub const DF_SYMBOLIC: u64 = 0x2;
+pub const DF_TEXTREL: u64 = 0x4;
+pub const DF_BIND_NOW: u64 = 0x8;
+pub const DF_STATIC_TLS: u64 = 0x10;
+
+/// Values of the `Flags1` entry.
+pub const DF_1_NOW: u64 = 0x1;
+pub const DF_1_GLOBAL: u64 = 0x2;
+pub const DF_1_GROUP: u64 = 0x4;
+pub const DF_1_NODELETE: u64 = 0x8;
+pub const DF_1_LOADFLTR: u64 = 0x10;
+pub const DF_1_INITFIRST: u64 = 0x20;
+pub const DF_1_NOOPEN: u64 = 0x40;
+pub const DF_1_ORIGIN: u64 = 0x80;
+pub const DF_1_DIRECT: u64 = 0x100;
+pub const DF_1_INTERPOSE: u64 = 0x400;
+pub const DF_1_NODEFLIB: u64 = 0x800;
+pub const DF_1_NODUMP: u64 = 0x1000;
+pub const DF_1_PIE: u64 = 0x0800_0000;
+
+/// An entry of the dynamic section. Entries which name a string hold the
+/// string resolved through the dynamic string table (`DT_STRTAB`), or `None`
+/// when its offset lies outside that table.
+pub enum DynamicEntry {
+    Needed(Option<String>),
+    PltRelocationsSize(u64),
+    PltGot(u64),
+    Hash(u64),
+    StringTable(u64),
+    SymbolTable(u64),
+    RelocationsWithAddends(u64),
+    RelocationsWithAddendsSize(u64),
+    RelocationWithAddendEntrySize(u64),
+    StringTableSize(u64),
+    SymbolEntrySize(u64),
+    Init(u64),
+    Fini(u64),
+    SharedObjectName(Option<String>),
+    RPath(Option<String>),
+    Symbolic,
+    Relocations(u64),
+    RelocationsSize(u64),
+    RelocationEntrySize(u64),
+    PltRelocationType(u64),
+    Debug(u64),
+    TextRelocations,
+    JumpRelocations(u64),
+    BindNow,
+    InitArray(u64),
+    FiniArray(u64),
+    InitArraySize(u64),
+    FiniArraySize(u64),
+    RunPath(Option<String>),
+    Flags(u64),
+    PreInitArray(u64),
+    PreInitArraySize(u64),
+    SymbolTableSectionIndices(u64),
+    RelativeRelocationsSize(u64),
+    RelativeRelocations(u64),
+    RelativeRelocationEntrySize(u64),
+    GnuHash(u64),
+    VersionSymbols(u64),
+    RelocationWithAddendCount(u64),
+    RelocationCount(u64),
+    Flags1(u64),
+    VersionDefinitions(u64),
+    VersionDefinitionCount(u64),
+    VersionRequirements(u64),
+    VersionRequirementCount(u64),
+    Auxiliary(Option<String>),
+    Filter(Option<String>),
+    Other { tag: u64, value: u64 },
+}
+
+impl DynamicEntry {
+    fn from_raw(tag: u64, value: u64, strings: &StringTable) -> Self {
+        let string = || strings.get(value as usize).map(String::from);
+
+        match tag {
+            1 => Self::Needed(string()),
+            2 => Self::PltRelocationsSize(value),
+            3 => Self::PltGot(value),
+            4 => Self::Hash(value),
+            5 => Self::StringTable(value),
+            6 => Self::SymbolTable(value),
+            7 => Self::RelocationsWithAddends(value),
+            8 => Self::RelocationsWithAddendsSize(value),
+            9 => Self::RelocationWithAddendEntrySize(value),
+            10 => Self::StringTableSize(value),
+            11 => Self::SymbolEntrySize(value),
+            12 => Self::Init(value),
+            13 => Self::Fini(value),
+            14 => Self::SharedObjectName(string()),
+            15 => Self::RPath(string()),
+            16 => Self::Symbolic,
+            17 => Self::Relocations(value),
+            18 => Self::RelocationsSize(value),
+            19 => Self::RelocationEntrySize(value),
+            20 => Self::PltRelocationType(value),
+            21 => Self::Debug(value),
+            22 => Self::TextRelocations,
+            23 => Self::JumpRelocations(value),
+            24 => Self::BindNow,
+            25 => Self::InitArray(value),
+            26 => Self::FiniArray(value),
+            27 => Self::InitArraySize(value),
+            28 => Self::FiniArraySize(value),
+            29 => Self::RunPath(string()),
+            30 => Self::Flags(value),
+            32 => Self::PreInitArray(value),
+            33 => Self::PreInitArraySize(value),
+            34 => Self::SymbolTableSectionIndices(value),
+            35 => Self::RelativeRelocationsSize(value),
+            36 => Self::RelativeRelocations(value),
+            37 => Self::RelativeRelocationEntrySize(value),
+            0x6FFF_FEF5 => Self::GnuHash(value),
+            0x6FFF_FFF0 => Self::VersionSymbols(value),
+            0x6FFF_FFF9 => Self::RelocationWithAddendCount(value),
+            0x6FFF_FFFA => Self::RelocationCount(value),
+            0x6FFF_FFFB => Self::Flags1(value),
+            0x6FFF_FFFC => Self::VersionDefinitions(value),
+            0x6FFF_FFFD => Self::VersionDefinitionCount(value),
+            0x6FFF_FFFE => Self::VersionRequirements(value),
+            0x6FFF_FFFF => Self::VersionRequirementCount(value),
+            0x7FFF_FFFD => Self::Auxiliary(string()),
+            0x7FFF_FFFF => Self::Filter(string()),
+            tag => Self::Other { tag, value },
+        }
+    }
+}
+
+/// A dynamic entry before its tag has been interpreted.
+struct RawEntry {
+    tag: u64,
+    value: u64,
+}
+
+impl ParseFromContext for RawEntry {
+    fn parse_from_context(
+        reader: &mut impl Read,
+        endianess: Endianess,
+        arch_width: ArchitectureWidth,
+    ) -> Result<Self, ParseError> {
+        let tag = NativeInteger::parse_from_context(reader, endianess, arch_width)?;
+        let value = NativeInteger::parse_from_context(reader, endianess, arch_width)?;
+        Ok(Self {
+            tag: tag.into(),
+            value: value.into(),
+        })
+    }
+}
+
+const DT_NULL: u64 = 0;
+const DT_STRTAB: u64 = 5;
+const DT_STRSZ: u64 = 10;
+
+impl ElfDescriptor {
+    /// Reads the entries of the dynamic segment. The segment is located with
+    /// the program header so that files without a section table can still be
+    /// read, falling back to the `SHT_DYNAMIC` section for files which lack a
+    /// program header.
+    pub fn dynamic_entries<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<Vec<DynamicEntry>, ParseError> {
+        let segment = self
+            .program_header
+            .entries()
+            .iter()
+            .find(|entry| matches!(entry.entry_type, program_header::EntryType::Dynamic))
+            .map(|entry| (entry.offset.into(), entry.segment_file_size.into()));
+        let section = || {
+            self.section_header
+                .entries()
+                .iter()
+                .find(|entry| {
+                    matches!(
+                        entry.entry_type,
+                        section_header::EntryType::DynamicLinkingInformation
+                    )
+                })
+                .map(|entry| (entry.offset.into(), entry.size.into()))
+        };
+        let (offset, size): (u64, u64) = match segment.or_else(section) {
+            Some(location) => location,
+            None => return Ok(Vec::new()),
+        };
+
+        check_bounds(reader, offset, size)?;
+        reader.seek(SeekFrom::Start(offset))?;
+        let entry_size = match self.file_header.width {
+            ArchitectureWidth::ThirtyTwo => 8,
+            ArchitectureWidth::SixtyFour => 16,
+        };
+        let mut raw_entries = Vec::new();
+        for _ in 0..size / entry_size {
+            let entry = RawEntry::parse_from_context(
+                reader,
+                self.file_header.endianess,
+                self.file_header.width,
+            )?;
+            if entry.tag == DT_NULL {
+                break;
+            }
+            raw_entries.push(entry);
+        }
+
+        let strings = self.dynamic_string_table(reader, &raw_entries)?;
+        Ok(raw_entries
+            .into_iter()
+            .map(|entry| DynamicEntry::from_raw(entry.tag, entry.value, &strings))
+            .collect())
+    }
+
+    /// Loads the table named by `DT_STRTAB`, which is given as a virtual
+    /// address rather than a file offset.
+    fn dynamic_string_table<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        raw_entries: &[RawEntry],
+    ) -> Result<StringTable, ParseError> {
+        let find = |tag| {
+            raw_entries
+                .iter()
+                .find(|entry| entry.tag == tag)
+                .map(|entry| entry.value)
+        };
+        let (address, size) = match (find(DT_STRTAB), find(DT_STRSZ)) {
+            (Some(address), Some(size)) => (address, size),
+            _ => return Ok(StringTable::from(Vec::new())),
+        };
+
+        self.seek_to_address(reader, address, size)?;
+        read_vec(reader, size as usize).map(StringTable::from)
+    }
+
+    /// Moves the reader to the file offset of a virtual address, ensuring
+    /// that `size` bytes can be read from there.
+    pub(crate) fn seek_to_address<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        address: u64,
+        size: u64,
+    ) -> Result<(), ParseError> {
+        let offset = self
+            .offset_of_address(address)
+            .ok_or(ParseError::OutOfBounds {
+                offset: address,
+                size,
+            })?;
+        check_bounds(reader, offset, size)?;
+        reader.seek(SeekFrom::Start(offset))?;
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use crate::elf::parsing::ParseFromContext;
-use crate::elf::parsing::ParseFromEndianess;
 use crate::elf::ArchitectureWidth;
 use crate::errors::ParseError;
+use crate::parsing::ParseFromEndianess;
 use crate::parsing::*;
 use crate::shared::Endianess;
 use crate::shared::NativeInteger;
@@ -28,14 +28,9 @@ pub struct FileHeader {
 
 impl Parse for FileHeader {
     fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
-        let mut buffer = [0; 24];
-        if reader.read(&mut buffer)? < 24 {
-            return Err(ParseError::MissingData);
-        }
-
         // Ensure magic number is present
         const MAGIC_NUMBER: [u8; 4] = [0x7f, b'E', b'L', b'F'];
-        if buffer[0..4] != MAGIC_NUMBER {
+        if read_bytes::<R, 4>(reader)? != MAGIC_NUMBER {
             return Err(ParseError::MissingData);
         }
 
@@ -43,11 +38,12 @@ impl Parse for FileHeader {
         let endianess = Endianess::parse(reader)?;
         let elf_version = ElfVersion::parse(reader)?;
         let os_abi = OsAbi::parse(reader)?;
-        // abi_version &buffer[8]
-        // pad &buffer[9..16]
+        // abi_version and padding
+        read_bytes::<R, 8>(reader)?;
         let object_file_type = ObjectFileType::parse_from_endianess(reader, endianess)?;
         let instruction_set_arch = InstructionSetArch::parse_from_endianess(reader, endianess)?;
-        // version &buffer[20..24]
+        // version
+        u32::parse_from_endianess(reader, endianess)?;
         let entry_point = NativeInteger::parse_from_context(reader, endianess, width)?;
         let program_header = NativeInteger::parse_from_context(reader, endianess, width)?;
         let section_header = NativeInteger::parse_from_context(reader, endianess, width)?;
@@ -188,6 +184,7 @@ impl ParseFromEndianess for ObjectFileType {
     }
 }
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum InstructionSetArch {
     NoSpecific,
     AttWe32100,
@@ -0,0 +1,391 @@
+//! Looks up dynamic symbols through the hash tables the dynamic linker uses,
+//! rather than reading every symbol.
+
+use crate::elf::parsing::ParseFromContext;
+use crate::elf::section_header::EntryType;
+use crate::elf::version::VersionTable;
+use crate::elf::ArchitectureWidth;
+use crate::elf::DynamicEntry;
+use crate::elf::ElfDescriptor;
+use crate::elf::Endianess;
+use crate::elf::NativeInteger;
+use crate::elf::StringTable;
+use crate::elf::Symbol;
+use crate::errors::ParseError;
+use crate::parsing::read_vec;
+use crate::parsing::ParseFromEndianess;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+/// The hash function used by `DT_GNU_HASH` tables.
+pub fn gnu_hash(name: &str) -> u32 {
+    name.bytes().fold(5381u32, |hash, byte| {
+        hash.wrapping_mul(33).wrapping_add(u32::from(byte))
+    })
+}
+
+/// The hash function used by `DT_HASH` tables.
+pub fn sysv_hash(name: &str) -> u32 {
+    name.bytes().fold(0u32, |hash, byte| {
+        let hash = (hash << 4).wrapping_add(u32::from(byte));
+        let high = hash & 0xf000_0000;
+        (hash ^ (high >> 24)) & !high
+    })
+}
+
+impl ElfDescriptor {
+    /// Finds the exported dynamic symbol with the provided name. The hash
+    /// tables are used in the same way as the dynamic linker uses them, with
+    /// the GNU table preferred over the SysV one, and the symbol table is
+    /// only scanned when the file has neither.
+    pub fn lookup_symbol<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        name: &str,
+    ) -> Result<Option<Symbol>, ParseError> {
+        let entries = self.dynamic_entries(reader)?;
+        let table = match SymbolTable::locate(self, reader, &entries)? {
+            Some(table) => table,
+            None => return Ok(None),
+        };
+
+        let gnu_address = entries.iter().find_map(|entry| match entry {
+            DynamicEntry::GnuHash(address) => Some(*address),
+            _ => None,
+        });
+        let is_gnu_hash = |entry_type: &EntryType| matches!(entry_type, EntryType::GnuHash);
+        if let Some(offset) = self.hash_table_offset(gnu_address, is_gnu_hash) {
+            let hash_table = GnuHashTable::parse(reader, self, &table, offset)?;
+            return hash_table.lookup(reader, &table, name);
+        }
+
+        let sysv_address = entries.iter().find_map(|entry| match entry {
+            DynamicEntry::Hash(address) => Some(*address),
+            _ => None,
+        });
+        let is_sysv_hash =
+            |entry_type: &EntryType| matches!(entry_type, EntryType::SymbolHashTable);
+        if let Some(offset) = self.hash_table_offset(sysv_address, is_sysv_hash) {
+            let hash_table = SysvHashTable::parse(reader, self, offset)?;
+            return hash_table.lookup(reader, &table, name);
+        }
+
+        for symbol in self.exported_symbols(reader)? {
+            let symbol = symbol?;
+            if is_match(&symbol, name) {
+                return Ok(Some(symbol));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Finds the file offset of a hash table, using the address from the
+    /// dynamic segment or else the matching section.
+    fn hash_table_offset<F: Fn(&EntryType) -> bool>(
+        &self,
+        address: Option<u64>,
+        is_hash_table: F,
+    ) -> Option<u64> {
+        let section = || {
+            self.section_header
+                .entries()
+                .iter()
+                .find(|entry| is_hash_table(&entry.entry_type))
+                .map(|entry| entry.offset.into())
+        };
+
+        address
+            .and_then(|address| self.offset_of_address(address))
+            .or_else(section)
+    }
+}
+
+/// Checks whether a symbol would satisfy an unversioned reference to `name`,
+/// which older versions of a symbol never do.
+fn is_match(symbol: &Symbol, name: &str) -> bool {
+    let is_default_version = symbol.version.as_ref().map(|version| version.hidden) != Some(true);
+    symbol.name.as_deref() == Some(name) && symbol.is_exported() && is_default_version
+}
+
+/// The dynamic symbol table along with what is needed to read single symbols
+/// out of it.
+struct SymbolTable {
+    endianess: Endianess,
+    arch_width: ArchitectureWidth,
+    offset: u64,
+    entry_size: u64,
+    /// The number of symbols, which only the section header records.
+    count: Option<u64>,
+    names: StringTable,
+    versions: Option<VersionTable>,
+}
+
+impl SymbolTable {
+    /// Finds the table through the section header, or through the dynamic
+    /// segment for files without a section table.
+    fn locate<R: Read + Seek>(
+        descriptor: &ElfDescriptor,
+        reader: &mut R,
+        entries: &[DynamicEntry],
+    ) -> Result<Option<Self>, ParseError> {
+        let default_entry_size = match descriptor.file_header.width {
+            ArchitectureWidth::ThirtyTwo => 16,
+            ArchitectureWidth::SixtyFour => 24,
+        };
+        let versions =
+            VersionTable::parse(reader, &descriptor.file_header, &descriptor.section_header)?;
+        let mut table = Self {
+            endianess: descriptor.file_header.endianess,
+            arch_width: descriptor.file_header.width,
+            offset: 0,
+            entry_size: default_entry_size,
+            count: None,
+            names: StringTable::from(Vec::new()),
+            versions,
+        };
+
+        let sections = descriptor.section_header.entries();
+        let section = sections
+            .iter()
+            .find(|entry| matches!(entry.entry_type, EntryType::DynamicLinkerSymbolTable));
+        if let Some(section) = section {
+            table.offset = section.offset.into();
+            table.entry_size = u64::from(section.entry_size).max(1);
+            table.count = Some(u64::from(section.size) / table.entry_size);
+            if let Some(names) = sections.get(section.associated_section as usize) {
+                table.names = StringTable::parse_from_section(reader, names)?;
+            }
+            return Ok(Some(table));
+        }
+
+        let mut address = None;
+        let mut strings = None;
+        let mut strings_size = None;
+        for entry in entries {
+            match entry {
+                DynamicEntry::SymbolTable(value) => address = Some(*value),
+                DynamicEntry::SymbolEntrySize(value) => table.entry_size = *value,
+                DynamicEntry::StringTable(value) => strings = Some(*value),
+                DynamicEntry::StringTableSize(value) => strings_size = Some(*value),
+                _ => {}
+            }
+        }
+        table.offset = match address.and_then(|address| descriptor.offset_of_address(address)) {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+        if let (Some(address), Some(size)) = (strings, strings_size) {
+            descriptor.seek_to_address(reader, address, size)?;
+            table.names = read_vec(reader, size as usize)?.into();
+        }
+
+        Ok(Some(table))
+    }
+
+    /// Reads the symbol at `index`.
+    fn get<R: Read + Seek>(&self, reader: &mut R, index: u32) -> Result<Symbol, ParseError> {
+        reader.seek(SeekFrom::Start(
+            self.offset + u64::from(index) * self.entry_size,
+        ))?;
+        let mut symbol = Symbol::parse_from_context(reader, self.endianess, self.arch_width)?;
+        symbol.name = self
+            .names
+            .get(symbol.name_offset as usize)
+            .map(String::from);
+        symbol.version = self
+            .versions
+            .as_ref()
+            .and_then(|versions| versions.version_of(index as usize));
+
+        Ok(symbol)
+    }
+
+    /// Reads the symbol at `index` if it is the exported symbol `name`.
+    fn get_matching<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        index: u32,
+        name: &str,
+    ) -> Result<Option<Symbol>, ParseError> {
+        let symbol = self.get(reader, index)?;
+        if is_match(&symbol, name) {
+            Ok(Some(symbol))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// A `DT_GNU_HASH` table, which pairs the hash buckets with a bloom filter
+/// that rules out most missing symbols without touching the symbol table.
+struct GnuHashTable {
+    symbol_offset: u32,
+    bloom_shift: u32,
+    bloom: Vec<u64>,
+    word_bits: u32,
+    buckets: Vec<u32>,
+    chains_offset: u64,
+    chain_count: u32,
+    endianess: Endianess,
+}
+
+impl GnuHashTable {
+    fn parse<R: Read + Seek>(
+        reader: &mut R,
+        descriptor: &ElfDescriptor,
+        table: &SymbolTable,
+        offset: u64,
+    ) -> Result<Self, ParseError> {
+        let endianess = descriptor.file_header.endianess;
+        let arch_width = descriptor.file_header.width;
+        reader.seek(SeekFrom::Start(offset))?;
+        let bucket_count = u32::parse_from_endianess(reader, endianess)?;
+        let symbol_offset = u32::parse_from_endianess(reader, endianess)?;
+        let bloom_size = u32::parse_from_endianess(reader, endianess)?;
+        let bloom_shift = u32::parse_from_endianess(reader, endianess)?;
+        if bucket_count == 0 || bloom_size == 0 {
+            return Err(ParseError::InvalidValue {
+                value: bucket_count.min(bloom_size).to_ne_bytes().into(),
+            });
+        }
+        // The shift applies to a 32-bit hash
+        if bloom_shift >= 32 {
+            return Err(ParseError::InvalidValue {
+                value: bloom_shift.to_ne_bytes().into(),
+            });
+        }
+
+        let bloom = (0..bloom_size)
+            .map(|_| {
+                NativeInteger::parse_from_context(reader, endianess, arch_width).map(u64::from)
+            })
+            .collect::<Result<_, _>>()?;
+        let buckets = (0..bucket_count)
+            .map(|_| u32::parse_from_endianess(reader, endianess))
+            .collect::<Result<_, _>>()?;
+        let chains_offset = reader.stream_position()?;
+        // The chains run to the end of the symbol table, or at most to the end
+        // of the file when its size isn't known
+        let chain_count = match table.count {
+            Some(count) => count.saturating_sub(u64::from(symbol_offset)),
+            None => reader.seek(SeekFrom::End(0))?.saturating_sub(chains_offset) / 4,
+        };
+        let chain_count = chain_count.min(u64::from(u32::MAX - symbol_offset)) as u32;
+        let word_bits = match arch_width {
+            ArchitectureWidth::ThirtyTwo => 32,
+            ArchitectureWidth::SixtyFour => 64,
+        };
+
+        Ok(Self {
+            symbol_offset,
+            bloom_shift,
+            bloom,
+            word_bits,
+            buckets,
+            chains_offset,
+            chain_count,
+            endianess,
+        })
+    }
+
+    fn lookup<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        table: &SymbolTable,
+        name: &str,
+    ) -> Result<Option<Symbol>, ParseError> {
+        let hash = gnu_hash(name);
+
+        let word = self.bloom[((hash / self.word_bits) as usize) % self.bloom.len()];
+        let mask =
+            (1 << (hash % self.word_bits)) | (1 << ((hash >> self.bloom_shift) % self.word_bits));
+        if word & mask != mask {
+            return Ok(None);
+        }
+
+        let start = self.buckets[(hash as usize) % self.buckets.len()];
+        if start < self.symbol_offset {
+            return Ok(None);
+        }
+        for chain_index in start - self.symbol_offset..self.chain_count {
+            reader.seek(SeekFrom::Start(
+                self.chains_offset + u64::from(chain_index) * 4,
+            ))?;
+            let chain_hash = u32::parse_from_endianess(reader, self.endianess)?;
+            if chain_hash | 1 == hash | 1 {
+                let index = self.symbol_offset + chain_index;
+                if let Some(symbol) = table.get_matching(reader, index, name)? {
+                    return Ok(Some(symbol));
+                }
+            }
+
+            // The last symbol of each chain has the low bit set
+            if chain_hash & 1 != 0 {
+                break;
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// A `DT_HASH` table, as defined by the System V ABI.
+struct SysvHashTable {
+    buckets: Vec<u32>,
+    chains: Vec<u32>,
+}
+
+impl SysvHashTable {
+    fn parse<R: Read + Seek>(
+        reader: &mut R,
+        descriptor: &ElfDescriptor,
+        offset: u64,
+    ) -> Result<Self, ParseError> {
+        let endianess = descriptor.file_header.endianess;
+        reader.seek(SeekFrom::Start(offset))?;
+        let bucket_count = u32::parse_from_endianess(reader, endianess)?;
+        let chain_count = u32::parse_from_endianess(reader, endianess)?;
+        if bucket_count == 0 {
+            return Err(ParseError::InvalidValue {
+                value: bucket_count.to_ne_bytes().into(),
+            });
+        }
+
+        let mut read_words = |count| {
+            (0..count)
+                .map(|_| u32::parse_from_endianess(reader, endianess))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let buckets = read_words(bucket_count)?;
+        let chains = read_words(chain_count)?;
+
+        Ok(Self { buckets, chains })
+    }
+
+    fn lookup<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        table: &SymbolTable,
+        name: &str,
+    ) -> Result<Option<Symbol>, ParseError> {
+        let hash = sysv_hash(name);
+        let mut index = self.buckets[(hash as usize) % self.buckets.len()];
+
+        // Each symbol is visited at most once, even if the chains loop
+        for _ in 0..self.chains.len() {
+            if index == 0 {
+                break;
+            }
+            if let Some(symbol) = table.get_matching(reader, index, name)? {
+                return Ok(Some(symbol));
+            }
+            index = match self.chains.get(index as usize) {
+                Some(next) => *next,
+                None => break,
+            };
+        }
+
+        Ok(None)
+    }
+}
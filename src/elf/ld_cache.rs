@@ -0,0 +1,302 @@
+//! Reads the cache of library locations which `ldconfig` writes to
+//! `/etc/ld.so.cache` for the dynamic linker.
+
+use crate::elf::file_header::InstructionSetArch;
+use crate::elf::ArchitectureWidth;
+use crate::elf::FileHeader;
+use crate::elf::LdSoConf;
+use crate::elf::StringTable;
+use crate::errors::ParseError;
+use crate::parsing::Parse;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::rooted;
+use crate::shared::Endianess;
+use std::fs::File;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+
+const OLD_MAGIC: &[u8] = b"ld.so-1.7.0";
+const NEW_MAGIC: &[u8] = b"glibc-ld.so.cache1.1";
+
+/// The bits of an entry's flags which describe the kind of library.
+const FLAG_TYPE_MASK: u32 = 0x00ff;
+/// The bits of an entry's flags which describe the architecture.
+const FLAG_REQUIRED_MASK: u32 = 0xff00;
+const FLAG_ELF_LIBC6: u32 = 0x0003;
+
+/// The alignment of the new format header when it follows the old format.
+const NEW_FORMAT_ALIGNMENT: u64 = 8;
+
+pub struct LdCache {
+    entries: Vec<CacheEntry>,
+}
+
+pub struct CacheEntry {
+    pub flags: u32,
+    pub name: String,
+    pub path: PathBuf,
+    pub os_version: u32,
+    pub hardware_capabilities: u64,
+}
+
+impl Parse for LdCache {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+
+        if data.starts_with(NEW_MAGIC) {
+            parse_new_format(&mut Cursor::new(data))
+        } else if data.starts_with(OLD_MAGIC) {
+            parse_old_format(&mut Cursor::new(data))
+        } else {
+            Err(ParseError::InvalidValue {
+                value: data.into_iter().take(NEW_MAGIC.len()).collect(),
+            })
+        }
+    }
+}
+
+impl LdCache {
+    pub fn entries(&self) -> &[CacheEntry] {
+        &self.entries
+    }
+
+    /// Finds the library with the provided name which was built for
+    /// `architecture`.
+    pub fn find(&self, name: &str, architecture: CacheArchitecture) -> Option<&CacheEntry> {
+        let flags = FLAG_ELF_LIBC6 | architecture.flags();
+        self.entries.iter().find(|entry| {
+            entry.name == name && entry.flags & (FLAG_TYPE_MASK | FLAG_REQUIRED_MASK) == flags
+        })
+    }
+}
+
+/// Reads the `ld.so-1.7.0` format. Newer versions of `ldconfig` follow it with
+/// the new format for compatibility, in which case the new format is used.
+fn parse_old_format<R: Read + Seek>(reader: &mut R) -> Result<LdCache, ParseError> {
+    // The magic number is padded to align the count
+    reader.seek(SeekFrom::Start(OLD_MAGIC.len() as u64 + 1))?;
+    let count = u32::parse_from_endianess(reader, Endianess::native())?;
+
+    let mut raw_entries = Vec::new();
+    for _ in 0..count {
+        let flags = u32::parse_from_endianess(reader, Endianess::native())?;
+        let key = u32::parse_from_endianess(reader, Endianess::native())?;
+        let value = u32::parse_from_endianess(reader, Endianess::native())?;
+        raw_entries.push((flags, key, value, 0, 0));
+    }
+
+    // String offsets are relative to the end of the entries
+    let strings_start = reader.stream_position()?;
+    let new_format_start = (strings_start + NEW_FORMAT_ALIGNMENT - 1) & !(NEW_FORMAT_ALIGNMENT - 1);
+    reader.seek(SeekFrom::Start(new_format_start))?;
+    let mut magic = [0; 20];
+    if reader.read(&mut magic)? == magic.len() && magic == NEW_MAGIC {
+        reader.seek(SeekFrom::Start(new_format_start))?;
+        return parse_new_format(reader);
+    }
+
+    reader.seek(SeekFrom::Start(strings_start))?;
+    build_cache(reader, raw_entries)
+}
+
+/// Reads the `glibc-ld.so.cache1.1` format, whose string offsets are relative
+/// to the start of its header.
+fn parse_new_format<R: Read + Seek>(reader: &mut R) -> Result<LdCache, ParseError> {
+    let start = reader.stream_position()?;
+    reader.seek(SeekFrom::Current(NEW_MAGIC.len() as i64))?;
+    let mut header = [0; 12];
+    reader.read_exact(&mut header)?;
+
+    // The cache is written in the byte order of the machine which made it
+    let endianess = match header[8] {
+        2 => Endianess::Little,
+        3 => Endianess::Big,
+        _ => Endianess::native(),
+    };
+    let count = u32::parse_from_endianess(&mut &header[0..4], endianess)?;
+
+    // Skip the extension offset and unused fields
+    reader.seek(SeekFrom::Current(16))?;
+    let mut raw_entries = Vec::new();
+    for _ in 0..count {
+        let flags = u32::parse_from_endianess(reader, endianess)?;
+        let key = u32::parse_from_endianess(reader, endianess)?;
+        let value = u32::parse_from_endianess(reader, endianess)?;
+        let os_version = u32::parse_from_endianess(reader, endianess)?;
+        let hardware_capabilities = u64::parse_from_endianess(reader, endianess)?;
+        raw_entries.push((flags, key, value, os_version, hardware_capabilities));
+    }
+
+    reader.seek(SeekFrom::Start(start))?;
+    build_cache(reader, raw_entries)
+}
+
+/// Resolves the names of the raw entries using the strings which start at
+/// the current position of `reader`.
+fn build_cache<R: Read>(
+    reader: &mut R,
+    raw_entries: Vec<(u32, u32, u32, u32, u64)>,
+) -> Result<LdCache, ParseError> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let strings = StringTable::from(data);
+    let string = |offset: u32| {
+        strings
+            .get(offset as usize)
+            .map(String::from)
+            .ok_or(ParseError::MissingData)
+    };
+
+    let entries = raw_entries
+        .into_iter()
+        .map(|(flags, key, value, os_version, hardware_capabilities)| {
+            Ok(CacheEntry {
+                flags,
+                name: string(key)?,
+                path: string(value)?.into(),
+                os_version,
+                hardware_capabilities,
+            })
+        })
+        .collect::<Result<_, ParseError>>()?;
+
+    Ok(LdCache { entries })
+}
+
+/// The architectures which `ldconfig` distinguishes between in the cache.
+#[derive(Clone, Copy, PartialEq)]
+pub enum CacheArchitecture {
+    /// Architectures which `ldconfig` doesn't mark, such as 32-bit x86.
+    Unmarked,
+    Sparc64,
+    X86_64,
+    S390x,
+    PowerPc64,
+    Mips64N32,
+    Mips64N64,
+    X32,
+    ArmHardFloat,
+    Aarch64,
+    ArmSoftFloat,
+    RiscVSoftFloat,
+    RiscVDoubleFloat,
+}
+
+impl CacheArchitecture {
+    /// Gets the architecture of the libraries which can be loaded into a
+    /// process described by `file_header`.
+    pub fn from_file_header(file_header: &FileHeader) -> Option<Self> {
+        const EF_ARM_ABI_FLOAT_SOFT: u32 = 0x200;
+        const EF_ARM_ABI_FLOAT_HARD: u32 = 0x400;
+        const EF_RISCV_FLOAT_ABI: u32 = 0x6;
+        const EF_RISCV_FLOAT_ABI_DOUBLE: u32 = 0x4;
+        const EF_MIPS_ABI2: u32 = 0x20;
+
+        Some(
+            match (file_header.instruction_set_arch, file_header.width) {
+                (InstructionSetArch::Amd64, ArchitectureWidth::SixtyFour) => Self::X86_64,
+                (InstructionSetArch::Amd64, ArchitectureWidth::ThirtyTwo) => Self::X32,
+                (InstructionSetArch::Arm64, ArchitectureWidth::SixtyFour) => Self::Aarch64,
+                (InstructionSetArch::PowerPc64, ArchitectureWidth::SixtyFour) => Self::PowerPc64,
+                (InstructionSetArch::S390, ArchitectureWidth::SixtyFour) => Self::S390x,
+                (InstructionSetArch::Arm, _) if file_header.flags & EF_ARM_ABI_FLOAT_HARD != 0 => {
+                    Self::ArmHardFloat
+                }
+                (InstructionSetArch::Arm, _) if file_header.flags & EF_ARM_ABI_FLOAT_SOFT != 0 => {
+                    Self::ArmSoftFloat
+                }
+                (InstructionSetArch::RiscV, ArchitectureWidth::SixtyFour) => {
+                    match file_header.flags & EF_RISCV_FLOAT_ABI {
+                        0 => Self::RiscVSoftFloat,
+                        EF_RISCV_FLOAT_ABI_DOUBLE => Self::RiscVDoubleFloat,
+                        _ => return None,
+                    }
+                }
+                (InstructionSetArch::Mips, ArchitectureWidth::SixtyFour) => Self::Mips64N64,
+                (InstructionSetArch::Mips, _) if file_header.flags & EF_MIPS_ABI2 != 0 => {
+                    Self::Mips64N32
+                }
+                _ => Self::Unmarked,
+            },
+        )
+    }
+
+    fn flags(self) -> u32 {
+        match self {
+            Self::Unmarked => 0x0000,
+            Self::Sparc64 => 0x0100,
+            Self::X86_64 => 0x0300,
+            Self::S390x => 0x0400,
+            Self::PowerPc64 => 0x0500,
+            Self::Mips64N32 => 0x0600,
+            Self::Mips64N64 => 0x0700,
+            Self::X32 => 0x0800,
+            Self::ArmHardFloat => 0x0900,
+            Self::Aarch64 => 0x0a00,
+            Self::ArmSoftFloat => 0x0b00,
+            Self::RiscVSoftFloat => 0x0f00,
+            Self::RiscVDoubleFloat => 0x1000,
+        }
+    }
+}
+
+/// Finds libraries in the places `ldconfig` arranges for the dynamic linker
+/// to look. When a system has no cache, the directories in its configuration
+/// are searched directly, as they would be by `ldconfig` when building one.
+pub struct SystemLibraries {
+    root: PathBuf,
+    cache: Option<LdCache>,
+    config: LdSoConf,
+}
+
+impl SystemLibraries {
+    /// Loads `/etc/ld.so.cache` and `/etc/ld.so.conf` from beneath `root`.
+    /// Either file may be missing, and a cache which can't be parsed is
+    /// ignored in favour of the configured directories.
+    pub fn load(root: &Path) -> Result<Self, ParseError> {
+        let cache = File::open(root.join("etc/ld.so.cache"))
+            .ok()
+            .and_then(|mut file| LdCache::parse(&mut file).ok());
+        let config = LdSoConf::load(root, Path::new("/etc/ld.so.conf"))?;
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            cache,
+            config,
+        })
+    }
+
+    pub fn cache(&self) -> Option<&LdCache> {
+        self.cache.as_ref()
+    }
+
+    pub fn config(&self) -> &LdSoConf {
+        &self.config
+    }
+
+    /// Finds where the library with the provided name and architecture lives.
+    pub fn locate(&self, name: &str, architecture: CacheArchitecture) -> Option<PathBuf> {
+        if let Some(cache) = &self.cache {
+            return cache
+                .find(name, architecture)
+                .map(|entry| rooted(Some(&self.root), &entry.path));
+        }
+
+        self.config
+            .directories
+            .iter()
+            .map(|directory| rooted(Some(&self.root), &directory.join(name)))
+            .find(|path| {
+                File::open(path)
+                    .ok()
+                    .and_then(|mut file| FileHeader::parse(&mut file).ok())
+                    .and_then(|header| CacheArchitecture::from_file_header(&header))
+                    == Some(architecture)
+            })
+    }
+}
@@ -0,0 +1,257 @@
+//! Reads the `ldconfig` configuration in `/etc/ld.so.conf`, which lists the
+//! directories to cache libraries from.
+
+use crate::errors::ParseError;
+use crate::shared::rooted;
+use std::collections::HashSet;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+pub struct LdSoConf {
+    /// The directories to search, in the order they are listed.
+    pub directories: Vec<PathBuf>,
+}
+
+impl LdSoConf {
+    /// Reads the configuration file at `path` along with the files it
+    /// includes. Absolute paths, including those in the files, are taken
+    /// relative to `root`. A missing configuration file lists no directories.
+    pub fn load(root: &Path, path: &Path) -> Result<Self, ParseError> {
+        let mut config = Self {
+            directories: Vec::new(),
+        };
+        let mut visited = HashSet::new();
+        config.read_file(root, path, &mut visited)?;
+
+        Ok(config)
+    }
+
+    /// Parses the contents of a configuration file, without following any
+    /// `include` directives.
+    pub fn parse_str(contents: &str) -> Self {
+        let mut config = Self {
+            directories: Vec::new(),
+        };
+        for line in lines(contents) {
+            if let Line::Directory(directory) = line {
+                config.add_directory(directory);
+            }
+        }
+
+        config
+    }
+
+    fn read_file(
+        &mut self,
+        root: &Path,
+        path: &Path,
+        visited: &mut HashSet<PathBuf>,
+    ) -> Result<(), ParseError> {
+        // Guards against files which include themselves
+        if !visited.insert(path.to_path_buf()) {
+            return Ok(());
+        }
+
+        let contents = match fs::read_to_string(rooted(Some(root), path)) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error.into()),
+        };
+        for line in lines(&contents) {
+            match line {
+                Line::Directory(directory) => self.add_directory(directory),
+                Line::Include(patterns) => {
+                    // Relative patterns are relative to the including file
+                    let base = path.parent().unwrap_or_else(|| Path::new("/"));
+                    for pattern in patterns.split_whitespace() {
+                        for included in glob(root, &base.join(pattern)) {
+                            self.read_file(root, &included, visited)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_directory(&mut self, directory: &str) {
+        let directory = PathBuf::from(directory.trim_end_matches('/'));
+        if !self.directories.contains(&directory) {
+            self.directories.push(directory);
+        }
+    }
+}
+
+enum Line<'a> {
+    Directory(&'a str),
+    Include(&'a str),
+}
+
+/// Splits the configuration into its meaningful lines, dropping comments and
+/// the `hwcap` directives which glibc no longer supports.
+fn lines(contents: &str) -> impl Iterator<Item = Line<'_>> {
+    contents.lines().filter_map(|line| {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let keyword = |name: &str| {
+            line.strip_prefix(name)
+                .filter(|rest| rest.starts_with(char::is_whitespace))
+                .map(str::trim)
+        };
+
+        if line.is_empty() || keyword("hwcap").is_some() {
+            None
+        } else if let Some(patterns) = keyword("include") {
+            Some(Line::Include(patterns))
+        } else {
+            Some(Line::Directory(line))
+        }
+    })
+}
+
+/// Expands the wildcards (`*`, `?` and `[...]`) in an absolute pattern into
+/// the matching paths beneath `root`, which are returned relative to `root`
+/// and sorted as `glob(3)` would.
+fn glob(root: &Path, pattern: &Path) -> Vec<PathBuf> {
+    let mut matches = vec![PathBuf::from("/")];
+    for component in pattern.components() {
+        let component = match component {
+            Component::Normal(component) => component.to_string_lossy(),
+            Component::ParentDir => {
+                matches.iter_mut().for_each(|path| {
+                    path.pop();
+                });
+                continue;
+            }
+            _ => continue,
+        };
+
+        if !component.contains(&['*', '?', '['][..]) {
+            matches = matches.iter().map(|path| path.join(&*component)).collect();
+            continue;
+        }
+
+        let mut expanded = Vec::new();
+        for path in &matches {
+            let entries = match fs::read_dir(rooted(Some(root), path)) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            let mut names = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .filter(|name| !name.starts_with('.') && matches_pattern(&component, name))
+                .collect::<Vec<_>>();
+            names.sort();
+            expanded.extend(names.into_iter().map(|name| path.join(name)));
+        }
+        matches = expanded;
+    }
+
+    matches
+        .into_iter()
+        .filter(|path| rooted(Some(root), path).exists())
+        .collect()
+}
+
+/// Matches a single path component against a shell wildcard pattern.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    matches_chars(&pattern, &name)
+}
+
+fn matches_chars(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|skip| matches_chars(&pattern[1..], &name[skip..])),
+        Some('?') => !name.is_empty() && matches_chars(&pattern[1..], &name[1..]),
+        Some('[') => {
+            let end = match pattern.iter().skip(2).position(|c| *c == ']') {
+                Some(position) => position + 2,
+                None => {
+                    return name.first() == Some(&'[') && matches_chars(&pattern[1..], &name[1..])
+                }
+            };
+            let (negated, set) = match pattern[1] {
+                '!' | '^' => (true, &pattern[2..end]),
+                _ => (false, &pattern[1..end]),
+            };
+            let c = match name.first() {
+                Some(c) => *c,
+                None => return false,
+            };
+            let mut found = false;
+            let mut index = 0;
+            while index < set.len() {
+                if index + 2 < set.len() && set[index + 1] == '-' {
+                    found |= set[index] <= c && c <= set[index + 2];
+                    index += 3;
+                } else {
+                    found |= set[index] == c;
+                    index += 1;
+                }
+            }
+            found != negated && matches_chars(&pattern[end + 1..], &name[1..])
+        }
+        Some(literal) => name.first() == Some(literal) && matches_chars(&pattern[1..], &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards() {
+        assert!(matches_pattern("*.conf", "libc.conf"));
+        assert!(matches_pattern("*.conf", ".conf"));
+        assert!(!matches_pattern("*.conf", "libc.conf.bak"));
+        assert!(matches_pattern("lib?.conf", "libx.conf"));
+        assert!(!matches_pattern("lib?.conf", "lib.conf"));
+        assert!(matches_pattern("[a-z]1", "q1"));
+        assert!(!matches_pattern("[a-z]1", "Q1"));
+        assert!(matches_pattern("[!x]y", "zy"));
+        assert!(!matches_pattern("[!x]y", "xy"));
+        assert!(matches_pattern("[^x]y", "zy"));
+    }
+
+    #[test]
+    fn unterminated_bracket_is_literal() {
+        assert!(matches_pattern("a[b", "a[b"));
+        assert!(!matches_pattern("a[b", "ab"));
+    }
+
+    #[test]
+    fn parse_str_skips_includes() {
+        let config = LdSoConf::parse_str(
+            "# comment\n/usr/local/lib/\ninclude /etc/ld.so.conf.d/*.conf\nhwcap 0 nosegneg\n\
+             /usr/lib # trailing\n/usr/local/lib\n",
+        );
+        assert_eq!(
+            config.directories,
+            [PathBuf::from("/usr/local/lib"), PathBuf::from("/usr/lib")]
+        );
+    }
+
+    #[test]
+    fn load_follows_includes() {
+        // One of the included files includes the top level file again, and
+        // hidden files and those without the `.conf` extension are skipped
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ld_so_conf");
+        let config = LdSoConf::load(&root, Path::new("/etc/ld.so.conf")).unwrap();
+        assert_eq!(
+            config.directories,
+            [
+                PathBuf::from("/usr/local/lib"),
+                PathBuf::from("/opt/a"),
+                PathBuf::from("/opt/b"),
+                PathBuf::from("/opt/nested"),
+                PathBuf::from("/usr/lib/extra"),
+            ]
+        );
+    }
+}
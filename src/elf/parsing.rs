@@ -1,68 +1,13 @@
 use crate::elf::ArchitectureWidth;
 use crate::errors::ParseError;
-use crate::parsing::read_bytes;
-use crate::parsing::Parse;
+use crate::parsing::check_bounds;
+use crate::parsing::ParseFromEndianess;
+use crate::parsing::To2Bytes;
 use crate::shared::Endianess;
 use crate::shared::NativeInteger;
 use std::io::Read;
-
-pub(crate) trait ParseFromEndianess: Sized {
-    fn parse_from_endianess<R: Read>(
-        reader: &mut R,
-        endianess: Endianess,
-    ) -> Result<Self, ParseError>;
-}
-
-impl ParseFromEndianess for u16 {
-    fn parse_from_endianess<R: Read>(
-        reader: &mut R,
-        endianess: Endianess,
-    ) -> Result<Self, ParseError> {
-        let buffer = read_bytes::<R, 2>(reader)?;
-        Ok(match endianess {
-            Endianess::Little => u16::from_le_bytes(buffer),
-            Endianess::Big => u16::from_be_bytes(buffer),
-        })
-    }
-}
-
-impl ParseFromEndianess for u32 {
-    fn parse_from_endianess<R: Read>(
-        reader: &mut R,
-        endianess: Endianess,
-    ) -> Result<Self, ParseError> {
-        let buffer = read_bytes::<R, 4>(reader)?;
-        Ok(match endianess {
-            Endianess::Little => u32::from_le_bytes(buffer),
-            Endianess::Big => u32::from_be_bytes(buffer),
-        })
-    }
-}
-
-impl ParseFromEndianess for u64 {
-    fn parse_from_endianess<R: Read>(
-        reader: &mut R,
-        endianess: Endianess,
-    ) -> Result<u64, ParseError> {
-        let buffer = read_bytes(reader)?;
-        Ok(match endianess {
-            Endianess::Little => u64::from_le_bytes(buffer),
-            Endianess::Big => u64::from_be_bytes(buffer),
-        })
-    }
-}
-
-impl<T> ParseFromEndianess for T
-where
-    T: Parse,
-{
-    fn parse_from_endianess<R: Read>(
-        reader: &mut R,
-        endianess: Endianess,
-    ) -> Result<Self, ParseError> {
-        T::parse(reader)
-    }
-}
+use std::io::Seek;
+use std::io::SeekFrom;
 
 pub(crate) trait ParseFromContext: Sized {
     fn parse_from_context(
@@ -124,3 +69,38 @@ where
         U::parse_from_context(reader, endianess, arch_width)
     }
 }
+
+/// Parses a table of `count` entries which starts at `offset`, where each
+/// entry occupies `entry_size` bytes regardless of the size of `T`. Entries
+/// smaller than `min_entry_size` would overlap, so are rejected.
+pub(crate) fn parse_table<T: ParseFromContext, R: Read + Seek>(
+    reader: &mut R,
+    endianess: Endianess,
+    arch_width: ArchitectureWidth,
+    offset: u64,
+    entry_size: u16,
+    min_entry_size: u16,
+    count: u64,
+) -> Result<Vec<T>, ParseError> {
+    // Absent tables, such as the program header of a relocatable object,
+    // leave the entry size zero as well
+    if count == 0 || offset == 0 {
+        return Ok(Vec::new());
+    }
+
+    if entry_size < min_entry_size {
+        return Err(ParseError::InvalidValue {
+            value: entry_size.to_bytes(endianess).into(),
+        });
+    }
+
+    let entry_size = u64::from(entry_size);
+    check_bounds(reader, offset, entry_size.saturating_mul(count))?;
+
+    (0..count)
+        .map(|index| {
+            reader.seek(SeekFrom::Start(offset + index * entry_size))?;
+            T::parse_from_context(reader, endianess, arch_width)
+        })
+        .collect()
+}
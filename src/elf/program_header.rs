@@ -1,13 +1,15 @@
 use crate::elf::file_header::FileHeader;
+use crate::elf::parsing::parse_table;
 use crate::elf::parsing::ParseFromContext;
-use crate::elf::parsing::ParseFromEndianess;
 use crate::elf::parsing::ParseFromWorkingContext;
 use crate::elf::ArchitectureWidth;
 use crate::elf::Endianess;
 use crate::elf::NativeInteger;
 use crate::errors::ParseError;
+use crate::parsing::ParseFromEndianess;
 use crate::parsing::*;
 use std::io::Read;
+use std::io::Seek;
 
 pub struct ProgramHeader {
     entries: Vec<Entry>,
@@ -17,6 +19,28 @@ impl ProgramHeader {
     pub fn add_entry(&mut self, entry: Entry) {
         self.entries.push(entry)
     }
+
+    /// Parses the table found at the offset given by the file header.
+    pub fn parse_table<R: Read + Seek>(
+        reader: &mut R,
+        file_header: &FileHeader,
+    ) -> Result<Self, ParseError> {
+        let entries = parse_table(
+            reader,
+            file_header.endianess,
+            file_header.width,
+            file_header.program_header.into(),
+            file_header.program_header_entry_size,
+            Entry::size(file_header.width),
+            file_header.program_header_entry_count.into(),
+        )?;
+
+        Ok(Self { entries })
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
 }
 
 impl ParseFromWorkingContext<FileHeader> for ProgramHeader {
@@ -44,6 +68,16 @@ pub struct Entry {
     pub alignment: NativeInteger,
 }
 
+impl Entry {
+    /// The size of an entry in a file of the provided class.
+    pub(crate) fn size(width: ArchitectureWidth) -> u16 {
+        match width {
+            ArchitectureWidth::ThirtyTwo => 32,
+            ArchitectureWidth::SixtyFour => 56,
+        }
+    }
+}
+
 impl ParseFromContext for Entry {
     fn parse_from_context(
         reader: &mut impl Read,
@@ -51,12 +85,19 @@ impl ParseFromContext for Entry {
         arch_width: ArchitectureWidth,
     ) -> Result<Self, ParseError> {
         let entry_type = EntryType::parse_from_endianess(reader, endianess)?;
-        let flags = u32::parse_from_endianess(reader, endianess)?;
+        // The flags are moved to the front of the entry for 64-bit files
+        let mut flags = match arch_width {
+            ArchitectureWidth::ThirtyTwo => 0,
+            ArchitectureWidth::SixtyFour => u32::parse_from_endianess(reader, endianess)?,
+        };
         let offset = NativeInteger::parse_from_context(reader, endianess, arch_width)?;
         let virtual_address = NativeInteger::parse_from_context(reader, endianess, arch_width)?;
         let physical_address = NativeInteger::parse_from_context(reader, endianess, arch_width)?;
         let segment_file_size = NativeInteger::parse_from_context(reader, endianess, arch_width)?;
         let segment_mem_size = NativeInteger::parse_from_context(reader, endianess, arch_width)?;
+        if let ArchitectureWidth::ThirtyTwo = arch_width {
+            flags = u32::parse_from_endianess(reader, endianess)?;
+        }
         let alignment = NativeInteger::parse_from_context(reader, endianess, arch_width)?;
         Ok(Entry {
             entry_type,
@@ -81,8 +122,14 @@ pub enum EntryType {
     ProgramHeader,
     ThreadLocalStorage,
     Loos,
+    GnuEhFrame,
+    GnuStack,
+    GnuRelro,
+    GnuProperty,
+    OsSpecific(u32),
     Hios,
     Loproc,
+    ProcessorSpecific(u32),
     Hiproc,
 }
 
@@ -102,9 +149,15 @@ impl ParseFromEndianess for EntryType {
             0x00000006 => Self::ProgramHeader,
             0x00000007 => Self::ThreadLocalStorage,
             0x60000000 => Self::Loos,
+            0x6474E550 => Self::GnuEhFrame,
+            0x6474E551 => Self::GnuStack,
+            0x6474E552 => Self::GnuRelro,
+            0x6474E553 => Self::GnuProperty,
             0x6FFFFFFF => Self::Hios,
+            0x60000001..=0x6FFFFFFE => Self::OsSpecific(value),
             0x70000000 => Self::Loproc,
             0x7FFFFFFF => Self::Hiproc,
+            0x70000001..=0x7FFFFFFE => Self::ProcessorSpecific(value),
             other => {
                 return Err(ParseError::InvalidValue {
                     value: other.to_bytes(endianess).into(),
@@ -1,13 +1,16 @@
+use crate::elf::parsing::parse_table;
 use crate::elf::parsing::ParseFromContext;
-use crate::elf::parsing::ParseFromEndianess;
 use crate::elf::parsing::ParseFromWorkingContext;
+use crate::elf::string_table::StringTable;
 use crate::elf::ArchitectureWidth;
 use crate::elf::Endianess;
 use crate::elf::FileHeader;
 use crate::elf::NativeInteger;
 use crate::errors::ParseError;
+use crate::parsing::ParseFromEndianess;
 use crate::parsing::*;
 use std::io::Read;
+use std::io::Seek;
 
 pub struct SectionHeader {
     entries: Vec<SectionHeaderEntry>,
@@ -23,6 +26,77 @@ impl SectionHeader {
         (0..working_context.section_header_entry_count)
             .map(move |_| SectionHeaderEntry::parse_from_context(reader, endianess, arch_width))
     }
+
+    /// Parses the table found at the offset given by the file header.
+    pub fn parse_table<R: Read + Seek>(
+        reader: &mut R,
+        file_header: &FileHeader,
+    ) -> Result<Self, ParseError> {
+        let offset = file_header.section_header.into();
+        if offset == 0 {
+            return Ok(Self {
+                entries: Vec::new(),
+            });
+        }
+
+        let entry_size = file_header.section_header_entry_size;
+        let min_entry_size = SectionHeaderEntry::size(file_header.width);
+        let mut entries = parse_table(
+            reader,
+            file_header.endianess,
+            file_header.width,
+            offset,
+            entry_size,
+            min_entry_size,
+            file_header.section_header_entry_count.into(),
+        )?;
+
+        // Files with too many sections store the real count in the first entry
+        if entries.is_empty() {
+            let first: Vec<SectionHeaderEntry> = parse_table(
+                reader,
+                file_header.endianess,
+                file_header.width,
+                offset,
+                entry_size,
+                min_entry_size,
+                1,
+            )?;
+            let count = first[0].size.into();
+            entries = parse_table(
+                reader,
+                file_header.endianess,
+                file_header.width,
+                offset,
+                entry_size,
+                min_entry_size,
+                count,
+            )?;
+        }
+
+        Ok(Self { entries })
+    }
+
+    pub fn entries(&self) -> &[SectionHeaderEntry] {
+        &self.entries
+    }
+
+    /// Finds the first section with the provided name. Names are resolved by
+    /// `ElfDescriptor::parse_seekable`, but a table parsed any other way has
+    /// none until `ElfDescriptor::load_section_names` or `resolve_names` is
+    /// called, so nothing is found before then.
+    pub fn by_name(&self, name: &str) -> Option<&SectionHeaderEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.name.as_deref() == Some(name))
+    }
+
+    /// Assigns each entry its name from the section names string table.
+    pub fn resolve_names(&mut self, names: &StringTable) {
+        for entry in &mut self.entries {
+            entry.name = names.get(entry.name_offset as usize).map(String::from);
+        }
+    }
 }
 
 impl ParseFromWorkingContext<FileHeader> for SectionHeader {
@@ -39,6 +113,7 @@ impl ParseFromWorkingContext<FileHeader> for SectionHeader {
 }
 
 pub struct SectionHeaderEntry {
+    pub name: Option<String>,
     pub name_offset: u32,
     pub entry_type: EntryType,
     pub flags: Flags,
@@ -51,6 +126,16 @@ pub struct SectionHeaderEntry {
     pub entry_size: NativeInteger,
 }
 
+impl SectionHeaderEntry {
+    /// The size of an entry in a file of the provided class.
+    pub(crate) fn size(width: ArchitectureWidth) -> u16 {
+        match width {
+            ArchitectureWidth::ThirtyTwo => 40,
+            ArchitectureWidth::SixtyFour => 64,
+        }
+    }
+}
+
 impl ParseFromContext for SectionHeaderEntry {
     fn parse_from_context(
         reader: &mut impl Read,
@@ -69,6 +154,7 @@ impl ParseFromContext for SectionHeaderEntry {
         let entry_size = NativeInteger::parse_from_context(reader, endianess, arch_width)?;
 
         Ok(Self {
+            name: None,
             name_offset,
             entry_type,
             flags,
@@ -103,6 +189,15 @@ pub enum EntryType {
     ExtendedSectionIndices,
     NumberOfDefinedTypes,
     StartOsSpecific,
+    GnuAttributes,
+    GnuHash,
+    GnuLibraryList,
+    GnuVersionDefinitions,
+    GnuVersionRequirements,
+    GnuVersionSymbols,
+    OsSpecific(u32),
+    ProcessorSpecific(u32),
+    UserSpecific(u32),
 }
 
 impl ParseFromEndianess for EntryType {
@@ -131,6 +226,15 @@ impl ParseFromEndianess for EntryType {
             0x00000012 => EntryType::ExtendedSectionIndices,
             0x00000013 => EntryType::NumberOfDefinedTypes,
             0x60000000 => EntryType::StartOsSpecific,
+            0x6FFFFFF5 => EntryType::GnuAttributes,
+            0x6FFFFFF6 => EntryType::GnuHash,
+            0x6FFFFFF7 => EntryType::GnuLibraryList,
+            0x6FFFFFFD => EntryType::GnuVersionDefinitions,
+            0x6FFFFFFE => EntryType::GnuVersionRequirements,
+            0x6FFFFFFF => EntryType::GnuVersionSymbols,
+            0x60000001..=0x6FFFFFFF => EntryType::OsSpecific(val),
+            0x70000000..=0x7FFFFFFF => EntryType::ProcessorSpecific(val),
+            0x80000000..=0xFFFFFFFF => EntryType::UserSpecific(val),
             other => {
                 return Err(ParseError::InvalidValue {
                     value: other.to_bytes(endianess).into(),
@@ -0,0 +1,38 @@
+use crate::elf::section_header::SectionHeaderEntry;
+use crate::errors::ParseError;
+use crate::parsing::read_vec;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::str;
+
+/// A block of null terminated strings which other structures refer to by
+/// their offset into the block (e.g. section names and symbol names).
+pub struct StringTable {
+    data: Box<[u8]>,
+}
+
+impl StringTable {
+    /// Loads the contents of a string table section from the file.
+    pub fn parse_from_section<R: Read + Seek>(
+        reader: &mut R,
+        section: &SectionHeaderEntry,
+    ) -> Result<Self, ParseError> {
+        reader.seek(SeekFrom::Start(section.offset.into()))?;
+        let size = u64::from(section.size) as usize;
+        read_vec(reader, size).map(Self::from)
+    }
+
+    /// Gets the string which starts at the provided offset into the table.
+    pub fn get(&self, offset: usize) -> Option<&str> {
+        let tail = self.data.get(offset..)?;
+        let end = tail.iter().position(|byte| *byte == 0)?;
+        str::from_utf8(&tail[..end]).ok()
+    }
+}
+
+impl From<Vec<u8>> for StringTable {
+    fn from(data: Vec<u8>) -> Self {
+        Self { data: data.into() }
+    }
+}
@@ -0,0 +1,294 @@
+use crate::elf::parsing::ParseFromContext;
+use crate::elf::section_header::SectionHeaderEntry;
+use crate::elf::version::SymbolVersion;
+use crate::elf::version::VersionTable;
+use crate::elf::ArchitectureWidth;
+use crate::elf::Endianess;
+use crate::elf::FileHeader;
+use crate::elf::NativeInteger;
+use crate::elf::SectionHeader;
+use crate::elf::StringTable;
+use crate::errors::ParseError;
+use crate::parsing::check_bounds;
+use crate::parsing::ParseFromEndianess;
+use crate::parsing::*;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+/// The section index used by symbols which are not defined in this file.
+pub const UNDEFINED_SECTION: u16 = 0x0000;
+
+pub struct Symbol {
+    pub name: Option<String>,
+    pub name_offset: u32,
+    pub value: NativeInteger,
+    pub size: NativeInteger,
+    pub binding: Binding,
+    pub symbol_type: SymbolType,
+    pub visibility: Visibility,
+    pub section_index: u16,
+    pub version: Option<SymbolVersion>,
+}
+
+impl Symbol {
+    pub fn is_defined(&self) -> bool {
+        self.section_index != UNDEFINED_SECTION
+    }
+
+    /// Determines whether the dynamic linker would use this symbol to resolve
+    /// references from other objects. This mirrors the checks made by glibc's
+    /// `do_lookup_x`, which also accepts `STB_GNU_UNIQUE` symbols.
+    pub fn is_exported(&self) -> bool {
+        let has_value = u64::from(self.value) != 0
+            || matches!(self.symbol_type, SymbolType::ThreadLocalStorage);
+        let binds_globally = matches!(
+            self.binding,
+            Binding::Global | Binding::Weak | Binding::GnuUnique
+        );
+        let is_visible = matches!(self.visibility, Visibility::Default | Visibility::Protected);
+        let is_linkable = matches!(
+            self.symbol_type,
+            SymbolType::NoType
+                | SymbolType::Object
+                | SymbolType::Function
+                | SymbolType::Common
+                | SymbolType::ThreadLocalStorage
+                | SymbolType::GnuIndirectFunction
+        );
+
+        self.is_defined() && has_value && binds_globally && is_visible && is_linkable
+    }
+
+    /// Determines whether this symbol must be provided by another object when
+    /// the file is loaded.
+    pub fn is_imported(&self) -> bool {
+        !self.is_defined() && !matches!(self.binding, Binding::Local)
+    }
+}
+
+impl ParseFromContext for Symbol {
+    fn parse_from_context(
+        reader: &mut impl Read,
+        endianess: Endianess,
+        arch_width: ArchitectureWidth,
+    ) -> Result<Self, ParseError> {
+        let name_offset = u32::parse_from_endianess(reader, endianess)?;
+        // The value and size come before the info fields in 32-bit files
+        let (value, size, info, other, section_index) = match arch_width {
+            ArchitectureWidth::ThirtyTwo => {
+                let value = NativeInteger::parse_from_context(reader, endianess, arch_width)?;
+                let size = NativeInteger::parse_from_context(reader, endianess, arch_width)?;
+                let info = u8::parse(reader)?;
+                let other = u8::parse(reader)?;
+                let section_index = u16::parse_from_endianess(reader, endianess)?;
+                (value, size, info, other, section_index)
+            }
+            ArchitectureWidth::SixtyFour => {
+                let info = u8::parse(reader)?;
+                let other = u8::parse(reader)?;
+                let section_index = u16::parse_from_endianess(reader, endianess)?;
+                let value = NativeInteger::parse_from_context(reader, endianess, arch_width)?;
+                let size = NativeInteger::parse_from_context(reader, endianess, arch_width)?;
+                (value, size, info, other, section_index)
+            }
+        };
+
+        Ok(Self {
+            name: None,
+            name_offset,
+            value,
+            size,
+            binding: Binding::from_info(info)?,
+            symbol_type: SymbolType::from_info(info)?,
+            visibility: Visibility::from_other(other),
+            section_index,
+            version: None,
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Binding {
+    Local,
+    Global,
+    Weak,
+    GnuUnique,
+    OsSpecific(u8),
+    ProcessorSpecific(u8),
+}
+
+impl Binding {
+    fn from_info(info: u8) -> Result<Self, ParseError> {
+        Ok(match info >> 4 {
+            0 => Self::Local,
+            1 => Self::Global,
+            2 => Self::Weak,
+            10 => Self::GnuUnique,
+            other @ 11..=12 => Self::OsSpecific(other),
+            other @ 13..=15 => Self::ProcessorSpecific(other),
+            other => {
+                return Err(ParseError::InvalidValue {
+                    value: other.to_bytes().into(),
+                })
+            }
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum SymbolType {
+    NoType,
+    Object,
+    Function,
+    Section,
+    File,
+    Common,
+    ThreadLocalStorage,
+    GnuIndirectFunction,
+    OsSpecific(u8),
+    ProcessorSpecific(u8),
+}
+
+impl SymbolType {
+    fn from_info(info: u8) -> Result<Self, ParseError> {
+        Ok(match info & 0xf {
+            0 => Self::NoType,
+            1 => Self::Object,
+            2 => Self::Function,
+            3 => Self::Section,
+            4 => Self::File,
+            5 => Self::Common,
+            6 => Self::ThreadLocalStorage,
+            10 => Self::GnuIndirectFunction,
+            other @ 11..=12 => Self::OsSpecific(other),
+            other @ 13..=15 => Self::ProcessorSpecific(other),
+            other => {
+                return Err(ParseError::InvalidValue {
+                    value: other.to_bytes().into(),
+                })
+            }
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Visibility {
+    Default,
+    Internal,
+    Hidden,
+    Protected,
+}
+
+impl Visibility {
+    fn from_other(other: u8) -> Self {
+        match other & 0x3 {
+            0 => Self::Default,
+            1 => Self::Internal,
+            2 => Self::Hidden,
+            _ => Self::Protected,
+        }
+    }
+}
+
+/// Reads the entries of a symbol table one at a time. The first entry of a
+/// symbol table is always the null symbol, which is yielded like any other
+/// so that indices line up with the other tables that refer to symbols.
+pub struct Symbols<'a, R> {
+    reader: &'a mut R,
+    endianess: Endianess,
+    arch_width: ArchitectureWidth,
+    names: StringTable,
+    versions: Option<VersionTable>,
+    offset: u64,
+    entry_size: u64,
+    index: u64,
+    count: u64,
+}
+
+impl<'a, R: Read + Seek> Symbols<'a, R> {
+    /// Prepares to read the symbols of `section`, or no symbols at all when
+    /// the file lacks the table.
+    pub(crate) fn new(
+        reader: &'a mut R,
+        file_header: &FileHeader,
+        section_header: &SectionHeader,
+        section: Option<&SectionHeaderEntry>,
+        versions: Option<VersionTable>,
+    ) -> Result<Self, ParseError> {
+        let mut symbols = Self {
+            reader,
+            endianess: file_header.endianess,
+            arch_width: file_header.width,
+            names: StringTable::from(Vec::new()),
+            versions,
+            offset: 0,
+            entry_size: 0,
+            index: 0,
+            count: 0,
+        };
+
+        if let Some(section) = section {
+            let entry_size = u64::from(section.entry_size);
+            if entry_size == 0 {
+                return Err(ParseError::InvalidValue {
+                    value: entry_size.to_bytes(file_header.endianess).into(),
+                });
+            }
+
+            let offset = section.offset.into();
+            let size = section.size.into();
+            check_bounds(symbols.reader, offset, size)?;
+
+            if let Some(names_section) = section_header
+                .entries()
+                .get(section.associated_section as usize)
+            {
+                symbols.names = StringTable::parse_from_section(symbols.reader, names_section)?;
+            }
+            symbols.offset = offset;
+            symbols.entry_size = entry_size;
+            symbols.count = size / entry_size;
+        }
+
+        Ok(symbols)
+    }
+}
+
+impl<'a, R: Read + Seek> Iterator for Symbols<'a, R> {
+    type Item = Result<Symbol, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+
+        let index = self.index;
+        let offset = self.offset + index * self.entry_size;
+        self.index += 1;
+
+        let symbol = self
+            .reader
+            .seek(SeekFrom::Start(offset))
+            .map_err(ParseError::from)
+            .and_then(|_| Symbol::parse_from_context(self.reader, self.endianess, self.arch_width))
+            .map(|mut symbol| {
+                symbol.name = self
+                    .names
+                    .get(symbol.name_offset as usize)
+                    .map(String::from);
+                symbol.version = self
+                    .versions
+                    .as_ref()
+                    .and_then(|versions| versions.version_of(index as usize));
+                symbol
+            });
+
+        Some(symbol)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.count - self.index) as usize;
+        (remaining, Some(remaining))
+    }
+}
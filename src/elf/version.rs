@@ -0,0 +1,307 @@
+use crate::elf::section_header::EntryType;
+use crate::elf::section_header::SectionHeaderEntry;
+use crate::elf::Endianess;
+use crate::elf::FileHeader;
+use crate::elf::SectionHeader;
+use crate::elf::StringTable;
+use crate::errors::ParseError;
+use crate::parsing::check_bounds;
+use crate::parsing::ParseFromEndianess;
+use std::collections::HashMap;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+/// The version indices which do not refer to a version definition or
+/// requirement.
+const VER_NDX_LOCAL: u16 = 0;
+const VER_NDX_GLOBAL: u16 = 1;
+const VERSYM_INDEX_MASK: u16 = 0x7fff;
+const VERSYM_HIDDEN: u16 = 0x8000;
+
+/// The version attached to a dynamic symbol.
+#[derive(Clone)]
+pub struct SymbolVersion {
+    pub name: Option<String>,
+    /// The library expected to provide the version, for undefined symbols.
+    pub library: Option<String>,
+    /// Whether this is an older version of the symbol (`name@VERSION`) rather
+    /// than the default one (`name@@VERSION`), which is used when linking.
+    pub hidden: bool,
+}
+
+/// A version defined by this file, which its symbols may be attached to.
+pub struct VersionDefinition {
+    pub name: Option<String>,
+    pub flags: u16,
+    pub index: u16,
+    pub hash: u32,
+    /// The versions which this one succeeds.
+    pub parents: Vec<Option<String>>,
+}
+
+impl VersionDefinition {
+    /// Whether this is the definition of the file itself rather than of a
+    /// version its symbols can have.
+    pub fn is_base(&self) -> bool {
+        const VER_FLG_BASE: u16 = 0x1;
+        self.flags & VER_FLG_BASE != 0
+    }
+
+    /// Reads every entry of a `SHT_GNU_verdef` section.
+    pub(crate) fn parse_section<R: Read + Seek>(
+        reader: &mut R,
+        endianess: Endianess,
+        section: &SectionHeaderEntry,
+        names: &StringTable,
+    ) -> Result<Vec<Self>, ParseError> {
+        let section_offset: u64 = section.offset.into();
+        check_bounds(reader, section_offset, section.size.into())?;
+
+        let mut definitions = Vec::new();
+        let mut offset = section_offset;
+        for _ in 0..section.section_info {
+            reader.seek(SeekFrom::Start(offset))?;
+            let _version = u16::parse_from_endianess(reader, endianess)?;
+            let flags = u16::parse_from_endianess(reader, endianess)?;
+            let index = u16::parse_from_endianess(reader, endianess)?;
+            let count = u16::parse_from_endianess(reader, endianess)?;
+            let hash = u32::parse_from_endianess(reader, endianess)?;
+            let aux = u32::parse_from_endianess(reader, endianess)?;
+            let next = u32::parse_from_endianess(reader, endianess)?;
+
+            // The first name is the version itself and the rest are parents
+            let mut aux_names = Vec::new();
+            let mut aux_offset = offset + u64::from(aux);
+            for _ in 0..count {
+                reader.seek(SeekFrom::Start(aux_offset))?;
+                let name = u32::parse_from_endianess(reader, endianess)?;
+                let aux_next = u32::parse_from_endianess(reader, endianess)?;
+                aux_names.push(names.get(name as usize).map(String::from));
+
+                if aux_next == 0 {
+                    break;
+                }
+                aux_offset += u64::from(aux_next);
+            }
+
+            let mut aux_names = aux_names.into_iter();
+            definitions.push(Self {
+                name: aux_names.next().and_then(|name| name),
+                flags,
+                index,
+                hash,
+                parents: aux_names.collect(),
+            });
+
+            if next == 0 {
+                break;
+            }
+            offset += u64::from(next);
+        }
+
+        Ok(definitions)
+    }
+}
+
+/// The versions which must be provided by one of the needed libraries.
+pub struct VersionRequirement {
+    pub file: Option<String>,
+    pub versions: Vec<RequiredVersion>,
+}
+
+pub struct RequiredVersion {
+    pub name: Option<String>,
+    pub hash: u32,
+    pub flags: u16,
+    pub index: u16,
+}
+
+impl VersionRequirement {
+    /// Reads every entry of a `SHT_GNU_verneed` section.
+    pub(crate) fn parse_section<R: Read + Seek>(
+        reader: &mut R,
+        endianess: Endianess,
+        section: &SectionHeaderEntry,
+        names: &StringTable,
+    ) -> Result<Vec<Self>, ParseError> {
+        let section_offset: u64 = section.offset.into();
+        check_bounds(reader, section_offset, section.size.into())?;
+
+        let mut requirements = Vec::new();
+        let mut offset = section_offset;
+        for _ in 0..section.section_info {
+            reader.seek(SeekFrom::Start(offset))?;
+            let _version = u16::parse_from_endianess(reader, endianess)?;
+            let count = u16::parse_from_endianess(reader, endianess)?;
+            let file = u32::parse_from_endianess(reader, endianess)?;
+            let aux = u32::parse_from_endianess(reader, endianess)?;
+            let next = u32::parse_from_endianess(reader, endianess)?;
+
+            let mut versions = Vec::new();
+            let mut aux_offset = offset + u64::from(aux);
+            for _ in 0..count {
+                reader.seek(SeekFrom::Start(aux_offset))?;
+                let hash = u32::parse_from_endianess(reader, endianess)?;
+                let flags = u16::parse_from_endianess(reader, endianess)?;
+                let index = u16::parse_from_endianess(reader, endianess)?;
+                let name = u32::parse_from_endianess(reader, endianess)?;
+                let aux_next = u32::parse_from_endianess(reader, endianess)?;
+
+                versions.push(RequiredVersion {
+                    name: names.get(name as usize).map(String::from),
+                    hash,
+                    flags,
+                    index,
+                });
+
+                if aux_next == 0 {
+                    break;
+                }
+                aux_offset += u64::from(aux_next);
+            }
+
+            requirements.push(Self {
+                file: names.get(file as usize).map(String::from),
+                versions,
+            });
+
+            if next == 0 {
+                break;
+            }
+            offset += u64::from(next);
+        }
+
+        Ok(requirements)
+    }
+}
+
+/// Maps each dynamic symbol to its version using the `SHT_GNU_versym`
+/// section, which holds one version index per dynamic symbol.
+pub(crate) struct VersionTable {
+    indices: Vec<u16>,
+    versions: HashMap<u16, SymbolVersion>,
+}
+
+impl VersionTable {
+    /// Loads the versioning sections of the file, if it has any.
+    pub(crate) fn parse<R: Read + Seek>(
+        reader: &mut R,
+        file_header: &FileHeader,
+        section_header: &SectionHeader,
+    ) -> Result<Option<Self>, ParseError> {
+        let versym = match find_section(section_header, |entry_type| {
+            matches!(entry_type, EntryType::GnuVersionSymbols)
+        }) {
+            Some(section) => section,
+            None => return Ok(None),
+        };
+
+        let offset: u64 = versym.offset.into();
+        let size: u64 = versym.size.into();
+        check_bounds(reader, offset, size)?;
+        reader.seek(SeekFrom::Start(offset))?;
+        let indices = (0..size / 2)
+            .map(|_| u16::parse_from_endianess(reader, file_header.endianess))
+            .collect::<Result<_, _>>()?;
+
+        let mut versions = HashMap::new();
+        for requirement in parse_requirements(reader, file_header, section_header)? {
+            for version in requirement.versions {
+                let symbol_version = SymbolVersion {
+                    name: version.name,
+                    library: requirement.file.clone(),
+                    hidden: false,
+                };
+                versions.insert(version.index & VERSYM_INDEX_MASK, symbol_version);
+            }
+        }
+        for definition in parse_definitions(reader, file_header, section_header)? {
+            let symbol_version = SymbolVersion {
+                name: definition.name,
+                library: None,
+                hidden: false,
+            };
+            versions.insert(definition.index & VERSYM_INDEX_MASK, symbol_version);
+        }
+
+        Ok(Some(Self { indices, versions }))
+    }
+
+    /// Gets the version of the dynamic symbol at `symbol_index`.
+    pub(crate) fn version_of(&self, symbol_index: usize) -> Option<SymbolVersion> {
+        let entry = self.indices.get(symbol_index)?;
+        match entry & VERSYM_INDEX_MASK {
+            VER_NDX_LOCAL | VER_NDX_GLOBAL => None,
+            index => self.versions.get(&index).map(|version| SymbolVersion {
+                hidden: entry & VERSYM_HIDDEN != 0,
+                ..version.clone()
+            }),
+        }
+    }
+}
+
+/// Reads the version requirements of the file, if it has any.
+pub(crate) fn parse_requirements<R: Read + Seek>(
+    reader: &mut R,
+    file_header: &FileHeader,
+    section_header: &SectionHeader,
+) -> Result<Vec<VersionRequirement>, ParseError> {
+    let verneed = match find_section(section_header, |entry_type| {
+        matches!(entry_type, EntryType::GnuVersionRequirements)
+    }) {
+        Some(section) => section,
+        None => return Ok(Vec::new()),
+    };
+
+    let names = linked_string_table(reader, section_header, verneed)?;
+    VersionRequirement::parse_section(reader, file_header.endianess, verneed, &names)
+}
+
+/// Reads the versions defined by the file, if it has any.
+pub(crate) fn parse_definitions<R: Read + Seek>(
+    reader: &mut R,
+    file_header: &FileHeader,
+    section_header: &SectionHeader,
+) -> Result<Vec<VersionDefinition>, ParseError> {
+    let verdef = match find_section(section_header, |entry_type| {
+        matches!(entry_type, EntryType::GnuVersionDefinitions)
+    }) {
+        Some(section) => section,
+        None => return Ok(Vec::new()),
+    };
+
+    let names = linked_string_table(reader, section_header, verdef)?;
+    VersionDefinition::parse_section(reader, file_header.endianess, verdef, &names)
+}
+
+/// Orders version names such as `GLIBC_2.2.5` by the numbers after `prefix`,
+/// ignoring names which don't follow that pattern (e.g. `GLIBC_PRIVATE`).
+pub(crate) fn version_numbers(name: &str, prefix: &str) -> Option<Vec<u32>> {
+    name.strip_prefix(prefix)?
+        .split('.')
+        .map(|number| number.parse().ok())
+        .collect()
+}
+
+fn find_section<F: Fn(&EntryType) -> bool>(
+    section_header: &SectionHeader,
+    predicate: F,
+) -> Option<&SectionHeaderEntry> {
+    section_header
+        .entries()
+        .iter()
+        .find(|entry| predicate(&entry.entry_type))
+}
+
+fn linked_string_table<R: Read + Seek>(
+    reader: &mut R,
+    section_header: &SectionHeader,
+    section: &SectionHeaderEntry,
+) -> Result<StringTable, ParseError> {
+    section_header
+        .entries()
+        .get(section.associated_section as usize)
+        .ok_or(ParseError::MissingData)
+        .and_then(|names| StringTable::parse_from_section(reader, names))
+}
@@ -5,6 +5,7 @@ pub enum ParseError {
     IoError(IoError),
     MissingData,
     InvalidValue { value: Box<[u8]> },
+    OutOfBounds { offset: u64, size: u64 },
 }
 
 impl From<IoError> for ParseError {
@@ -6,7 +6,10 @@ pub mod dll;
 #[cfg(feature = "elf")]
 pub mod elf;
 pub mod errors;
+#[cfg(feature = "macho")]
+pub mod macho;
 mod parsing;
 mod shared;
 
 pub use self::parsing::Parse;
+pub use self::parsing::ParseSeekable;
@@ -0,0 +1,187 @@
+//! Types for representing a Mach-O file, the format of executables and
+//! dynamic libraries (`.dylib`) on macOS and iOS.
+//! Reference pulled from `<mach-o/loader.h>` in Apple's cctools.
+
+mod dependencies;
+pub mod export;
+pub mod fat;
+pub mod header;
+pub mod load_command;
+
+pub use self::dependencies::Dependency;
+pub use self::dependencies::DependencyResolver;
+pub use self::export::Export;
+pub use self::export::ExportKind;
+pub use self::fat::FatArch;
+pub use self::fat::FatBinary;
+pub use self::header::ArchitectureWidth;
+pub use self::header::CpuType;
+pub use self::header::FileType;
+pub use self::header::MachHeader;
+pub use self::load_command::DyldInfo;
+pub use self::load_command::DylibCommand;
+pub use self::load_command::DylibKind;
+pub use self::load_command::LinkEditData;
+pub use self::load_command::LoadCommand;
+pub use self::load_command::Section;
+pub use self::load_command::Segment;
+pub use self::load_command::Version;
+use crate::errors::ParseError;
+use crate::macho::fat::FAT_MAGIC;
+use crate::macho::fat::FAT_MAGIC_64;
+use crate::macho::load_command::LC_DYLD_EXPORTS_TRIE;
+use crate::parsing::check_bounds;
+use crate::parsing::read_bytes;
+use crate::parsing::read_vec;
+use crate::parsing::Parse;
+use crate::parsing::ParseSeekable;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+pub struct MachODescriptor {
+    pub header: MachHeader,
+    pub load_commands: Vec<LoadCommand>,
+    /// The offset of the slice within a universal file, which the file
+    /// offsets in the load commands are relative to.
+    pub offset: u64,
+}
+
+impl Parse for MachODescriptor {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        let header = MachHeader::parse(reader)?;
+        let load_commands = LoadCommand::parse_commands(reader, &header)?;
+
+        Ok(Self {
+            header,
+            load_commands,
+            offset: 0,
+        })
+    }
+}
+
+impl ParseSeekable for MachODescriptor {
+    fn parse_seekable<R: Read + Seek>(reader: &mut R) -> Result<Self, ParseError> {
+        reader.seek(SeekFrom::Start(0))?;
+        Self::parse(reader)
+    }
+}
+
+impl MachODescriptor {
+    /// Parses a file for the named architecture (e.g. `arm64`), which picks
+    /// the slice of a universal file. Without a name, the first slice is
+    /// parsed.
+    pub fn parse_architecture<R: Read + Seek>(
+        reader: &mut R,
+        architecture: Option<&str>,
+    ) -> Result<Self, ParseError> {
+        let descriptor = Self::parse_matching(reader, |cpu_type, cpu_subtype| {
+            architecture.is_none() || cpu_type.architecture_name(cpu_subtype) == architecture
+        })?;
+
+        descriptor.ok_or_else(|| ParseError::InvalidValue {
+            value: architecture.unwrap_or_default().as_bytes().into(),
+        })
+    }
+
+    /// Parses a thin file, or the first slice of a universal file, whose CPU
+    /// type and subtype are accepted by `matches`.
+    pub(crate) fn parse_matching<R: Read + Seek>(
+        reader: &mut R,
+        matches: impl Fn(CpuType, u32) -> bool,
+    ) -> Result<Option<Self>, ParseError> {
+        reader.seek(SeekFrom::Start(0))?;
+        let magic = u32::from_be_bytes(read_bytes::<R, 4>(reader)?);
+        reader.seek(SeekFrom::Start(0))?;
+        if magic != FAT_MAGIC && magic != FAT_MAGIC_64 {
+            let descriptor = Self::parse(reader)?;
+            return Ok(Some(descriptor).filter(|descriptor| {
+                matches(descriptor.header.cpu_type, descriptor.header.cpu_subtype)
+            }));
+        }
+
+        let fat = FatBinary::parse(reader)?;
+        fat.architectures
+            .iter()
+            .find(|architecture| matches(architecture.cpu_type, architecture.cpu_subtype))
+            .map(|architecture| architecture.parse_descriptor(reader))
+            .transpose()
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = &Segment> {
+        self.load_commands
+            .iter()
+            .filter_map(|command| match command {
+                LoadCommand::Segment(segment) => Some(segment),
+                _ => None,
+            })
+    }
+
+    /// Finds the segment with the provided name, such as `__TEXT`.
+    pub fn segment(&self, name: &str) -> Option<&Segment> {
+        self.segments().find(|segment| segment.name == name)
+    }
+
+    pub fn sections(&self) -> impl Iterator<Item = &Section> {
+        self.segments().flat_map(|segment| &segment.sections)
+    }
+
+    /// Gets the install name the library is identified by, which only
+    /// dynamic libraries have.
+    pub fn install_name(&self) -> Option<&str> {
+        self.dylibs()
+            .find(|dylib| dylib.kind == DylibKind::Id)
+            .map(|dylib| dylib.name.as_str())
+    }
+
+    /// Iterates over the libraries the file loads, in the order used by the
+    /// library ordinals of its imports and re-exports.
+    pub fn dependencies(&self) -> impl Iterator<Item = &DylibCommand> {
+        self.dylibs().filter(|dylib| dylib.kind != DylibKind::Id)
+    }
+
+    /// Iterates over the paths which `@rpath` install names are searched
+    /// for in, before any of `@loader_path` and `@executable_path` are
+    /// substituted.
+    pub fn rpaths(&self) -> impl Iterator<Item = &str> {
+        self.load_commands
+            .iter()
+            .filter_map(|command| match command {
+                LoadCommand::Rpath(path) => Some(path.as_str()),
+                _ => None,
+            })
+    }
+
+    /// Reads the symbols the file exports from its export trie, which is
+    /// either in its own command or part of the loader information.
+    pub fn exports<R: Read + Seek>(&self, reader: &mut R) -> Result<Vec<Export>, ParseError> {
+        let trie = self.load_commands.iter().find_map(|command| match command {
+            LoadCommand::LinkEditData(data) if data.command == LC_DYLD_EXPORTS_TRIE => {
+                Some((data.offset, data.size))
+            }
+            LoadCommand::DyldInfo(info) if info.export_size != 0 => {
+                Some((info.export_offset, info.export_size))
+            }
+            _ => None,
+        });
+        let (offset, size) = match trie {
+            Some(trie) => trie,
+            None => return Ok(Vec::new()),
+        };
+
+        let offset = self.offset + u64::from(offset);
+        check_bounds(reader, offset, size.into())?;
+        reader.seek(SeekFrom::Start(offset))?;
+        let trie = read_vec(reader, size as usize)?;
+        export::parse_trie(&trie)
+    }
+
+    fn dylibs(&self) -> impl Iterator<Item = &DylibCommand> {
+        self.load_commands
+            .iter()
+            .filter_map(|command| match command {
+                LoadCommand::Dylib(dylib) => Some(dylib),
+                _ => None,
+            })
+    }
+}
@@ -0,0 +1,283 @@
+//! Resolves the tree of dynamic libraries a Mach-O file needs, following the
+//! search order of `dyld`. Libraries which macOS only ships inside the shared
+//! cache aren't on disk, so they are only found within a sysroot which has
+//! them extracted.
+
+use crate::errors::ParseError;
+use crate::macho::header::CPU_SUBTYPE_MASK;
+use crate::macho::DylibKind;
+use crate::macho::MachODescriptor;
+use crate::shared::rooted;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::env;
+use std::fs::File;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// A library needed by a file, along with the libraries it needs in turn.
+pub struct Dependency {
+    /// The install name the library was requested by (e.g.
+    /// `@rpath/libfoo.dylib`).
+    pub name: String,
+    /// Where the library was found, if it was found at all.
+    pub path: Option<PathBuf>,
+    /// Whether the library was already loaded for an earlier request, in
+    /// which case its own dependencies are only listed under that request.
+    pub repeated: bool,
+    /// Whether the library is weakly linked, so the file can still be loaded
+    /// when it is missing.
+    pub weak: bool,
+    pub dependencies: Vec<Dependency>,
+}
+
+pub struct DependencyResolver {
+    /// A directory which all absolute paths are taken relative to.
+    pub sysroot: Option<PathBuf>,
+    /// The directories searched as if they were in `DYLD_LIBRARY_PATH`.
+    pub library_path: Vec<PathBuf>,
+    /// The directories searched as if they were in
+    /// `DYLD_FALLBACK_LIBRARY_PATH`, once the install name itself wasn't
+    /// found.
+    pub fallback_library_path: Vec<PathBuf>,
+    /// The architecture to resolve a universal file for, such as `arm64`.
+    /// When unset, the first slice is used. Libraries are always loaded for
+    /// the same CPU type as the file.
+    pub architecture: Option<String>,
+}
+
+impl DependencyResolver {
+    pub fn new() -> Self {
+        Self {
+            sysroot: None,
+            library_path: Vec::new(),
+            fallback_library_path: default_fallback_path(),
+            architecture: None,
+        }
+    }
+
+    /// Creates a resolver which searches the directories in the
+    /// `DYLD_LIBRARY_PATH` and `DYLD_FALLBACK_LIBRARY_PATH` of the current
+    /// process.
+    pub fn from_env() -> Self {
+        let paths =
+            |variable| env::var_os(variable).map(|paths| env::split_paths(&paths).collect());
+
+        Self {
+            sysroot: None,
+            library_path: paths("DYLD_LIBRARY_PATH").unwrap_or_default(),
+            fallback_library_path: paths("DYLD_FALLBACK_LIBRARY_PATH")
+                .unwrap_or_else(default_fallback_path),
+            architecture: None,
+        }
+    }
+
+    /// Builds the tree of libraries which would be loaded alongside the file.
+    pub fn resolve(&self, path: &Path) -> Result<Dependency, ParseError> {
+        let mut file = File::open(path)?;
+        let descriptor =
+            MachODescriptor::parse_architecture(&mut file, self.architecture.as_deref())?;
+        let object = LoadedObject::new(path, descriptor);
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut loaded = HashMap::new();
+        if let Some(install_name) = object.descriptor.install_name() {
+            loaded.insert(install_name.to_string(), path.to_path_buf());
+        }
+
+        let mut nodes = vec![Node {
+            name,
+            path: Some(path.to_path_buf()),
+            repeated: false,
+            weak: false,
+            parent: None,
+            object: Some(object),
+            children: Vec::new(),
+        }];
+        let mut queue = VecDeque::new();
+        queue.push_back(0);
+        while let Some(index) = queue.pop_front() {
+            let needed = match &nodes[index].object {
+                Some(object) => object
+                    .descriptor
+                    .dependencies()
+                    .map(|dylib| (dylib.name.clone(), dylib.kind == DylibKind::Weak))
+                    .collect::<Vec<_>>(),
+                None => continue,
+            };
+
+            for (name, weak) in needed {
+                let child = nodes.len();
+                let found = self.search(&nodes, index, &name);
+                let repeated = matches!(&found, Some((path, _))
+                    if loaded.values().any(|loaded| loaded == path));
+                let path = found.as_ref().map(|(path, _)| path.clone());
+                let object = match found {
+                    Some((path, object)) if !repeated => {
+                        loaded.insert(name.clone(), path);
+                        queue.push_back(child);
+                        Some(object)
+                    }
+                    _ => None,
+                };
+
+                nodes.push(Node {
+                    name,
+                    path,
+                    repeated,
+                    weak,
+                    parent: Some(index),
+                    object,
+                    children: Vec::new(),
+                });
+                nodes[index].children.push(child);
+            }
+        }
+
+        Ok(build_tree(&mut nodes, 0))
+    }
+
+    /// Searches for the library `name` on behalf of the object at `requester`.
+    fn search(
+        &self,
+        nodes: &[Node],
+        requester: usize,
+        name: &str,
+    ) -> Option<(PathBuf, LoadedObject)> {
+        let object = nodes[requester].object.as_ref()?;
+        let leaf = Path::new(name).file_name()?;
+        let sysroot = self.sysroot.as_deref();
+        let found = self
+            .library_path
+            .iter()
+            .find_map(|directory| self.try_load(&rooted(sysroot, &directory.join(leaf)), object));
+        if found.is_some() {
+            return found;
+        }
+
+        // The run paths of the requester are searched first, followed by
+        // those of each object which loaded it in turn
+        let found = if let Some(relative) = name.strip_prefix("@rpath/") {
+            let mut ancestor = Some(requester);
+            let mut found = None;
+            while let (Some(index), None) = (ancestor, &found) {
+                if let Some(loader) = &nodes[index].object {
+                    found = loader.descriptor.rpaths().find_map(|rpath| {
+                        let directory = self.expand(rpath, loader, nodes)?;
+                        self.try_load(&directory.join(relative), object)
+                    });
+                }
+                ancestor = nodes[index].parent;
+            }
+            found
+        } else {
+            self.expand(name, object, nodes)
+                .and_then(|path| self.try_load(&path, object))
+        };
+
+        found.or_else(|| {
+            self.fallback_library_path.iter().find_map(|directory| {
+                self.try_load(&rooted(sysroot, &directory.join(leaf)), object)
+            })
+        })
+    }
+
+    /// Loads the object at `path`, or its slice which can be loaded into the
+    /// same process as `requester`. A slice for the same CPU subtype, such as
+    /// `arm64e`, is preferred over one which only has the same CPU type.
+    fn try_load(&self, path: &Path, requester: &LoadedObject) -> Option<(PathBuf, LoadedObject)> {
+        let mut file = File::open(path).ok()?;
+        let cpu_type = requester.descriptor.header.cpu_type;
+        let cpu_subtype = requester.descriptor.header.cpu_subtype & CPU_SUBTYPE_MASK;
+        let descriptor = MachODescriptor::parse_matching(&mut file, |other, other_subtype| {
+            other == cpu_type && other_subtype & CPU_SUBTYPE_MASK == cpu_subtype
+        })
+        .ok()
+        .flatten()
+        .or_else(|| {
+            MachODescriptor::parse_matching(&mut file, |other, _| other == cpu_type)
+                .ok()
+                .flatten()
+        })?;
+
+        Some((path.to_path_buf(), LoadedObject::new(path, descriptor)))
+    }
+
+    /// Substitutes `@loader_path` and `@executable_path` at the start of a
+    /// path, which are already within the sysroot unlike absolute paths. Any
+    /// other use of `@` can't be resolved.
+    fn expand(&self, path: &str, loader: &LoadedObject, nodes: &[Node]) -> Option<PathBuf> {
+        if let Some(relative) = path.strip_prefix("@loader_path") {
+            return Some(loader.origin.join(relative.trim_start_matches('/')));
+        }
+
+        if let Some(relative) = path.strip_prefix("@executable_path") {
+            let executable = nodes.first()?.object.as_ref()?;
+            return Some(executable.origin.join(relative.trim_start_matches('/')));
+        }
+
+        if path.starts_with('@') {
+            None
+        } else {
+            Some(rooted(self.sysroot.as_deref(), Path::new(path)))
+        }
+    }
+}
+
+impl Default for DependencyResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The details of an object which affect how its dependencies are found.
+struct LoadedObject {
+    descriptor: MachODescriptor,
+    /// The directory holding the object, which `@loader_path` stands for.
+    origin: PathBuf,
+}
+
+impl LoadedObject {
+    fn new(path: &Path, descriptor: MachODescriptor) -> Self {
+        Self {
+            descriptor,
+            origin: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        }
+    }
+}
+
+/// A dependency while the tree is being built.
+struct Node {
+    name: String,
+    path: Option<PathBuf>,
+    repeated: bool,
+    weak: bool,
+    parent: Option<usize>,
+    object: Option<LoadedObject>,
+    children: Vec<usize>,
+}
+
+fn build_tree(nodes: &mut Vec<Node>, index: usize) -> Dependency {
+    let children = std::mem::take(&mut nodes[index].children);
+    let dependencies = children
+        .into_iter()
+        .map(|child| build_tree(nodes, child))
+        .collect();
+    let node = &mut nodes[index];
+
+    Dependency {
+        name: std::mem::take(&mut node.name),
+        path: node.path.take(),
+        repeated: node.repeated,
+        weak: node.weak,
+        dependencies,
+    }
+}
+
+/// The directories `dyld` falls back to when `DYLD_FALLBACK_LIBRARY_PATH` is
+/// unset.
+fn default_fallback_path() -> Vec<PathBuf> {
+    vec![PathBuf::from("/usr/local/lib"), PathBuf::from("/usr/lib")]
+}
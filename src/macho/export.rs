@@ -0,0 +1,144 @@
+use crate::errors::ParseError;
+use std::collections::HashSet;
+
+pub const EXPORT_SYMBOL_FLAGS_KIND_MASK: u64 = 0x03;
+pub const EXPORT_SYMBOL_FLAGS_KIND_REGULAR: u64 = 0x00;
+pub const EXPORT_SYMBOL_FLAGS_KIND_THREAD_LOCAL: u64 = 0x01;
+pub const EXPORT_SYMBOL_FLAGS_KIND_ABSOLUTE: u64 = 0x02;
+pub const EXPORT_SYMBOL_FLAGS_WEAK_DEFINITION: u64 = 0x04;
+pub const EXPORT_SYMBOL_FLAGS_REEXPORT: u64 = 0x08;
+pub const EXPORT_SYMBOL_FLAGS_STUB_AND_RESOLVER: u64 = 0x10;
+
+/// A symbol the file makes available to the files which load it.
+pub struct Export {
+    /// The name of the symbol, including the leading underscore of C names.
+    pub name: String,
+    pub flags: u64,
+    pub kind: ExportKind,
+}
+
+impl Export {
+    pub fn is_weak(&self) -> bool {
+        self.flags & EXPORT_SYMBOL_FLAGS_WEAK_DEFINITION != 0
+    }
+
+    pub fn is_thread_local(&self) -> bool {
+        self.flags & EXPORT_SYMBOL_FLAGS_KIND_MASK == EXPORT_SYMBOL_FLAGS_KIND_THREAD_LOCAL
+    }
+
+    pub fn is_absolute(&self) -> bool {
+        self.flags & EXPORT_SYMBOL_FLAGS_KIND_MASK == EXPORT_SYMBOL_FLAGS_KIND_ABSOLUTE
+    }
+}
+
+pub enum ExportKind {
+    /// A symbol defined by the file, at an offset from its header.
+    Regular { address: u64 },
+    /// A symbol defined by one of the libraries the file loads.
+    Reexport {
+        /// The one based position of the library among the libraries the
+        /// file loads.
+        library_ordinal: u64,
+        /// The name of the symbol in that library, if it differs.
+        imported_name: Option<String>,
+    },
+    /// A function whose address is decided by calling the resolver when the
+    /// stub is first called.
+    StubAndResolver { stub: u64, resolver: u64 },
+}
+
+/// Walks the export trie, whose edges spell out the names of the symbols and
+/// whose terminal nodes describe them.
+pub(crate) fn parse_trie(trie: &[u8]) -> Result<Vec<Export>, ParseError> {
+    let mut exports = Vec::new();
+    if trie.is_empty() {
+        return Ok(exports);
+    }
+
+    // Nodes are visited at most once, so that a malformed trie can't loop
+    let mut visited = HashSet::new();
+    let mut pending = vec![(0, Vec::new())];
+    while let Some((offset, name)) = pending.pop() {
+        if !visited.insert(offset) {
+            return Err(ParseError::InvalidValue {
+                value: (offset as u64).to_le_bytes().into(),
+            });
+        }
+
+        let mut reader = trie.get(offset..).unwrap_or_default();
+        let terminal_size = read_uleb128(&mut reader)? as usize;
+        let mut terminal = reader.get(..terminal_size).ok_or(ParseError::MissingData)?;
+        reader = &reader[terminal_size..];
+        if terminal_size != 0 {
+            exports.push(parse_terminal(&mut terminal, &name)?);
+        }
+
+        let child_count = *reader.first().ok_or(ParseError::MissingData)?;
+        reader = &reader[1..];
+        let mut children = Vec::with_capacity(child_count.into());
+        for _ in 0..child_count {
+            let length = reader
+                .iter()
+                .position(|byte| *byte == 0)
+                .ok_or(ParseError::MissingData)?;
+            let mut child_name = name.clone();
+            child_name.extend_from_slice(&reader[..length]);
+            reader = &reader[length + 1..];
+            children.push((read_uleb128(&mut reader)? as usize, child_name));
+        }
+
+        // Children are pushed in reverse so that they are visited in order
+        pending.extend(children.into_iter().rev());
+    }
+
+    Ok(exports)
+}
+
+fn parse_terminal(reader: &mut &[u8], name: &[u8]) -> Result<Export, ParseError> {
+    let flags = read_uleb128(reader)?;
+    let kind = if flags & EXPORT_SYMBOL_FLAGS_REEXPORT != 0 {
+        let library_ordinal = read_uleb128(reader)?;
+        let length = reader.iter().position(|byte| *byte == 0).unwrap_or(0);
+        let imported_name = match length {
+            0 => None,
+            _ => Some(String::from_utf8_lossy(&reader[..length]).into_owned()),
+        };
+        ExportKind::Reexport {
+            library_ordinal,
+            imported_name,
+        }
+    } else if flags & EXPORT_SYMBOL_FLAGS_STUB_AND_RESOLVER != 0 {
+        let stub = read_uleb128(reader)?;
+        let resolver = read_uleb128(reader)?;
+        ExportKind::StubAndResolver { stub, resolver }
+    } else {
+        let address = read_uleb128(reader)?;
+        ExportKind::Regular { address }
+    };
+
+    Ok(Export {
+        name: String::from_utf8_lossy(name).into_owned(),
+        flags,
+        kind,
+    })
+}
+
+fn read_uleb128(reader: &mut &[u8]) -> Result<u64, ParseError> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let (byte, rest) = reader.split_first().ok_or(ParseError::MissingData)?;
+        *reader = rest;
+        if shift >= 64 {
+            return Err(ParseError::InvalidValue {
+                value: Box::new([*byte]),
+            });
+        }
+
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
@@ -0,0 +1,109 @@
+use crate::errors::ParseError;
+use crate::macho::CpuType;
+use crate::macho::MachODescriptor;
+use crate::parsing::read_bytes;
+use crate::parsing::Parse;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+pub const FAT_MAGIC: u32 = 0xcafe_babe;
+pub const FAT_MAGIC_64: u32 = 0xcafe_babf;
+
+/// A universal file, which holds a complete Mach-O file for each of the
+/// architectures it supports. Its headers are always big endian.
+pub struct FatBinary {
+    pub architectures: Vec<FatArch>,
+}
+
+impl FatBinary {
+    /// Finds the slice for the named architecture, such as `arm64`.
+    pub fn architecture(&self, name: &str) -> Option<&FatArch> {
+        self.architectures
+            .iter()
+            .find(|architecture| architecture.name() == Some(name))
+    }
+}
+
+impl Parse for FatBinary {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        let magic = read_bytes::<R, 4>(reader)?;
+        let wide = match u32::from_be_bytes(magic) {
+            FAT_MAGIC => false,
+            FAT_MAGIC_64 => true,
+            _ => {
+                return Err(ParseError::InvalidValue {
+                    value: magic.into(),
+                })
+            }
+        };
+
+        let count = u32::parse_from_endianess(reader, Endianess::Big)?;
+        let architectures = (0..count)
+            .map(|_| FatArch::parse(reader, wide))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { architectures })
+    }
+}
+
+/// The location of the Mach-O file for one architecture.
+pub struct FatArch {
+    pub cpu_type: CpuType,
+    pub cpu_subtype: u32,
+    pub offset: u64,
+    pub size: u64,
+    /// The alignment of the slice, as a power of two.
+    pub align: u32,
+}
+
+impl FatArch {
+    fn parse<R: Read>(reader: &mut R, wide: bool) -> Result<Self, ParseError> {
+        let cpu_type = CpuType::from(u32::parse_from_endianess(reader, Endianess::Big)?);
+        let cpu_subtype = u32::parse_from_endianess(reader, Endianess::Big)?;
+        let (offset, size) = if wide {
+            (
+                u64::parse_from_endianess(reader, Endianess::Big)?,
+                u64::parse_from_endianess(reader, Endianess::Big)?,
+            )
+        } else {
+            (
+                u32::parse_from_endianess(reader, Endianess::Big)?.into(),
+                u32::parse_from_endianess(reader, Endianess::Big)?.into(),
+            )
+        };
+        let align = u32::parse_from_endianess(reader, Endianess::Big)?;
+        if wide {
+            // reserved
+            u32::parse_from_endianess(reader, Endianess::Big)?;
+        }
+
+        Ok(Self {
+            cpu_type,
+            cpu_subtype,
+            offset,
+            size,
+            align,
+        })
+    }
+
+    /// Gets the name tools such as `lipo` use for the architecture.
+    pub fn name(&self) -> Option<&'static str> {
+        self.cpu_type.architecture_name(self.cpu_subtype)
+    }
+
+    /// Parses the Mach-O file held by the slice, whose offsets are relative
+    /// to the start of the slice.
+    pub fn parse_descriptor<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<MachODescriptor, ParseError> {
+        reader.seek(SeekFrom::Start(self.offset))?;
+        let mut descriptor = MachODescriptor::parse(reader)?;
+        descriptor.offset = self.offset;
+
+        Ok(descriptor)
+    }
+}
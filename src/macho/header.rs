@@ -0,0 +1,204 @@
+use crate::errors::ParseError;
+use crate::parsing::read_bytes;
+use crate::parsing::Parse;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::io::Read;
+
+pub const MH_MAGIC: u32 = 0xfeed_face;
+pub const MH_MAGIC_64: u32 = 0xfeed_facf;
+
+pub const MH_NOUNDEFS: u32 = 0x0000_0001;
+pub const MH_DYLDLINK: u32 = 0x0000_0004;
+pub const MH_TWOLEVEL: u32 = 0x0000_0080;
+pub const MH_WEAK_DEFINES: u32 = 0x0000_8000;
+pub const MH_BINDS_TO_WEAK: u32 = 0x0001_0000;
+pub const MH_PIE: u32 = 0x0020_0000;
+pub const MH_NO_REEXPORTED_DYLIBS: u32 = 0x0010_0000;
+pub const MH_APP_EXTENSION_SAFE: u32 = 0x0200_0000;
+
+/// The flag marking 64-bit variants of a CPU type.
+pub const CPU_ARCH_ABI64: u32 = 0x0100_0000;
+/// The flag marking CPU types which use 32-bit pointers on a 64-bit CPU.
+pub const CPU_ARCH_ABI64_32: u32 = 0x0200_0000;
+
+/// The mask of the subtype bits which aren't capabilities of the CPU.
+pub const CPU_SUBTYPE_MASK: u32 = 0x00ff_ffff;
+
+/// The header which starts every Mach-O file, or each slice of a universal
+/// file.
+pub struct MachHeader {
+    pub width: ArchitectureWidth,
+    pub endianess: Endianess,
+    pub cpu_type: CpuType,
+    pub cpu_subtype: u32,
+    pub file_type: FileType,
+    pub command_count: u32,
+    pub commands_size: u32,
+    pub flags: u32,
+}
+
+impl MachHeader {
+    /// Gets the name tools such as `lipo` use for the architecture.
+    pub fn architecture_name(&self) -> Option<&'static str> {
+        self.cpu_type.architecture_name(self.cpu_subtype)
+    }
+
+    /// The size of the header, which the load commands follow.
+    pub fn size(&self) -> u32 {
+        match self.width {
+            ArchitectureWidth::ThirtyTwo => 28,
+            ArchitectureWidth::SixtyFour => 32,
+        }
+    }
+}
+
+impl Parse for MachHeader {
+    fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
+        // The magic number is written in the byte order of the file
+        let magic = read_bytes::<R, 4>(reader)?;
+        let (width, endianess) = match u32::from_be_bytes(magic) {
+            MH_MAGIC => (ArchitectureWidth::ThirtyTwo, Endianess::Big),
+            MH_MAGIC_64 => (ArchitectureWidth::SixtyFour, Endianess::Big),
+            other if other.swap_bytes() == MH_MAGIC => {
+                (ArchitectureWidth::ThirtyTwo, Endianess::Little)
+            }
+            other if other.swap_bytes() == MH_MAGIC_64 => {
+                (ArchitectureWidth::SixtyFour, Endianess::Little)
+            }
+            _ => {
+                return Err(ParseError::InvalidValue {
+                    value: magic.into(),
+                })
+            }
+        };
+
+        let cpu_type = CpuType::from(u32::parse_from_endianess(reader, endianess)?);
+        let cpu_subtype = u32::parse_from_endianess(reader, endianess)?;
+        let file_type = FileType::from(u32::parse_from_endianess(reader, endianess)?);
+        let command_count = u32::parse_from_endianess(reader, endianess)?;
+        let commands_size = u32::parse_from_endianess(reader, endianess)?;
+        let flags = u32::parse_from_endianess(reader, endianess)?;
+        if let ArchitectureWidth::SixtyFour = width {
+            // reserved
+            u32::parse_from_endianess(reader, endianess)?;
+        }
+
+        Ok(Self {
+            width,
+            endianess,
+            cpu_type,
+            cpu_subtype,
+            file_type,
+            command_count,
+            commands_size,
+            flags,
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ArchitectureWidth {
+    ThirtyTwo,
+    SixtyFour,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CpuType {
+    X86,
+    X86_64,
+    Arm,
+    Arm64,
+    Arm64_32,
+    PowerPc,
+    PowerPc64,
+    Other(u32),
+}
+
+impl CpuType {
+    pub fn value(self) -> u32 {
+        match self {
+            Self::X86 => 7,
+            Self::X86_64 => 7 | CPU_ARCH_ABI64,
+            Self::Arm => 12,
+            Self::Arm64 => 12 | CPU_ARCH_ABI64,
+            Self::Arm64_32 => 12 | CPU_ARCH_ABI64_32,
+            Self::PowerPc => 18,
+            Self::PowerPc64 => 18 | CPU_ARCH_ABI64,
+            Self::Other(value) => value,
+        }
+    }
+
+    /// Gets the name tools such as `lipo` use for the CPU type and subtype,
+    /// which distinguishes variants of an architecture like `arm64e`.
+    pub fn architecture_name(self, cpu_subtype: u32) -> Option<&'static str> {
+        Some(match (self, cpu_subtype & CPU_SUBTYPE_MASK) {
+            (Self::X86, _) => "i386",
+            (Self::X86_64, 8) => "x86_64h",
+            (Self::X86_64, _) => "x86_64",
+            (Self::Arm, 6) => "armv6",
+            (Self::Arm, 9) => "armv7",
+            (Self::Arm, 11) => "armv7s",
+            (Self::Arm, 12) => "armv7k",
+            (Self::Arm, _) => "arm",
+            (Self::Arm64, 2) => "arm64e",
+            (Self::Arm64, _) => "arm64",
+            (Self::Arm64_32, _) => "arm64_32",
+            (Self::PowerPc, _) => "ppc",
+            (Self::PowerPc64, _) => "ppc64",
+            (Self::Other(_), _) => return None,
+        })
+    }
+}
+
+impl From<u32> for CpuType {
+    fn from(value: u32) -> Self {
+        match value {
+            7 => Self::X86,
+            0x0100_0007 => Self::X86_64,
+            12 => Self::Arm,
+            0x0100_000c => Self::Arm64,
+            0x0200_000c => Self::Arm64_32,
+            18 => Self::PowerPc,
+            0x0100_0012 => Self::PowerPc64,
+            other => Self::Other(other),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FileType {
+    Object,
+    Execute,
+    FixedVmLibrary,
+    Core,
+    Preload,
+    Dylib,
+    Dylinker,
+    Bundle,
+    DylibStub,
+    Dsym,
+    KextBundle,
+    FileSet,
+    Other(u32),
+}
+
+impl From<u32> for FileType {
+    fn from(value: u32) -> Self {
+        match value {
+            0x1 => Self::Object,
+            0x2 => Self::Execute,
+            0x3 => Self::FixedVmLibrary,
+            0x4 => Self::Core,
+            0x5 => Self::Preload,
+            0x6 => Self::Dylib,
+            0x7 => Self::Dylinker,
+            0x8 => Self::Bundle,
+            0x9 => Self::DylibStub,
+            0xa => Self::Dsym,
+            0xb => Self::KextBundle,
+            0xc => Self::FileSet,
+            other => Self::Other(other),
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use crate::errors::ParseError;
+use crate::macho::ArchitectureWidth;
+use crate::macho::MachHeader;
+use crate::parsing::read_bytes;
+use crate::parsing::read_vec;
+use crate::parsing::ParseFromEndianess;
+use crate::shared::Endianess;
+use std::fmt;
+use std::io::Read;
+
+/// The flag on commands which the loader must understand to load the file.
+pub const LC_REQ_DYLD: u32 = 0x8000_0000;
+
+pub const LC_SEGMENT: u32 = 0x01;
+pub const LC_SYMTAB: u32 = 0x02;
+pub const LC_DYSYMTAB: u32 = 0x0b;
+pub const LC_LOAD_DYLIB: u32 = 0x0c;
+pub const LC_ID_DYLIB: u32 = 0x0d;
+pub const LC_LOAD_DYLINKER: u32 = 0x0e;
+pub const LC_LOAD_WEAK_DYLIB: u32 = 0x18 | LC_REQ_DYLD;
+pub const LC_SEGMENT_64: u32 = 0x19;
+pub const LC_UUID: u32 = 0x1b;
+pub const LC_RPATH: u32 = 0x1c | LC_REQ_DYLD;
+pub const LC_CODE_SIGNATURE: u32 = 0x1d;
+pub const LC_SEGMENT_SPLIT_INFO: u32 = 0x1e;
+pub const LC_REEXPORT_DYLIB: u32 = 0x1f | LC_REQ_DYLD;
+pub const LC_LAZY_LOAD_DYLIB: u32 = 0x20;
+pub const LC_DYLD_INFO: u32 = 0x22;
+pub const LC_DYLD_INFO_ONLY: u32 = 0x22 | LC_REQ_DYLD;
+pub const LC_LOAD_UPWARD_DYLIB: u32 = 0x23 | LC_REQ_DYLD;
+pub const LC_FUNCTION_STARTS: u32 = 0x26;
+pub const LC_MAIN: u32 = 0x28 | LC_REQ_DYLD;
+pub const LC_DATA_IN_CODE: u32 = 0x29;
+pub const LC_DYLIB_CODE_SIGN_DRS: u32 = 0x2b;
+pub const LC_LINKER_OPTIMIZATION_HINT: u32 = 0x2e;
+pub const LC_DYLD_EXPORTS_TRIE: u32 = 0x33 | LC_REQ_DYLD;
+pub const LC_DYLD_CHAINED_FIXUPS: u32 = 0x34 | LC_REQ_DYLD;
+
+pub const VM_PROT_READ: u32 = 0x1;
+pub const VM_PROT_WRITE: u32 = 0x2;
+pub const VM_PROT_EXECUTE: u32 = 0x4;
+
+/// The mask of the section type within the section flags.
+pub const SECTION_TYPE: u32 = 0x0000_00ff;
+pub const S_ATTR_PURE_INSTRUCTIONS: u32 = 0x8000_0000;
+pub const S_ATTR_SOME_INSTRUCTIONS: u32 = 0x0000_0400;
+
+/// One of the commands following the header, which describe how the file is
+/// laid out in memory and what it needs to be loaded.
+pub enum LoadCommand {
+    Segment(Segment),
+    Dylib(DylibCommand),
+    Rpath(String),
+    DyldInfo(DyldInfo),
+    /// One of the commands which locate data within the `__LINKEDIT`
+    /// segment, such as the export trie or the code signature.
+    LinkEditData(LinkEditData),
+    Uuid([u8; 16]),
+    Other {
+        command: u32,
+        data: Box<[u8]>,
+    },
+}
+
+impl LoadCommand {
+    /// Parses the commands which follow the header.
+    pub(crate) fn parse_commands<R: Read>(
+        reader: &mut R,
+        header: &MachHeader,
+    ) -> Result<Vec<Self>, ParseError> {
+        (0..header.command_count)
+            .map(|_| Self::parse(reader, header))
+            .collect()
+    }
+
+    fn parse<R: Read>(reader: &mut R, header: &MachHeader) -> Result<Self, ParseError> {
+        let endianess = header.endianess;
+        let command = u32::parse_from_endianess(reader, endianess)?;
+        let size = u32::parse_from_endianess(reader, endianess)?;
+        let size = size.checked_sub(8).ok_or(ParseError::InvalidValue {
+            value: size.to_ne_bytes().into(),
+        })?;
+
+        // Commands are parsed from their own bytes, which strings within them
+        // are found by the offset of
+        let data = read_vec(reader, size as usize)?;
+        let reader = &mut &data[..];
+        Ok(match command {
+            LC_SEGMENT | LC_SEGMENT_64 => Self::Segment(Segment::parse(reader, header)?),
+            LC_ID_DYLIB | LC_LOAD_DYLIB | LC_LOAD_WEAK_DYLIB | LC_REEXPORT_DYLIB
+            | LC_LAZY_LOAD_DYLIB | LC_LOAD_UPWARD_DYLIB => {
+                let kind = DylibKind::from_command(command);
+                let name = command_string(&data, reader, endianess)?;
+                Self::Dylib(DylibCommand {
+                    kind,
+                    name,
+                    timestamp: u32::parse_from_endianess(reader, endianess)?,
+                    current_version: Version(u32::parse_from_endianess(reader, endianess)?),
+                    compatibility_version: Version(u32::parse_from_endianess(reader, endianess)?),
+                })
+            }
+            LC_RPATH => Self::Rpath(command_string(&data, reader, endianess)?),
+            LC_DYLD_INFO | LC_DYLD_INFO_ONLY => Self::DyldInfo(DyldInfo {
+                rebase_offset: u32::parse_from_endianess(reader, endianess)?,
+                rebase_size: u32::parse_from_endianess(reader, endianess)?,
+                bind_offset: u32::parse_from_endianess(reader, endianess)?,
+                bind_size: u32::parse_from_endianess(reader, endianess)?,
+                weak_bind_offset: u32::parse_from_endianess(reader, endianess)?,
+                weak_bind_size: u32::parse_from_endianess(reader, endianess)?,
+                lazy_bind_offset: u32::parse_from_endianess(reader, endianess)?,
+                lazy_bind_size: u32::parse_from_endianess(reader, endianess)?,
+                export_offset: u32::parse_from_endianess(reader, endianess)?,
+                export_size: u32::parse_from_endianess(reader, endianess)?,
+            }),
+            LC_CODE_SIGNATURE
+            | LC_SEGMENT_SPLIT_INFO
+            | LC_FUNCTION_STARTS
+            | LC_DATA_IN_CODE
+            | LC_DYLIB_CODE_SIGN_DRS
+            | LC_LINKER_OPTIMIZATION_HINT
+            | LC_DYLD_EXPORTS_TRIE
+            | LC_DYLD_CHAINED_FIXUPS => Self::LinkEditData(LinkEditData {
+                command,
+                offset: u32::parse_from_endianess(reader, endianess)?,
+                size: u32::parse_from_endianess(reader, endianess)?,
+            }),
+            LC_UUID => Self::Uuid(read_bytes::<_, 16>(reader)?),
+            command => Self::Other {
+                command,
+                data: data.into(),
+            },
+        })
+    }
+}
+
+/// A range of the file mapped into memory, along with the sections within it.
+pub struct Segment {
+    pub name: String,
+    pub vm_address: u64,
+    pub vm_size: u64,
+    pub file_offset: u64,
+    pub file_size: u64,
+    pub max_protection: u32,
+    pub initial_protection: u32,
+    pub flags: u32,
+    pub sections: Vec<Section>,
+}
+
+impl Segment {
+    fn parse(reader: &mut &[u8], header: &MachHeader) -> Result<Self, ParseError> {
+        let endianess = header.endianess;
+        let name = fixed_string(reader)?;
+        let vm_address = parse_native(reader, header)?;
+        let vm_size = parse_native(reader, header)?;
+        let file_offset = parse_native(reader, header)?;
+        let file_size = parse_native(reader, header)?;
+        let max_protection = u32::parse_from_endianess(reader, endianess)?;
+        let initial_protection = u32::parse_from_endianess(reader, endianess)?;
+        let section_count = u32::parse_from_endianess(reader, endianess)?;
+        let flags = u32::parse_from_endianess(reader, endianess)?;
+        let sections = (0..section_count)
+            .map(|_| Section::parse(reader, header))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            name,
+            vm_address,
+            vm_size,
+            file_offset,
+            file_size,
+            max_protection,
+            initial_protection,
+            flags,
+            sections,
+        })
+    }
+}
+
+pub struct Section {
+    pub name: String,
+    pub segment_name: String,
+    pub address: u64,
+    pub size: u64,
+    pub offset: u32,
+    /// The alignment of the section, as a power of two.
+    pub align: u32,
+    pub relocations_offset: u32,
+    pub relocation_count: u32,
+    pub flags: u32,
+    pub reserved1: u32,
+    pub reserved2: u32,
+}
+
+impl Section {
+    fn parse(reader: &mut &[u8], header: &MachHeader) -> Result<Self, ParseError> {
+        let endianess = header.endianess;
+        let name = fixed_string(reader)?;
+        let segment_name = fixed_string(reader)?;
+        let address = parse_native(reader, header)?;
+        let size = parse_native(reader, header)?;
+        let offset = u32::parse_from_endianess(reader, endianess)?;
+        let align = u32::parse_from_endianess(reader, endianess)?;
+        let relocations_offset = u32::parse_from_endianess(reader, endianess)?;
+        let relocation_count = u32::parse_from_endianess(reader, endianess)?;
+        let flags = u32::parse_from_endianess(reader, endianess)?;
+        let reserved1 = u32::parse_from_endianess(reader, endianess)?;
+        let reserved2 = u32::parse_from_endianess(reader, endianess)?;
+        if let ArchitectureWidth::SixtyFour = header.width {
+            // reserved3
+            u32::parse_from_endianess(reader, endianess)?;
+        }
+
+        Ok(Self {
+            name,
+            segment_name,
+            address,
+            size,
+            offset,
+            align,
+            relocations_offset,
+            relocation_count,
+            flags,
+            reserved1,
+            reserved2,
+        })
+    }
+}
+
+/// A dynamic library which the file either is or depends on.
+pub struct DylibCommand {
+    pub kind: DylibKind,
+    /// The install name of the library, such as `/usr/lib/libSystem.B.dylib`
+    /// or `@rpath/libfoo.dylib`.
+    pub name: String,
+    pub timestamp: u32,
+    pub current_version: Version,
+    pub compatibility_version: Version,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DylibKind {
+    /// The identity of the library the file itself is.
+    Id,
+    Load,
+    /// A library the file still loads without if it is missing.
+    Weak,
+    /// A library whose exports the file exports as its own.
+    Reexport,
+    Lazy,
+    /// A library which depends on the file in turn.
+    Upward,
+}
+
+impl DylibKind {
+    fn from_command(command: u32) -> Self {
+        match command {
+            LC_ID_DYLIB => Self::Id,
+            LC_LOAD_WEAK_DYLIB => Self::Weak,
+            LC_REEXPORT_DYLIB => Self::Reexport,
+            LC_LAZY_LOAD_DYLIB => Self::Lazy,
+            LC_LOAD_UPWARD_DYLIB => Self::Upward,
+            _ => Self::Load,
+        }
+    }
+}
+
+/// A library version packed as `xxxx.yy.zz` into 16, 8 and 8 bits.
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub struct Version(pub u32);
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}",
+            self.0 >> 16,
+            (self.0 >> 8) & 0xff,
+            self.0 & 0xff
+        )
+    }
+}
+
+/// The locations of the compressed information the loader uses, which are
+/// all file offsets within the `__LINKEDIT` segment.
+pub struct DyldInfo {
+    pub rebase_offset: u32,
+    pub rebase_size: u32,
+    pub bind_offset: u32,
+    pub bind_size: u32,
+    pub weak_bind_offset: u32,
+    pub weak_bind_size: u32,
+    pub lazy_bind_offset: u32,
+    pub lazy_bind_size: u32,
+    pub export_offset: u32,
+    pub export_size: u32,
+}
+
+pub struct LinkEditData {
+    pub command: u32,
+    /// The offset of the data within the file.
+    pub offset: u32,
+    pub size: u32,
+}
+
+fn parse_native(reader: &mut &[u8], header: &MachHeader) -> Result<u64, ParseError> {
+    match header.width {
+        ArchitectureWidth::ThirtyTwo => {
+            u32::parse_from_endianess(reader, header.endianess).map(u64::from)
+        }
+        ArchitectureWidth::SixtyFour => u64::parse_from_endianess(reader, header.endianess),
+    }
+}
+
+/// Reads a name padded with nulls to 16 bytes.
+fn fixed_string(reader: &mut &[u8]) -> Result<String, ParseError> {
+    let name = read_bytes::<_, 16>(reader)?;
+    Ok(null_terminated(&name))
+}
+
+/// Reads a string which is found by its offset from the start of the command.
+fn command_string(
+    data: &[u8],
+    reader: &mut &[u8],
+    endianess: Endianess,
+) -> Result<String, ParseError> {
+    let offset = u32::parse_from_endianess(reader, endianess)?;
+    let string = (offset as usize)
+        .checked_sub(8)
+        .and_then(|offset| data.get(offset..))
+        .ok_or(ParseError::OutOfBounds {
+            offset: offset.into(),
+            size: 1,
+        })?;
+
+    Ok(null_terminated(string))
+}
+
+fn null_terminated(data: &[u8]) -> String {
+    let length = data
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..length]).into_owned()
+}
@@ -1,18 +1,343 @@
 use clap::load_yaml;
 use clap::App;
+use clap::ArgMatches;
+use dolls::dll;
+use dolls::dll::ApiSetSchema;
+use dolls::dll::Archive;
+use dolls::dll::Object;
+use dolls::dll::PeDescriptor;
+use dolls::elf::Dependency;
+use dolls::elf::DependencyResolver;
 use dolls::elf::ElfDescriptor;
+use dolls::macho;
+use dolls::macho::fat::FAT_MAGIC;
+use dolls::macho::fat::FAT_MAGIC_64;
+use dolls::macho::header::MH_MAGIC;
+use dolls::macho::header::MH_MAGIC_64;
+use dolls::macho::FatBinary;
+use dolls::macho::MachHeader;
 use dolls::Parse;
+use dolls::ParseSeekable;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
 
 fn main() {
     let yaml = load_yaml!("cli.yml");
     let matches = App::from_yaml(yaml).get_matches();
 
-    let rel_path = matches.value_of("INPUT").unwrap();
-    let mut abs_path = std::env::current_dir().unwrap();
-    abs_path.push(rel_path);
+    if let Some(matches) = matches.subcommand_matches("deps") {
+        return deps(matches);
+    }
+
+    if let Some(matches) = matches.subcommand_matches("def") {
+        return def(matches);
+    }
+
+    if let Some(matches) = matches.subcommand_matches("symbols") {
+        return symbols(matches);
+    }
+
+    if let Some(matches) = matches.subcommand_matches("assembly") {
+        return assembly(matches);
+    }
+
+    let abs_path = input_path(&matches);
 
     let mut file = std::fs::File::open(abs_path).unwrap();
-    let descriptor = ElfDescriptor::parse(&mut file).unwrap();
+    let descriptor = ElfDescriptor::parse_seekable(&mut file).unwrap();
 
     print!("{:#?}", descriptor);
 }
+
+fn deps(matches: &ArgMatches) {
+    let path = input_path(matches);
+    if is_pe_image(&path) {
+        return dll_deps(matches, &path);
+    }
+
+    if is_macho(&path) {
+        return macho_deps(matches, &path);
+    }
+
+    let mut resolver = DependencyResolver::from_env();
+    resolver.sysroot = matches.value_of("sysroot").map(PathBuf::from);
+
+    let tree = resolver.resolve(&path).unwrap();
+    print_dependency(&tree, 0);
+}
+
+fn dll_deps(matches: &ArgMatches, path: &Path) {
+    let mut resolver = dll::DependencyResolver::new();
+    resolver.sysroot = matches.value_of("sysroot").map(PathBuf::from);
+    resolver.api_set_schema = matches.value_of("api-set-schema").map(|schema| {
+        let mut file = File::open(schema).unwrap();
+        ApiSetSchema::parse_dll(&mut file).unwrap()
+    });
+
+    let tree = resolver.resolve(path).unwrap();
+    print_dll_dependency(&tree, 0);
+}
+
+fn macho_deps(matches: &ArgMatches, path: &Path) {
+    let mut resolver = macho::DependencyResolver::from_env();
+    resolver.sysroot = matches.value_of("sysroot").map(PathBuf::from);
+    resolver.architecture = matches.value_of("arch").map(String::from);
+
+    if let Some(architecture) = &resolver.architecture {
+        let architectures = macho_architectures(path);
+        if !architectures.contains(&architecture.as_str()) {
+            eprintln!(
+                "{} has no {} slice, only {}",
+                path.display(),
+                architecture,
+                architectures.join(", ")
+            );
+            std::process::exit(1);
+        }
+    }
+
+    let tree = resolver.resolve(path).unwrap();
+    print_macho_dependency(&tree, 0);
+}
+
+/// Lists the architectures of a universal file, or the one architecture of
+/// a thin file.
+fn macho_architectures(path: &Path) -> Vec<&'static str> {
+    let mut file = File::open(path).unwrap();
+    let architectures = match FatBinary::parse(&mut file) {
+        Ok(fat) => fat.architectures.iter().map(|slice| slice.name()).collect(),
+        Err(_) => {
+            file.seek(SeekFrom::Start(0)).unwrap();
+            vec![MachHeader::parse(&mut file).unwrap().architecture_name()]
+        }
+    };
+
+    architectures.into_iter().flatten().collect()
+}
+
+fn def(matches: &ArgMatches) {
+    let path = input_path(matches);
+    let mut file = File::open(&path).unwrap();
+    let descriptor = PeDescriptor::parse_seekable(&mut file).unwrap();
+    let mut definition = match descriptor.module_definition(&mut file).unwrap() {
+        Some(definition) => definition,
+        None if descriptor.clr_header(&mut file).unwrap().is_some() => {
+            eprintln!(
+                "{} has no exports, as it is a .NET assembly (see `dolls assembly`)",
+                path.display()
+            );
+            std::process::exit(1);
+        }
+        None => {
+            eprintln!("{} has no exports", path.display());
+            std::process::exit(1);
+        }
+    };
+
+    // Fall back to the name of the file for DLLs which don't record their own
+    if definition.name.is_empty() {
+        definition.name = path.file_name().unwrap().to_string_lossy().into_owned();
+    }
+
+    match matches.value_of("output") {
+        Some(output) => std::fs::write(output, definition.to_string()).unwrap(),
+        None => print!("{}", definition),
+    }
+
+    if let Some(import_library) = matches.value_of("import-library") {
+        let mut writer = BufWriter::new(File::create(import_library).unwrap());
+        definition.write_import_library(&mut writer).unwrap();
+    }
+}
+
+fn symbols(matches: &ArgMatches) {
+    let find = matches.value_of("find");
+    let print_symbols = |source: &str, object: &Object| {
+        for symbol in object.defined_symbols() {
+            if find.is_none() || find == Some(symbol.as_str()) {
+                println!("{}: {}", source, symbol);
+            }
+        }
+    };
+
+    for input in matches.values_of("INPUT").unwrap() {
+        let mut file = File::open(input).unwrap();
+        if !is_archive(Path::new(input)) {
+            print_symbols(input, &Object::parse_seekable(&mut file).unwrap());
+            continue;
+        }
+
+        // Members which can't be read, such as resources or the intermediate
+        // code of `/GL` objects, are reported and skipped
+        let archive = Archive::parse_seekable(&mut file).unwrap();
+        for member in &archive.members {
+            let source = format!("{}({})", input, member.name);
+            match archive.member_object(&mut file, member) {
+                Ok(object) => print_symbols(&source, &object),
+                Err(error) => eprintln!("{}: skipped, not a COFF object ({:?})", source, error),
+            }
+        }
+    }
+}
+
+fn assembly(matches: &ArgMatches) {
+    let path = input_path(matches);
+    let mut file = File::open(&path).unwrap();
+    let descriptor = PeDescriptor::parse_seekable(&mut file).unwrap();
+    let metadata = match descriptor.clr_metadata(&mut file).unwrap() {
+        Some(metadata) => metadata,
+        None => {
+            eprintln!("{} is not a .NET assembly", path.display());
+            std::process::exit(1);
+        }
+    };
+
+    match &metadata.assembly {
+        Some(assembly) => println!("{}", assembly),
+        None => println!("{} (module)", metadata.module_name),
+    }
+    println!("Runtime: {}", metadata.version);
+
+    println!("References:");
+    for reference in &metadata.assembly_references {
+        println!("    {}", reference);
+    }
+
+    println!("Public types:");
+    for definition in metadata
+        .type_definitions
+        .iter()
+        .filter(|definition| definition.is_public())
+    {
+        println!("    {}", definition.full_name());
+    }
+}
+
+fn print_dependency(dependency: &Dependency, depth: usize) {
+    let indent = "    ".repeat(depth);
+    match (&dependency.path, depth) {
+        (Some(path), 0) => println!("{}", path.display()),
+        (Some(path), _) if dependency.repeated => {
+            println!(
+                "{}{} => {} (already loaded)",
+                indent,
+                dependency.name,
+                path.display()
+            )
+        }
+        (Some(path), _) => println!("{}{} => {}", indent, dependency.name, path.display()),
+        (None, _) => println!("{}{} => not found", indent, dependency.name),
+    }
+
+    for child in &dependency.dependencies {
+        print_dependency(child, depth + 1);
+    }
+}
+
+fn print_dll_dependency(dependency: &dll::Dependency, depth: usize) {
+    let indent = "    ".repeat(depth);
+    let delay_loaded = if dependency.delay_loaded {
+        " (delay-loaded)"
+    } else {
+        ""
+    };
+    match (&dependency.path, depth) {
+        (Some(path), 0) => println!("{}", path.display()),
+        (Some(path), _) if dependency.repeated => println!(
+            "{}{} => {}{} (already loaded)",
+            indent,
+            dependency.name,
+            path.display(),
+            delay_loaded
+        ),
+        (Some(path), _) => println!(
+            "{}{} => {}{}",
+            indent,
+            dependency.name,
+            path.display(),
+            delay_loaded
+        ),
+        (None, _) => println!("{}{} => not found{}", indent, dependency.name, delay_loaded),
+    }
+
+    for child in &dependency.dependencies {
+        print_dll_dependency(child, depth + 1);
+    }
+}
+
+fn print_macho_dependency(dependency: &macho::Dependency, depth: usize) {
+    let indent = "    ".repeat(depth);
+    let weak = if dependency.weak { " (weak)" } else { "" };
+    match (&dependency.path, depth) {
+        (Some(path), 0) => println!("{}", path.display()),
+        (Some(path), _) if dependency.repeated => println!(
+            "{}{} => {}{} (already loaded)",
+            indent,
+            dependency.name,
+            path.display(),
+            weak
+        ),
+        (Some(path), _) => println!(
+            "{}{} => {}{}",
+            indent,
+            dependency.name,
+            path.display(),
+            weak
+        ),
+        (None, _) => println!("{}{} => not found{}", indent, dependency.name, weak),
+    }
+
+    for child in &dependency.dependencies {
+        print_macho_dependency(child, depth + 1);
+    }
+}
+
+/// Checks for the `MZ` signature which starts every PE image.
+fn is_pe_image(path: &Path) -> bool {
+    let mut magic = [0; 2];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok()
+        && &magic == b"MZ"
+}
+
+/// Checks for the magic number of a 32 or 64-bit Mach-O file, in either
+/// byte order, or of a universal file.
+fn is_macho(path: &Path) -> bool {
+    let mut header = [0; 8];
+    if File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_err()
+    {
+        return false;
+    }
+
+    // Java class files share the magic number of universal files, but follow
+    // it with their version, which is at least 45, rather than a slice count
+    let magic = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+    let count = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+    [MH_MAGIC, MH_MAGIC_64].contains(&magic)
+        || [MH_MAGIC, MH_MAGIC_64].contains(&magic.swap_bytes())
+        || ([FAT_MAGIC, FAT_MAGIC_64].contains(&magic) && count < 20)
+}
+
+/// Checks for the signature which starts every `ar` archive.
+fn is_archive(path: &Path) -> bool {
+    let mut magic = [0; 8];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok()
+        && &magic == b"!<arch>\n"
+}
+
+fn input_path(matches: &ArgMatches) -> PathBuf {
+    let rel_path = matches.value_of("INPUT").unwrap();
+    let mut abs_path = std::env::current_dir().unwrap();
+    abs_path.push(rel_path);
+    abs_path
+}
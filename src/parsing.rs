@@ -1,12 +1,19 @@
 use crate::errors::ParseError;
 use crate::shared::Endianess;
 use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
 
 pub trait Parse: Sized {
     /// Build the type from a readable sequence of bytes.
     fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError>;
 }
 
+pub trait ParseSeekable: Sized {
+    /// Build the type from a sequence of bytes which can be read out of order.
+    fn parse_seekable<R: Read + Seek>(reader: &mut R) -> Result<Self, ParseError>;
+}
+
 impl Parse for u8 {
     fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
         let byte = read_bytes::<R, 1>(reader)?;
@@ -14,6 +21,64 @@ impl Parse for u8 {
     }
 }
 
+pub(crate) trait ParseFromEndianess: Sized {
+    fn parse_from_endianess<R: Read>(
+        reader: &mut R,
+        endianess: Endianess,
+    ) -> Result<Self, ParseError>;
+}
+
+impl ParseFromEndianess for u16 {
+    fn parse_from_endianess<R: Read>(
+        reader: &mut R,
+        endianess: Endianess,
+    ) -> Result<Self, ParseError> {
+        let buffer = read_bytes::<R, 2>(reader)?;
+        Ok(match endianess {
+            Endianess::Little => u16::from_le_bytes(buffer),
+            Endianess::Big => u16::from_be_bytes(buffer),
+        })
+    }
+}
+
+impl ParseFromEndianess for u32 {
+    fn parse_from_endianess<R: Read>(
+        reader: &mut R,
+        endianess: Endianess,
+    ) -> Result<Self, ParseError> {
+        let buffer = read_bytes::<R, 4>(reader)?;
+        Ok(match endianess {
+            Endianess::Little => u32::from_le_bytes(buffer),
+            Endianess::Big => u32::from_be_bytes(buffer),
+        })
+    }
+}
+
+impl ParseFromEndianess for u64 {
+    fn parse_from_endianess<R: Read>(
+        reader: &mut R,
+        endianess: Endianess,
+    ) -> Result<u64, ParseError> {
+        let buffer = read_bytes(reader)?;
+        Ok(match endianess {
+            Endianess::Little => u64::from_le_bytes(buffer),
+            Endianess::Big => u64::from_be_bytes(buffer),
+        })
+    }
+}
+
+impl<T> ParseFromEndianess for T
+where
+    T: Parse,
+{
+    fn parse_from_endianess<R: Read>(
+        reader: &mut R,
+        endianess: Endianess,
+    ) -> Result<Self, ParseError> {
+        T::parse(reader)
+    }
+}
+
 pub trait ToByte {
     fn to_bytes(&self) -> [u8; 1];
 }
@@ -73,3 +138,25 @@ pub(crate) fn read_bytes<R: Read, const SIZE: usize>(
         Ok(buffer)
     }
 }
+
+pub(crate) fn read_vec<R: Read>(reader: &mut R, size: usize) -> Result<Vec<u8>, ParseError> {
+    let mut buffer = Vec::new();
+    if reader.take(size as u64).read_to_end(&mut buffer)? < size {
+        Err(ParseError::MissingData)
+    } else {
+        Ok(buffer)
+    }
+}
+
+/// Ensures that `size` bytes starting at `offset` are within the file.
+pub(crate) fn check_bounds<R: Seek>(
+    reader: &mut R,
+    offset: u64,
+    size: u64,
+) -> Result<(), ParseError> {
+    let file_size = reader.seek(SeekFrom::End(0))?;
+    match offset.checked_add(size) {
+        Some(end) if end <= file_size => Ok(()),
+        _ => Err(ParseError::OutOfBounds { offset, size }),
+    }
+}
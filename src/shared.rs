@@ -2,6 +2,7 @@ use crate::errors::ParseError;
 use crate::parsing::Parse;
 use std::io::Read;
 
+#[derive(Clone, Copy)]
 pub enum NativeInteger {
     ThirtyTwo(u32),
     SixtyFour(u64),
@@ -19,12 +20,33 @@ impl From<u64> for NativeInteger {
     }
 }
 
+impl From<NativeInteger> for u64 {
+    fn from(src: NativeInteger) -> Self {
+        match src {
+            NativeInteger::ThirtyTwo(val) => val.into(),
+            NativeInteger::SixtyFour(val) => val,
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum Endianess {
     Little,
     Big,
 }
 
+#[cfg(feature = "elf")]
+impl Endianess {
+    /// Gets the byte order of the machine running the program.
+    pub fn native() -> Self {
+        if cfg!(target_endian = "big") {
+            Self::Big
+        } else {
+            Self::Little
+        }
+    }
+}
+
 impl Parse for Endianess {
     fn parse<R: Read>(reader: &mut R) -> Result<Self, ParseError> {
         let mut buf = [0];
@@ -43,3 +65,17 @@ impl Parse for Endianess {
         })
     }
 }
+
+/// Places an absolute path inside `sysroot`. Paths which are relative, or
+/// already derived from a path within the sysroot, must not be passed in, as
+/// they would be rooted a second time.
+#[cfg(any(feature = "elf", feature = "macho"))]
+pub(crate) fn rooted(
+    sysroot: Option<&std::path::Path>,
+    path: &std::path::Path,
+) -> std::path::PathBuf {
+    match (sysroot, path.strip_prefix("/")) {
+        (Some(sysroot), Ok(relative)) => sysroot.join(relative),
+        _ => path.to_path_buf(),
+    }
+}
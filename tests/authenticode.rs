@@ -0,0 +1,95 @@
+#![cfg(feature = "dll")]
+
+use dolls::dll::DigestAlgorithm;
+use dolls::dll::PeDescriptor;
+use dolls::ParseSeekable;
+use std::fs::File;
+use std::path::Path;
+
+fn open(name: &str) -> (File, PeDescriptor) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/dll")
+        .join(name);
+    let mut file = File::open(path).unwrap();
+    let descriptor = PeDescriptor::parse_seekable(&mut file).unwrap();
+    (file, descriptor)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Computes the digest of the image with the algorithm it was signed with,
+/// returning it alongside the signed digest.
+fn digests(name: &str) -> (String, String) {
+    let (mut file, descriptor) = open(name);
+    let certificates = descriptor.certificates(&mut file).unwrap();
+    let signed_digest = certificates[0].signed_digest().unwrap().unwrap();
+    let digest = descriptor
+        .authenticode_digest(&mut file, &signed_digest.algorithm)
+        .unwrap()
+        .unwrap();
+    (hex(&digest), hex(&signed_digest.digest))
+}
+
+#[test]
+fn sha256_digest() {
+    let (digest, signed_digest) = digests("signed64.dll");
+    assert_eq!(
+        digest,
+        "575f57991241e12f6dee9148ba9a3320d2bad54ec13f60cdc2d77c987aec0950"
+    );
+    assert_eq!(digest, signed_digest);
+
+    let (mut file, descriptor) = open("signed64.dll");
+    assert_eq!(
+        descriptor.check_authenticode_digest(&mut file).unwrap(),
+        Some(true)
+    );
+}
+
+#[test]
+fn sha1_digest() {
+    let (digest, signed_digest) = digests("signed32.dll");
+    assert_eq!(digest, "92891585e8aba1cb8ef753b38a21af6c9090b2fd");
+    assert_eq!(digest, signed_digest);
+
+    let (mut file, descriptor) = open("signed32.dll");
+    assert_eq!(
+        descriptor.check_authenticode_digest(&mut file).unwrap(),
+        Some(true)
+    );
+}
+
+#[test]
+fn tampered_digest() {
+    let (digest, signed_digest) = digests("tampered.dll");
+    assert_eq!(
+        digest,
+        "575f57991241e12f6dee9148ba9a3320d2bad54ec13f60cdc2d77c987aec0950"
+    );
+    assert_ne!(digest, signed_digest);
+
+    let (mut file, descriptor) = open("tampered.dll");
+    assert_eq!(
+        descriptor.check_authenticode_digest(&mut file).unwrap(),
+        Some(false)
+    );
+}
+
+#[test]
+fn unsigned_image() {
+    let (mut file, descriptor) = open("lib64.dll");
+    assert!(descriptor.certificates(&mut file).unwrap().is_empty());
+    assert_eq!(
+        descriptor.check_authenticode_digest(&mut file).unwrap(),
+        None
+    );
+
+    // The digest can still be computed, but not with unknown algorithms
+    let other = DigestAlgorithm::Other(Box::new([0x2a]));
+    assert!(descriptor
+        .authenticode_digest(&mut file, &other)
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,902 @@
+#![cfg(feature = "dll")]
+
+use dolls::dll::Archive;
+use dolls::dll::CodeView;
+use dolls::dll::DebugData;
+use dolls::dll::DebugType;
+use dolls::dll::DefinitionExport;
+use dolls::dll::Dependency;
+use dolls::dll::DependencyResolver;
+use dolls::dll::GuardFunction;
+use dolls::dll::Import;
+use dolls::dll::ImportObject;
+use dolls::dll::Machine;
+use dolls::dll::ModuleDefinition;
+use dolls::dll::Object;
+use dolls::dll::PeDescriptor;
+use dolls::dll::RelocationType;
+use dolls::dll::ResourceId;
+use dolls::dll::IMPORT_OBJECT_CODE;
+use dolls::dll::IMPORT_OBJECT_DATA;
+use dolls::dll::IMPORT_OBJECT_NAME;
+use dolls::dll::IMPORT_OBJECT_NAME_NO_PREFIX;
+use dolls::dll::IMPORT_OBJECT_ORDINAL;
+use dolls::ParseSeekable;
+use std::fs;
+use std::fs::File;
+use std::io::Cursor;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/dll")
+}
+
+fn open(name: &str) -> (File, PeDescriptor) {
+    let mut file = File::open(fixtures().join(name)).unwrap();
+    let descriptor = PeDescriptor::parse_seekable(&mut file).unwrap();
+    (file, descriptor)
+}
+
+/// Lists each export as its ordinal, name and either its address or the
+/// export it forwards to.
+fn exports(name: &str) -> Vec<(u32, Option<String>, String)> {
+    let (mut file, descriptor) = open(name);
+    descriptor
+        .exported_symbols(&mut file)
+        .unwrap()
+        .map(|export| {
+            let export = export.unwrap();
+            let target = match export.forwarder {
+                Some(forwarder) => forwarder,
+                None => format!("{:#x}", export.rva),
+            };
+            (export.ordinal, export.name, target)
+        })
+        .collect()
+}
+
+/// Lists each imported DLL with its imports, named by ordinal (`#12`) or by
+/// name and hint (`HeapAlloc@5`).
+fn imports(name: &str) -> Vec<(String, bool, Vec<String>)> {
+    let (mut file, descriptor) = open(name);
+    let imports = descriptor.imports(&mut file).unwrap();
+    let delay_load_imports = descriptor.delay_load_imports(&mut file).unwrap();
+    imports
+        .into_iter()
+        .chain(delay_load_imports)
+        .map(|dll| {
+            let names = dll
+                .imports
+                .iter()
+                .map(|import| match import {
+                    Import::Ordinal(ordinal) => format!("#{}", ordinal),
+                    Import::Name { hint, name } => format!("{}@{}", name, hint),
+                })
+                .collect();
+            (dll.name, dll.delay_loaded, names)
+        })
+        .collect()
+}
+
+/// Flattens the tree into one line per DLL, with paths relative to the
+/// fixtures and the nesting shown by indentation.
+fn flatten(dependency: &Dependency, depth: usize, lines: &mut Vec<String>) {
+    let root = fixtures();
+    let path = match &dependency.path {
+        Some(path) => path
+            .strip_prefix(&root)
+            .unwrap()
+            .to_string_lossy()
+            .replace('\\', "/"),
+        None => String::from("not found"),
+    };
+    let mut line = format!("{}{} => {}", "  ".repeat(depth), dependency.name, path);
+    if dependency.repeated {
+        line.push_str(" (repeated)");
+    }
+    if dependency.delay_loaded {
+        line.push_str(" (delay)");
+    }
+    lines.push(line);
+    for child in &dependency.dependencies {
+        flatten(child, depth + 1, lines);
+    }
+}
+
+fn dependency_tree(name: &str) -> Vec<String> {
+    let mut resolver = DependencyResolver::new();
+    resolver.sysroot = Some(fixtures().join("sysroot"));
+    let tree = resolver.resolve(&fixtures().join(name)).unwrap();
+    let mut lines = Vec::new();
+    flatten(&tree, 0, &mut lines);
+    lines
+}
+
+fn expected_exports() -> Vec<(u32, Option<String>, String)> {
+    let export = |ordinal, name: Option<&str>, target: &str| {
+        (ordinal, name.map(String::from), String::from(target))
+    };
+    vec![
+        export(1, Some("Alpha"), "0x1000"),
+        export(2, Some("Beta"), "0x1010"),
+        export(2, Some("Gamma"), "0x1010"),
+        export(4, None, "0x1020"),
+        export(5, Some("Forwarded"), "KERNEL32.HeapAlloc"),
+    ]
+}
+
+#[test]
+fn headers() {
+    let (_, descriptor) = open("lib64.dll");
+    assert!(matches!(descriptor.coff_header.machine, Machine::Amd64));
+    let (_, descriptor) = open("lib32.dll");
+    assert!(matches!(descriptor.coff_header.machine, Machine::I386));
+}
+
+#[test]
+fn pe32_plus_exports() {
+    assert_eq!(exports("lib64.dll"), expected_exports());
+
+    let (mut file, descriptor) = open("lib64.dll");
+    let directory = descriptor.export_directory(&mut file).unwrap().unwrap();
+    assert_eq!(directory.name.as_deref(), Some("lib64.dll"));
+    assert_eq!(directory.ordinal_base, 1);
+    assert_eq!(directory.address_count, 5);
+    assert_eq!(directory.name_count, 4);
+}
+
+#[test]
+fn pe32_exports() {
+    assert_eq!(exports("lib32.dll"), expected_exports());
+}
+
+#[test]
+fn pe32_plus_imports() {
+    let strings = |names: &[&str]| names.iter().map(|name| String::from(*name)).collect();
+    assert_eq!(
+        imports("lib64.dll"),
+        [
+            (
+                String::from("KERNEL32.dll"),
+                false,
+                strings(&["HeapAlloc@5", "#12"])
+            ),
+            (String::from("helper64.dll"), false, strings(&["Help@0"])),
+            (String::from("missing64.dll"), true, strings(&["Maybe@0"])),
+        ]
+    );
+}
+
+#[test]
+fn pe32_imports() {
+    let strings = |names: &[&str]| names.iter().map(|name| String::from(*name)).collect();
+    assert_eq!(
+        imports("lib32.dll"),
+        [
+            (
+                String::from("KERNEL32.dll"),
+                false,
+                strings(&["HeapAlloc@5", "#12"])
+            ),
+            (String::from("missing32.dll"), true, strings(&["Maybe@0"])),
+        ]
+    );
+}
+
+#[test]
+fn pe32_plus_dependency_tree() {
+    assert_eq!(
+        dependency_tree("lib64.dll"),
+        [
+            "lib64.dll => lib64.dll",
+            "  KERNEL32.dll => sysroot/Windows/System32/KERNEL32.DLL",
+            "  helper64.dll => helper64.dll",
+            "    kernel32.dll => sysroot/Windows/System32/KERNEL32.DLL (repeated)",
+            "  missing64.dll => not found (delay)",
+        ]
+    );
+}
+
+#[test]
+fn pe32_dependency_tree() {
+    // 32-bit images load their system DLLs from SysWOW64
+    assert_eq!(
+        dependency_tree("lib32.dll"),
+        [
+            "lib32.dll => lib32.dll",
+            "  KERNEL32.dll => sysroot/Windows/SysWOW64/kernel32.dll",
+            "  missing32.dll => not found (delay)",
+        ]
+    );
+}
+
+#[test]
+fn big_object_symbols() {
+    let mut file = File::open(fixtures().join("big.obj")).unwrap();
+    let object = Object::parse_seekable(&mut file).unwrap();
+    assert_eq!(object.defined_symbols(), ["short", "a_long_symbol_name"]);
+
+    let object = match object {
+        Object::Coff(object) => object,
+        Object::Import(_) => panic!("parsed as an import object"),
+    };
+    assert!(object.big_object);
+    assert!(matches!(object.coff_header.machine, Machine::Amd64));
+    assert_eq!(object.section_table.entries()[0].name, ".text");
+}
+
+#[test]
+fn other_anonymous_objects_are_rejected() {
+    // The intermediate code written with /GL starts like an import object
+    let mut file = File::open(fixtures().join("ltcg.obj")).unwrap();
+    assert!(Object::parse_seekable(&mut file).is_err());
+}
+
+#[test]
+fn debug_entries() {
+    let (mut file, descriptor) = open("debug.dll");
+    let entries = descriptor.debug_entries(&mut file).unwrap();
+    let types: Vec<_> = entries.iter().map(|entry| entry.debug_type).collect();
+    assert!(
+        types
+            == [
+                DebugType::CodeView,
+                DebugType::CodeView,
+                DebugType::Pogo,
+                DebugType::Repro,
+                DebugType::VcFeature,
+            ]
+    );
+
+    // The data of the first entry is past the end of the file, which only
+    // loses that entry's data
+    assert!(entries[0].data.is_none());
+    match &entries[1].data {
+        Some(DebugData::CodeView(CodeView::Rsds { guid, age, path })) => {
+            assert_eq!(guid.to_string(), "1B4E28BA-2FA1-11D2-883F-0016D3CCA427");
+            assert_eq!(*age, 3);
+            assert_eq!(path, "C:\\build\\out\\foo.pdb");
+        }
+        _ => panic!("the RSDS record wasn't decoded"),
+    }
+    match &entries[2].data {
+        Some(DebugData::Pogo(pogo_entries)) => {
+            let pogo_entries: Vec<_> = pogo_entries
+                .iter()
+                .map(|entry| (entry.rva, entry.size, entry.name.as_str()))
+                .collect();
+            assert_eq!(
+                pogo_entries,
+                [(0x1000, 0x10, ".text$mn"), (0x2000, 0x20, ".rdata")]
+            );
+        }
+        _ => panic!("the POGO record wasn't decoded"),
+    }
+    match &entries[3].data {
+        Some(DebugData::Repro(hash)) => assert_eq!(**hash, (0..32).collect::<Vec<u8>>()[..]),
+        _ => panic!("the REPRO record wasn't decoded"),
+    }
+    match &entries[4].data {
+        Some(DebugData::VcFeature(feature)) => {
+            let counts = (
+                feature.pre_vc11_count,
+                feature.c_cpp_count,
+                feature.gs_count,
+                feature.sdl_count,
+                feature.guard_n_count,
+            );
+            assert_eq!(counts, (0, 12, 12, 3, 0));
+        }
+        _ => panic!("the VC_FEATURE record wasn't decoded"),
+    }
+}
+
+#[test]
+fn symbol_server_keys() {
+    let (mut file, descriptor) = open("debug.dll");
+    let code_view = descriptor.code_view(&mut file).unwrap().unwrap();
+    assert_eq!(code_view.path(), "C:\\build\\out\\foo.pdb");
+    assert_eq!(code_view.pdb_key(), "1B4E28BA2FA111D2883F0016D3CCA4273");
+    assert_eq!(descriptor.symbol_server_key(), "123456783000");
+}
+
+fn resource_id(id: &ResourceId) -> String {
+    match id {
+        ResourceId::Id(id) => id.to_string(),
+        ResourceId::Name(name) => name.clone(),
+    }
+}
+
+#[test]
+fn resource_tree() {
+    // Named entries come before numbered ones at each level
+    let (mut file, descriptor) = open("resources.dll");
+    let resources = descriptor.resources(&mut file).unwrap();
+    let described: Vec<_> = resources
+        .iter()
+        .map(|resource| {
+            (
+                resource_id(&resource.resource_type),
+                resource_id(&resource.name),
+                resource_id(&resource.language),
+                resource.size,
+            )
+        })
+        .collect();
+    let resource = |resource_type: &str, name: &str, language: &str, size| {
+        (resource_type.into(), name.into(), language.into(), size)
+    };
+    assert_eq!(
+        described,
+        [
+            resource("MYDATA", "CONFIG", "0", 5),
+            resource("16", "1", "1033", 408),
+            resource("24", "2", "1033", 11),
+        ]
+    );
+
+    let data = descriptor.resource_data(&mut file, &resources[0]).unwrap();
+    assert_eq!(data, b"hello");
+    let data = descriptor.resource_data(&mut file, &resources[2]).unwrap();
+    assert_eq!(data, b"<assembly/>");
+}
+
+#[test]
+fn version_info() {
+    let (mut file, descriptor) = open("resources.dll");
+    let version_info = descriptor.version_info(&mut file).unwrap().unwrap();
+    let fixed = version_info.fixed.as_ref().unwrap();
+    assert_eq!(fixed.file_version, [1, 2, 3, 4]);
+    assert_eq!(fixed.product_version, [1, 2, 0, 0]);
+
+    assert_eq!(version_info.strings.len(), 1);
+    assert_eq!(version_info.strings[0].key, "040904b0");
+    let keys: Vec<_> = version_info.strings[0]
+        .values
+        .iter()
+        .map(|(key, _)| key.as_str())
+        .collect();
+    assert_eq!(keys, ["CompanyName", "FileVersion", "ProductName", "Empty"]);
+    assert_eq!(version_info.get("CompanyName"), Some("Example Corp"));
+    assert_eq!(version_info.get("FileVersion"), Some("1.2.3.4"));
+    assert_eq!(version_info.get("ProductName"), Some("Dolls Test"));
+    assert_eq!(version_info.get("Empty"), Some(""));
+    assert_eq!(version_info.get("Missing"), None);
+    assert_eq!(version_info.translations, [(0x409, 1200)]);
+
+    let (mut file, descriptor) = open("lib64.dll");
+    assert!(descriptor.version_info(&mut file).unwrap().is_none());
+}
+
+/// Lists each base relocation as its type and address.
+fn relocations(name: &str) -> Vec<(u32, Vec<String>)> {
+    let (mut file, descriptor) = open(name);
+    let blocks: Vec<_> = descriptor.base_relocations(&mut file).collect();
+    blocks
+        .into_iter()
+        .map(|block| {
+            let block = block.unwrap();
+            let relocations = block
+                .relocations
+                .iter()
+                .map(|relocation| {
+                    let relocation_type = match relocation.relocation_type {
+                        RelocationType::Absolute => String::from("absolute"),
+                        RelocationType::HighLow => String::from("highlow"),
+                        RelocationType::HighAdj(low) => format!("highadj {:#x}", low),
+                        RelocationType::Dir64 => String::from("dir64"),
+                        _ => String::from("other"),
+                    };
+                    format!("{} {:#x}", relocation_type, relocation.rva)
+                })
+                .collect();
+            (block.page_rva, relocations)
+        })
+        .collect()
+}
+
+#[test]
+fn base_relocations() {
+    // The entry following a HIGHADJ entry holds its low bits, rather than
+    // being a relocation of its own
+    let expected = vec![
+        (
+            0x1000,
+            vec![
+                String::from("dir64 0x1010"),
+                String::from("highlow 0x1020"),
+                String::from("highadj 0x1234 0x1030"),
+                String::from("absolute 0x1000"),
+                String::from("absolute 0x1000"),
+            ],
+        ),
+        (
+            0x2000,
+            vec![
+                String::from("highlow 0x2ffc"),
+                String::from("absolute 0x2000"),
+            ],
+        ),
+    ];
+    assert_eq!(relocations("hard64.dll"), expected);
+    assert_eq!(relocations("hard32.dll"), expected);
+    assert!(relocations("lib64.dll").is_empty());
+}
+
+#[test]
+fn tls_callbacks() {
+    for (name, base) in &[("hard64.dll", 0x1_8000_0000), ("hard32.dll", 0x1000_0000)] {
+        let (mut file, descriptor) = open(name);
+        let tls_directory = descriptor.tls_directory(&mut file).unwrap().unwrap();
+        assert_eq!(tls_directory.raw_data_start, base + 0x2000);
+        assert_eq!(tls_directory.raw_data_end, base + 0x2010);
+        assert_eq!(tls_directory.callbacks_address, base + 0x2000);
+        assert_eq!(tls_directory.zero_fill_size, 16);
+        assert_eq!(tls_directory.characteristics, 0x0030_0000);
+
+        let callbacks: Vec<_> = descriptor
+            .tls_callbacks(&mut file)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(callbacks, [base + 0x1000, base + 0x1010]);
+    }
+
+    let (mut file, descriptor) = open("lib64.dll");
+    assert!(descriptor.tls_directory(&mut file).unwrap().is_none());
+    assert_eq!(descriptor.tls_callbacks(&mut file).unwrap().count(), 0);
+}
+
+#[test]
+fn load_config() {
+    for (name, base, size) in &[
+        ("hard64.dll", 0x1_8000_0000, 148),
+        ("hard32.dll", 0x1000_0000, 92),
+    ] {
+        let (mut file, descriptor) = open(name);
+        let load_config = descriptor.load_config(&mut file).unwrap().unwrap();
+        assert_eq!(load_config.size, *size);
+        assert_eq!(load_config.security_cookie, base + 0x3000);
+        assert_eq!(load_config.guard_cf_check_function_pointer, base + 0x1030);
+        assert_eq!(load_config.guard_cf_function_count, 3);
+        assert_eq!(load_config.guard_flags, 0x1000_0500);
+        assert!(load_config.is_cf_instrumented());
+        assert_eq!(load_config.guard_cf_function_table_stride(), 1);
+
+        let guard_functions: Vec<_> = descriptor
+            .guard_functions(&mut file)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let guard_function = |rva, flags| GuardFunction { rva, flags };
+        assert!(
+            guard_functions
+                == [
+                    guard_function(0x1000, 0),
+                    guard_function(0x1010, 1),
+                    guard_function(0x1020, 2),
+                ]
+        );
+    }
+
+    // Only 32-bit images have a table of exception handlers, and they hold
+    // the heap flags before the affinity mask
+    let (mut file, descriptor) = open("hard32.dll");
+    let load_config = descriptor.load_config(&mut file).unwrap().unwrap();
+    assert_eq!(load_config.se_handler_table, 0x1000_0040);
+    assert_eq!(load_config.se_handler_count, 2);
+    assert_eq!(load_config.process_heap_flags, 7);
+    assert_eq!(load_config.process_affinity_mask, 9);
+
+    let (mut file, descriptor) = open("lib64.dll");
+    assert!(descriptor.load_config(&mut file).unwrap().is_none());
+}
+
+#[test]
+fn rich_header() {
+    let (mut file, descriptor) = open("rich.dll");
+    let rich_header = descriptor.rich_header(&mut file).unwrap().unwrap();
+    assert_eq!(rich_header.offset, 0x80);
+    assert_eq!(rich_header.key, 0xa8fc_7d52);
+    assert!(rich_header.is_valid());
+    let entries: Vec<_> = rich_header
+        .entries
+        .iter()
+        .map(|entry| (entry.product_id, entry.build, entry.count))
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            (0x0001, 0, 12),
+            (0x0104, 30148, 8),
+            (0x0105, 30148, 3),
+            (0x0103, 30148, 1),
+            (0x0102, 30148, 1),
+        ]
+    );
+
+    // Changing the DOS stub after linking breaks the checksum
+    let mut data = fs::read(fixtures().join("rich.dll")).unwrap();
+    data[0x4e] ^= 0x20;
+    let mut cursor = Cursor::new(data);
+    let descriptor = PeDescriptor::parse_seekable(&mut cursor).unwrap();
+    let rich_header = descriptor.rich_header(&mut cursor).unwrap().unwrap();
+    assert!(!rich_header.is_valid());
+    assert_eq!(rich_header.entries.len(), 5);
+
+    let (mut file, descriptor) = open("lib64.dll");
+    assert!(descriptor.rich_header(&mut file).unwrap().is_none());
+}
+
+#[test]
+fn image_checksum() {
+    let (mut file, descriptor) = open("checksum.dll");
+    let checksum = descriptor.image_checksum(&mut file).unwrap();
+    assert_eq!(checksum.stored, 0xfb8e);
+    assert_eq!(checksum.computed, 0xfb8e);
+    assert!(checksum.matches());
+
+    // The checksum catches a change to any byte, including the last one of
+    // a file with an odd length
+    let data = fs::read(fixtures().join("checksum.dll")).unwrap();
+    for offset in &[0x200, data.len() - 1] {
+        let mut data = data.clone();
+        data[*offset] ^= 0x01;
+        let mut cursor = Cursor::new(data);
+        let descriptor = PeDescriptor::parse_seekable(&mut cursor).unwrap();
+        let checksum = descriptor.image_checksum(&mut cursor).unwrap();
+        assert_eq!(checksum.stored, 0xfb8e);
+        assert!(!checksum.matches());
+    }
+
+    // Most images leave the checksum as zero
+    let (mut file, descriptor) = open("lib64.dll");
+    let checksum = descriptor.image_checksum(&mut file).unwrap();
+    assert_eq!(checksum.stored, 0);
+    assert_eq!(checksum.computed, 0x726b);
+    assert!(!checksum.matches());
+}
+
+#[test]
+fn module_definition() {
+    // The second name of an export leaves out the ordinal, which was already
+    // given, while an export without a name is made one up
+    let (mut file, descriptor) = open("lib64.dll");
+    let definition = descriptor.module_definition(&mut file).unwrap().unwrap();
+    assert_eq!(
+        definition.to_string(),
+        "LIBRARY lib64.dll\n\
+         EXPORTS\n    \
+         Alpha @1\n    \
+         Beta @2\n    \
+         Gamma\n    \
+         ordinal4 @4 NONAME\n    \
+         Forwarded = KERNEL32.HeapAlloc @5\n"
+    );
+
+    let definition = ModuleDefinition {
+        name: String::from("my lib.dll"),
+        machine: Machine::Amd64,
+        exports: vec![DefinitionExport {
+            name: String::from("value"),
+            ordinal: Some(3),
+            no_name: false,
+            data: true,
+            forwarder: None,
+        }],
+    };
+    assert_eq!(
+        definition.to_string(),
+        "LIBRARY \"my lib.dll\"\nEXPORTS\n    value @3 DATA\n"
+    );
+}
+
+/// Writes an import library and reads it back.
+fn import_library(definition: &ModuleDefinition) -> (Archive, Cursor<Vec<u8>>) {
+    let mut data = Vec::new();
+    definition.write_import_library(&mut data).unwrap();
+    let mut reader = Cursor::new(data);
+    let archive = Archive::parse_seekable(&mut reader).unwrap();
+    (archive, reader)
+}
+
+#[test]
+fn written_import_library() {
+    for (name, prefix, name_type) in &[
+        ("lib64.dll", "", IMPORT_OBJECT_NAME),
+        ("lib32.dll", "_", IMPORT_OBJECT_NAME_NO_PREFIX),
+    ] {
+        let (mut file, descriptor) = open(name);
+        let definition = descriptor.module_definition(&mut file).unwrap().unwrap();
+        let (archive, mut reader) = import_library(&definition);
+
+        // The import descriptor, its null terminator and the null thunk come
+        // before an object for each export
+        assert_eq!(archive.members.len(), 8);
+        assert!(archive.members.iter().all(|member| member.name == *name));
+        let library = name.trim_end_matches(".dll");
+        let descriptor_symbol = format!("__IMPORT_DESCRIPTOR_{}", library);
+        assert!(archive.find_symbol(&descriptor_symbol).is_some());
+        assert!(archive.find_symbol("__NULL_IMPORT_DESCRIPTOR").is_some());
+
+        for (export, ordinal, expected_name_type) in &[
+            ("Alpha", 1, *name_type),
+            ("Gamma", 0, *name_type),
+            ("ordinal4", 4, IMPORT_OBJECT_ORDINAL),
+            ("Forwarded", 5, *name_type),
+        ] {
+            let symbol = format!("{}{}", prefix, export);
+            let member = archive.find_symbol(&format!("__imp_{}", symbol)).unwrap();
+            let import = match archive.member_object(&mut reader, member).unwrap() {
+                Object::Import(import) => import,
+                Object::Coff(_) => panic!("{} isn't a short import object", symbol),
+            };
+            assert_eq!(import.symbol, symbol);
+            assert_eq!(import.dll, *name);
+            assert_eq!(import.ordinal_hint, *ordinal);
+            assert_eq!(import.import_type, IMPORT_OBJECT_CODE);
+            assert_eq!(import.name_type, *expected_name_type);
+            let imported_name = match import.name_type {
+                IMPORT_OBJECT_ORDINAL => None,
+                _ => Some(export.to_string()),
+            };
+            assert_eq!(import.imported_name(), imported_name);
+            assert!(std::ptr::eq(archive.find_symbol(&symbol).unwrap(), member));
+        }
+    }
+}
+
+#[test]
+fn written_data_import() {
+    // Variables can only be imported through the import address table, so
+    // have no thunk
+    let definition = ModuleDefinition {
+        name: String::from("data.dll"),
+        machine: Machine::Amd64,
+        exports: vec![DefinitionExport {
+            name: String::from("value"),
+            ordinal: Some(1),
+            no_name: false,
+            data: true,
+            forwarder: None,
+        }],
+    };
+    let (archive, mut reader) = import_library(&definition);
+    assert!(archive.find_symbol("value").is_none());
+    let member = archive.find_symbol("__imp_value").unwrap();
+    match archive.member_object(&mut reader, member).unwrap() {
+        Object::Import(import) => assert_eq!(import.import_type, IMPORT_OBJECT_DATA),
+        Object::Coff(_) => panic!("the variable isn't a short import object"),
+    }
+}
+
+#[test]
+fn too_many_members_for_an_import_library() {
+    // The second linker member indexes members with 16 bits, and three
+    // members are taken by the import descriptor
+    let export = |index| DefinitionExport {
+        name: format!("f{}", index),
+        ordinal: None,
+        no_name: false,
+        data: false,
+        forwarder: None,
+    };
+    let mut definition = ModuleDefinition {
+        name: String::from("many.dll"),
+        machine: Machine::Amd64,
+        exports: (0..usize::from(u16::MAX) - 3).map(export).collect(),
+    };
+    assert!(definition.write_import_library(&mut Vec::new()).is_ok());
+
+    definition.exports.push(export(usize::from(u16::MAX)));
+    let error = definition
+        .write_import_library(&mut Vec::new())
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+}
+
+/// Reads the short import object which defines a symbol of an archive.
+fn archive_import(archive: &Archive, file: &mut File, symbol: &str) -> ImportObject {
+    let member = archive.find_symbol(symbol).unwrap();
+    match archive.member_object(file, member).unwrap() {
+        Object::Import(import) => import,
+        Object::Coff(_) => panic!("{} isn't defined by a short import object", symbol),
+    }
+}
+
+#[test]
+fn archive_linker_members() {
+    // The second linker member lists the offset of every member, and the
+    // symbols in sorted order
+    let mut file = File::open(fixtures().join("imports.lib")).unwrap();
+    let archive = Archive::parse_seekable(&mut file).unwrap();
+    assert_eq!(archive.members.len(), 9);
+    assert!(archive
+        .members
+        .iter()
+        .all(|member| member.name == "exp64.dll"));
+    let header_offsets: Vec<_> = archive
+        .members
+        .iter()
+        .map(|member| member.header_offset as u32)
+        .collect();
+    assert_eq!(archive.member_offsets, Some(header_offsets));
+
+    let symbols: Vec<_> = archive
+        .symbols
+        .iter()
+        .map(|symbol| symbol.name.as_str())
+        .collect();
+    assert_eq!(
+        symbols,
+        [
+            "?cpp@@YAXXZ",
+            "__IMPORT_DESCRIPTOR_exp64",
+            "__NULL_IMPORT_DESCRIPTOR",
+            "__imp_?cpp@@YAXXZ",
+            "__imp_alpha",
+            "__imp_alpha_alias",
+            "__imp_beta_variable",
+            "__imp_fwd",
+            "__imp_ordinal7",
+            "alpha",
+            "alpha_alias",
+            "fwd",
+            "ordinal7",
+            "\x7fexp64_NULL_THUNK_DATA",
+        ]
+    );
+    assert!(archive.symbols.iter().all(|symbol| symbol.member.is_some()));
+
+    // The import descriptor is an ordinary object
+    let member = archive.find_symbol("__IMPORT_DESCRIPTOR_exp64").unwrap();
+    assert!(std::ptr::eq(member, &archive.members[0]));
+    match archive.member_object(&mut file, member).unwrap() {
+        Object::Coff(object) => assert!(matches!(object.coff_header.machine, Machine::Amd64)),
+        Object::Import(_) => panic!("the import descriptor is a short import object"),
+    }
+}
+
+#[test]
+fn archive_short_import_objects() {
+    let mut file = File::open(fixtures().join("imports.lib")).unwrap();
+    let archive = Archive::parse_seekable(&mut file).unwrap();
+
+    let import = archive_import(&archive, &mut file, "__imp_alpha");
+    assert!(matches!(import.machine, Machine::Amd64));
+    assert_eq!(import.symbol, "alpha");
+    assert_eq!(import.dll, "exp64.dll");
+    assert_eq!(import.ordinal_hint, 3);
+    assert_eq!(import.import_type, IMPORT_OBJECT_CODE);
+    assert_eq!(import.imported_name().as_deref(), Some("alpha"));
+    assert_eq!(import.defined_symbols(), ["__imp_alpha", "alpha"]);
+
+    // The thunk and the import address table entry are in the same member
+    let thunk = archive.find_symbol("alpha").unwrap();
+    assert!(std::ptr::eq(
+        thunk,
+        archive.find_symbol("__imp_alpha").unwrap()
+    ));
+
+    // Variables have no thunk
+    let import = archive_import(&archive, &mut file, "__imp_beta_variable");
+    assert_eq!(import.import_type, IMPORT_OBJECT_DATA);
+    assert_eq!(import.defined_symbols(), ["__imp_beta_variable"]);
+    assert!(archive.find_symbol("beta_variable").is_none());
+
+    let import = archive_import(&archive, &mut file, "__imp_ordinal7");
+    assert_eq!(import.name_type, IMPORT_OBJECT_ORDINAL);
+    assert_eq!(import.ordinal_hint, 7);
+    assert_eq!(import.imported_name(), None);
+
+    let import = archive_import(&archive, &mut file, "__imp_?cpp@@YAXXZ");
+    assert_eq!(import.imported_name().as_deref(), Some("?cpp@@YAXXZ"));
+    assert!(archive.find_symbol("__imp_missing").is_none());
+}
+
+#[test]
+fn archive_without_second_linker_member() {
+    // GNU archives only have the first linker member, and name members which
+    // don't fit in the header through the long names member
+    let mut file = File::open(fixtures().join("objects.a")).unwrap();
+    let archive = Archive::parse_seekable(&mut file).unwrap();
+    assert!(archive.member_offsets.is_none());
+    let names: Vec<_> = archive
+        .members
+        .iter()
+        .map(|member| member.name.as_str())
+        .collect();
+    assert_eq!(names, ["small.obj", "ltcg.obj", "a_long_object_name.obj"]);
+
+    let member = archive.find_symbol("small_data").unwrap();
+    assert_eq!(member.name, "small.obj");
+    let member = archive.find_symbol("a_long_symbol_name").unwrap();
+    assert_eq!(member.name, "a_long_object_name.obj");
+
+    // The intermediate code of a `/GL` object can't be read, which doesn't
+    // stop the other members from being read
+    let symbols: Vec<_> = archive
+        .members
+        .iter()
+        .map(|member| {
+            archive
+                .member_object(&mut file, member)
+                .ok()
+                .map(|object| object.defined_symbols())
+        })
+        .collect();
+    assert_eq!(
+        symbols,
+        [
+            Some(vec![String::from("small"), String::from("small_data")]),
+            None,
+            Some(vec![
+                String::from("short"),
+                String::from("a_long_symbol_name")
+            ]),
+        ]
+    );
+}
+
+#[test]
+fn managed_assembly() {
+    let (mut file, descriptor) = open("managed.dll");
+    let clr_header = descriptor.clr_header(&mut file).unwrap().unwrap();
+    assert_eq!(clr_header.major_runtime_version, 2);
+    assert_eq!(clr_header.minor_runtime_version, 5);
+    assert!(clr_header.is_il_only());
+
+    let metadata = descriptor.clr_metadata(&mut file).unwrap().unwrap();
+    assert_eq!(metadata.version, "v4.0.30319");
+    assert_eq!(metadata.module_name, "Lib.dll");
+    assert_eq!(
+        metadata.module_version_id.unwrap().to_string(),
+        "12345678-9ABC-DEF0-1122-334455667788"
+    );
+
+    // The assembly holds its full public key, while references hold the
+    // token of it
+    let assembly = metadata.assembly.as_ref().unwrap();
+    assert_eq!(assembly.name, "Acme.Lib");
+    assert_eq!(assembly.version, [1, 2, 3, 4]);
+    assert_eq!(assembly.public_key.len(), 160);
+    assert_eq!(
+        assembly.to_string(),
+        "Acme.Lib, Version=1.2.3.4, Culture=neutral, PublicKeyToken=473c444ebb4661a5"
+    );
+    let references: Vec<_> = metadata
+        .assembly_references
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        references,
+        [
+            "System.Runtime, Version=4.2.2.0, Culture=neutral, PublicKeyToken=b03f5f7f11d50a3a",
+            "Satellite, Version=1.0.0.0, Culture=fr-FR, PublicKeyToken=null",
+        ]
+    );
+
+    // Nested types aren't visible at the top level, even when public
+    let type_definitions: Vec<_> = metadata
+        .type_definitions
+        .iter()
+        .map(|type_definition| (type_definition.full_name(), type_definition.is_public()))
+        .collect();
+    assert_eq!(
+        type_definitions,
+        [
+            (String::from("<Module>"), false),
+            (String::from("Acme.Tools.Widget"), true),
+            (String::from("Acme.Hidden"), false),
+            (String::from("Nested"), false),
+            (String::from("Acme.IThing"), true),
+        ]
+    );
+
+    let (mut file, descriptor) = open("lib64.dll");
+    assert!(descriptor.clr_header(&mut file).unwrap().is_none());
+    assert!(descriptor.clr_metadata(&mut file).unwrap().is_none());
+}
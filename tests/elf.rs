@@ -0,0 +1,367 @@
+#![cfg(feature = "elf")]
+
+use dolls::elf::gnu_hash;
+use dolls::elf::sysv_hash;
+use dolls::elf::Binding;
+use dolls::elf::Dependency;
+use dolls::elf::DependencyResolver;
+use dolls::elf::DynamicEntry;
+use dolls::elf::ElfDescriptor;
+use dolls::elf::Symbol;
+use dolls::elf::SymbolType;
+use dolls::elf::Visibility;
+use dolls::ParseSeekable;
+use std::fs::File;
+use std::path::Path;
+use std::path::PathBuf;
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/elf")
+}
+
+fn open(name: &str) -> (File, ElfDescriptor) {
+    let mut file = File::open(fixtures().join(name)).unwrap();
+    let descriptor = ElfDescriptor::parse_seekable(&mut file).unwrap();
+    (file, descriptor)
+}
+
+fn names<'a>(symbols: impl Iterator<Item = &'a Symbol>) -> Vec<&'a str> {
+    symbols
+        .map(|symbol| symbol.name.as_deref().unwrap_or_default())
+        .collect()
+}
+
+#[test]
+fn shared_object_tables() {
+    let (_, descriptor) = open("tree/lib/libfoo.so");
+    assert_eq!(descriptor.program_header.entries().len(), 4);
+    assert_eq!(descriptor.section_header.entries().len(), 21);
+}
+
+#[test]
+fn relocatable_object_tables() {
+    // Relocatable objects have no program header, and leave its entry size
+    // zero
+    let (_, descriptor) = open("foo.o");
+    assert_eq!(descriptor.file_header.program_header_entry_size, 0);
+    assert!(descriptor.program_header.entries().is_empty());
+    assert_eq!(descriptor.section_header.entries().len(), 10);
+}
+
+#[test]
+fn section_names() {
+    let (_, descriptor) = open("foo.o");
+    let names: Vec<_> = descriptor
+        .section_header
+        .entries()
+        .iter()
+        .map(|entry| entry.name.as_deref().unwrap_or_default())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "",
+            ".text",
+            ".rela.text",
+            ".data",
+            ".bss",
+            ".comment",
+            ".note.GNU-stack",
+            ".symtab",
+            ".strtab",
+            ".shstrtab",
+        ]
+    );
+
+    let (_, descriptor) = open("tree/lib/libfoo.so");
+    let dynamic = descriptor.section_header.by_name(".dynamic").unwrap();
+    assert_eq!(u64::from(dynamic.addr), 0x14c8);
+    assert!(descriptor.section_header.by_name(".missing").is_none());
+}
+
+#[test]
+fn symbol_table() {
+    let (mut file, descriptor) = open("foo.o");
+    let symbols: Vec<_> = descriptor
+        .symbols(&mut file)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        names(symbols.iter()),
+        [
+            "",
+            "foo.c",
+            "foo_internal",
+            "foo",
+            "bar_old",
+            "bar_new",
+            "sys_call",
+            "missing_call",
+            "foo_extra",
+            "foo_value",
+        ]
+    );
+
+    let foo = &symbols[3];
+    assert!(matches!(foo.binding, Binding::Global));
+    assert!(matches!(foo.symbol_type, SymbolType::Function));
+    assert!(matches!(foo.visibility, Visibility::Default));
+    assert_eq!(u64::from(foo.size), 35);
+    assert!(foo.is_defined());
+    assert!(matches!(symbols[1].symbol_type, SymbolType::File));
+    assert!(matches!(symbols[2].visibility, Visibility::Hidden));
+    assert!(matches!(symbols[9].symbol_type, SymbolType::Object));
+    assert!(!symbols[4].is_defined());
+}
+
+#[test]
+fn dynamic_symbol_table() {
+    let (mut file, descriptor) = open("tree/lib/libfoo.so");
+    let symbols: Vec<_> = descriptor
+        .dynamic_symbols(&mut file)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(symbols.len(), 10);
+    assert_eq!(symbols[7].name.as_deref(), Some("foo"));
+    assert_eq!(u64::from(symbols[7].value), 0x496);
+
+    // Stripped files have no full symbol table
+    let (mut file, descriptor) = open("libfoo-no-sections.so");
+    assert_eq!(descriptor.symbols(&mut file).unwrap().count(), 0);
+}
+
+#[test]
+fn exported_symbols() {
+    // The version symbols have no value, so aren't exported
+    let (mut file, descriptor) = open("tree/lib/libfoo.so");
+    let symbols: Vec<_> = descriptor
+        .exported_symbols(&mut file)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(names(symbols.iter()), ["foo_extra", "foo", "foo_value"]);
+}
+
+#[test]
+fn imported_symbols() {
+    let (mut file, descriptor) = open("tree/lib/libfoo.so");
+    let symbols: Vec<_> = descriptor
+        .imported_symbols(&mut file)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        names(symbols.iter()),
+        ["bar_old", "sys_call", "missing_call", "bar_new"]
+    );
+}
+
+#[test]
+fn hash_functions() {
+    assert_eq!(gnu_hash(""), 5381);
+    assert_eq!(gnu_hash("printf"), 0x156b_2bb8);
+    assert_eq!(sysv_hash(""), 0);
+    assert_eq!(sysv_hash("printf"), 0x0779_05a6);
+}
+
+#[test]
+fn lookup_symbol() {
+    // The first file has both hash tables, the second only the SysV one, and
+    // the third is found through the program header alone
+    for name in &[
+        "tree/lib/libfoo.so",
+        "libfoo-sysv.so",
+        "libfoo-no-sections.so",
+    ] {
+        let (mut file, descriptor) = open(name);
+        let mut lookup = |symbol| descriptor.lookup_symbol(&mut file, symbol).unwrap();
+        for symbol in &["foo", "foo_extra", "foo_value"] {
+            let found = lookup(symbol).unwrap();
+            assert_eq!(found.name.as_deref(), Some(*symbol), "{}", name);
+        }
+
+        // Neither undefined, local nor version symbols are exported
+        for symbol in &["bar_old", "missing_call", "foo_internal", "FOO_1.0", "baz"] {
+            assert!(lookup(symbol).is_none(), "{} in {}", symbol, name);
+        }
+    }
+}
+
+#[test]
+fn version_definitions() {
+    let (mut file, descriptor) = open("tree/lib/libfoo.so");
+    let definitions = descriptor.version_definitions(&mut file).unwrap();
+    let described: Vec<_> = definitions
+        .iter()
+        .map(|definition| {
+            let name = definition.name.as_deref().unwrap_or_default();
+            let parents: Vec<_> = definition
+                .parents
+                .iter()
+                .map(|parent| parent.as_deref().unwrap_or_default())
+                .collect();
+            (name, definition.index, definition.is_base(), parents)
+        })
+        .collect();
+    assert_eq!(
+        described,
+        [
+            ("libfoo.so", 1, true, vec![]),
+            ("FOO_1.0", 2, false, vec![]),
+            ("FOO_1.1", 3, false, vec!["FOO_1.0"]),
+        ]
+    );
+}
+
+#[test]
+fn version_requirements() {
+    let (mut file, descriptor) = open("tree/lib/libfoo.so");
+    let requirements = descriptor.version_requirements(&mut file).unwrap();
+    assert_eq!(requirements.len(), 1);
+    assert_eq!(requirements[0].file.as_deref(), Some("libbar.so.1"));
+    let versions: Vec<_> = requirements[0]
+        .versions
+        .iter()
+        .map(|version| (version.name.as_deref().unwrap_or_default(), version.index))
+        .collect();
+    assert_eq!(versions, [("BAR_2.0", 5), ("BAR_1.0", 4)]);
+
+    assert_eq!(
+        descriptor
+            .highest_required_version(&mut file, "BAR_")
+            .unwrap()
+            .as_deref(),
+        Some("BAR_2.0")
+    );
+    assert!(descriptor
+        .highest_required_version(&mut file, "GLIBC_")
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn symbol_versions() {
+    let (mut file, descriptor) = open("tree/lib/libfoo.so");
+    let versions: Vec<_> = descriptor
+        .dynamic_symbols(&mut file)
+        .unwrap()
+        .map(|symbol| {
+            let symbol = symbol.unwrap();
+            let version = symbol.version.map(|version| {
+                let name = version.name.unwrap_or_default();
+                let library = version.library.unwrap_or_default();
+                (name, library, version.hidden)
+            });
+            (symbol.name.unwrap_or_default(), version)
+        })
+        .filter(|(name, _)| name.starts_with("foo") || name.starts_with("bar"))
+        .collect();
+    let version = |name: &str, library: &str| Some((name.into(), library.into(), false));
+    assert_eq!(
+        versions,
+        [
+            ("bar_old".into(), version("BAR_1.0", "libbar.so.1")),
+            ("bar_new".into(), version("BAR_2.0", "libbar.so.1")),
+            ("foo_extra".into(), version("FOO_1.1", "")),
+            ("foo".into(), version("FOO_1.0", "")),
+            ("foo_value".into(), version("FOO_1.0", "")),
+        ]
+    );
+}
+
+/// Describes the entries of the dynamic section which name other objects or
+/// locate the tables used by the dynamic linker.
+fn dynamic_entries(name: &str) -> Vec<String> {
+    let (mut file, descriptor) = open(name);
+    descriptor
+        .dynamic_entries(&mut file)
+        .unwrap()
+        .into_iter()
+        .filter_map(|entry| match entry {
+            DynamicEntry::Needed(name) => Some(format!("needed {}", name?)),
+            DynamicEntry::SharedObjectName(name) => Some(format!("soname {}", name?)),
+            DynamicEntry::RunPath(paths) => Some(format!("runpath {}", paths?)),
+            DynamicEntry::Hash(address) => Some(format!("hash {:#x}", address)),
+            DynamicEntry::GnuHash(address) => Some(format!("gnu hash {:#x}", address)),
+            DynamicEntry::VersionDefinitionCount(count) => Some(format!("verdefnum {}", count)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn dynamic_section() {
+    let expected = [
+        "needed libbar.so.1",
+        "needed libsys.so.1",
+        "needed libmissing.so",
+        "soname libfoo.so",
+        "runpath $ORIGIN",
+        "hash 0x120",
+        "gnu hash 0x160",
+        "verdefnum 3",
+    ];
+    assert_eq!(dynamic_entries("tree/lib/libfoo.so"), expected);
+
+    // Without a section table, the entries are found through the program
+    // header
+    let (_, descriptor) = open("libfoo-no-sections.so");
+    assert!(descriptor.section_header.entries().is_empty());
+    assert_eq!(dynamic_entries("libfoo-no-sections.so"), expected);
+}
+
+/// Flattens the tree into one line per object, with paths relative to the
+/// fixtures and the nesting shown by indentation.
+fn flatten(dependency: &Dependency, depth: usize, lines: &mut Vec<String>) {
+    let path = match &dependency.path {
+        Some(path) => path
+            .strip_prefix(fixtures())
+            .unwrap()
+            .to_string_lossy()
+            .into_owned(),
+        None => String::from("not found"),
+    };
+    let mut line = format!("{}{} => {}", "  ".repeat(depth), dependency.name, path);
+    if dependency.repeated {
+        line.push_str(" (repeated)");
+    }
+    lines.push(line);
+    for child in &dependency.dependencies {
+        flatten(child, depth + 1, lines);
+    }
+}
+
+fn dependency_tree(path: &Path) -> Vec<String> {
+    let mut resolver = DependencyResolver::new();
+    resolver.sysroot = Some(fixtures().join("sysroot"));
+    let tree = resolver.resolve(path).unwrap();
+    let mut lines = Vec::new();
+    flatten(&tree, 0, &mut lines);
+    lines
+}
+
+/// The tree of `tree/app`, whose run path finds `libfoo.so` through
+/// `$ORIGIN/lib`. That finds `libbar.so.1` through its own `$ORIGIN`, while
+/// `libsys.so.1` is only in the default directories of the sysroot.
+const APP_TREE: [&str; 6] = [
+    "app => tree/app",
+    "  libfoo.so => tree/lib/libfoo.so",
+    "    libbar.so.1 => tree/lib/libbar.so.1",
+    "      libsys.so.1 => sysroot/usr/lib64/libsys.so.1 (repeated)",
+    "    libsys.so.1 => sysroot/usr/lib64/libsys.so.1",
+    "    libmissing.so => not found",
+];
+
+#[test]
+fn origin_run_path_dependency_tree() {
+    assert_eq!(dependency_tree(&fixtures().join("tree/app")), APP_TREE);
+}
+
+#[test]
+fn bare_file_name_dependency_tree() {
+    // No other test depends on the working directory
+    std::env::set_current_dir(fixtures().join("tree")).unwrap();
+    assert_eq!(dependency_tree(Path::new("app")), APP_TREE);
+}
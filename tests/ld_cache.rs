@@ -0,0 +1,111 @@
+#![cfg(feature = "elf")]
+
+use dolls::elf::CacheArchitecture;
+use dolls::elf::LdCache;
+use dolls::elf::SystemLibraries;
+use dolls::Parse;
+use std::fs::File;
+use std::path::Path;
+
+fn fixture(name: &str) -> LdCache {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/ld_cache")
+        .join(name);
+    let mut file = File::open(path).unwrap();
+    LdCache::parse(&mut file).unwrap()
+}
+
+fn find(cache: &LdCache, name: &str, architecture: CacheArchitecture) -> Option<String> {
+    cache
+        .find(name, architecture)
+        .map(|entry| entry.path.to_string_lossy().into_owned())
+}
+
+fn check_entries(cache: &LdCache) {
+    let names: Vec<_> = cache
+        .entries()
+        .iter()
+        .map(|entry| (entry.flags, entry.name.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            (0x0303, "libc.so.6"),
+            (0x0303, "libm.so.6"),
+            (0x0003, "libc.so.6")
+        ]
+    );
+
+    assert_eq!(
+        find(cache, "libc.so.6", CacheArchitecture::X86_64).as_deref(),
+        Some("/lib/x86_64-linux-gnu/libc.so.6")
+    );
+    assert_eq!(
+        find(cache, "libm.so.6", CacheArchitecture::X86_64).as_deref(),
+        Some("/lib/x86_64-linux-gnu/libm.so.6")
+    );
+    assert_eq!(
+        find(cache, "libc.so.6", CacheArchitecture::Unmarked).as_deref(),
+        Some("/lib/i386-linux-gnu/libc.so.6")
+    );
+    assert_eq!(find(cache, "libm.so.6", CacheArchitecture::Unmarked), None);
+    assert_eq!(find(cache, "libz.so.1", CacheArchitecture::X86_64), None);
+}
+
+#[test]
+fn old_format() {
+    let cache = fixture("old.cache");
+    check_entries(&cache);
+
+    // The old format has no room for these
+    let entry = &cache.entries()[2];
+    assert_eq!(entry.os_version, 0);
+    assert_eq!(entry.hardware_capabilities, 0);
+}
+
+#[test]
+fn new_format() {
+    let cache = fixture("new.cache");
+    check_entries(&cache);
+
+    let entry = &cache.entries()[2];
+    assert_eq!(entry.os_version, 0x30200);
+    assert_eq!(entry.hardware_capabilities, 0x2000);
+}
+
+#[test]
+fn new_format_big_endian() {
+    let cache = fixture("new-big-endian.cache");
+    check_entries(&cache);
+
+    let entry = &cache.entries()[2];
+    assert_eq!(entry.os_version, 0x30200);
+    assert_eq!(entry.hardware_capabilities, 0x2000);
+}
+
+#[test]
+fn combined_format_prefers_new_format() {
+    let cache = fixture("combined.cache");
+    check_entries(&cache);
+
+    let entry = &cache.entries()[2];
+    assert_eq!(entry.os_version, 0x30200);
+    assert_eq!(entry.hardware_capabilities, 0x2000);
+}
+
+#[test]
+fn unknown_magic() {
+    let mut data: &[u8] = b"not a library cache";
+    assert!(LdCache::parse(&mut data).is_err());
+}
+
+#[test]
+fn corrupt_cache_is_ignored() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ld_cache/corrupt");
+    let libraries = SystemLibraries::load(&root).unwrap();
+    assert!(libraries.cache().is_none());
+    assert_eq!(
+        libraries.locate("libc.so.6", CacheArchitecture::X86_64),
+        None
+    );
+}
@@ -0,0 +1,294 @@
+#![cfg(feature = "macho")]
+
+use dolls::macho::ArchitectureWidth;
+use dolls::macho::CpuType;
+use dolls::macho::Dependency;
+use dolls::macho::DependencyResolver;
+use dolls::macho::DylibKind;
+use dolls::macho::ExportKind;
+use dolls::macho::FatBinary;
+use dolls::macho::FileType;
+use dolls::macho::LoadCommand;
+use dolls::macho::MachODescriptor;
+use dolls::Parse;
+use dolls::ParseSeekable;
+use std::fs::File;
+use std::path::Path;
+use std::path::PathBuf;
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/macho")
+}
+
+fn open(name: &str) -> (File, MachODescriptor) {
+    let mut file = File::open(fixtures().join(name)).unwrap();
+    let descriptor = MachODescriptor::parse_seekable(&mut file).unwrap();
+    (file, descriptor)
+}
+
+/// Lists each export as its name and a description of what it is.
+fn exports(name: &str) -> Vec<(String, String)> {
+    let (mut file, descriptor) = open(name);
+    let mut exports: Vec<_> = descriptor
+        .exports(&mut file)
+        .unwrap()
+        .into_iter()
+        .map(|export| {
+            let mut kind = match &export.kind {
+                ExportKind::Regular { address } => format!("{:#x}", address),
+                ExportKind::Reexport {
+                    library_ordinal,
+                    imported_name,
+                } => format!(
+                    "{} from {}",
+                    imported_name.as_deref().unwrap_or(&export.name),
+                    library_ordinal
+                ),
+                ExportKind::StubAndResolver { stub, resolver } => {
+                    format!("{:#x} resolved by {:#x}", stub, resolver)
+                }
+            };
+            if export.is_weak() {
+                kind.push_str(" (weak)");
+            }
+            if export.is_thread_local() {
+                kind.push_str(" (thread local)");
+            }
+            if export.is_absolute() {
+                kind.push_str(" (absolute)");
+            }
+            (export.name, kind)
+        })
+        .collect();
+    exports.sort();
+    exports
+}
+
+fn expected_exports() -> Vec<(String, String)> {
+    let export = |name: &str, kind: &str| (String::from(name), String::from(kind));
+    vec![
+        export("_abs", "0x1234 (absolute)"),
+        export("_bar", "_baz from 1"),
+        export("_foo", "0x1000"),
+        export("_foobar", "0x1010"),
+        export("_food", "0x1020 (weak)"),
+        export("_ifunc", "0x1040 resolved by 0x1050"),
+        export("_qux", "_qux from 2"),
+        export("_tls", "0x1030 (thread local)"),
+    ]
+}
+
+/// Flattens the tree into one line per library, with paths relative to the
+/// fixtures and the nesting shown by indentation.
+fn flatten(dependency: &Dependency, depth: usize, lines: &mut Vec<String>) {
+    let path = match &dependency.path {
+        Some(path) => path
+            .strip_prefix(fixtures())
+            .unwrap()
+            .to_string_lossy()
+            .into_owned(),
+        None => String::from("not found"),
+    };
+    let mut line = format!("{}{} => {}", "  ".repeat(depth), dependency.name, path);
+    if dependency.repeated {
+        line.push_str(" (repeated)");
+    }
+    if dependency.weak {
+        line.push_str(" (weak)");
+    }
+    lines.push(line);
+    for child in &dependency.dependencies {
+        flatten(child, depth + 1, lines);
+    }
+}
+
+fn dependency_tree(name: &str, architecture: Option<&str>) -> Vec<String> {
+    let mut resolver = DependencyResolver::new();
+    resolver.sysroot = Some(fixtures().join("sysroot"));
+    resolver.architecture = architecture.map(String::from);
+    let tree = resolver.resolve(&fixtures().join(name)).unwrap();
+    let mut lines = Vec::new();
+    flatten(&tree, 0, &mut lines);
+    lines
+}
+
+#[test]
+fn headers() {
+    let headers = [
+        (
+            "le64.dylib",
+            ArchitectureWidth::SixtyFour,
+            CpuType::Arm64,
+            "arm64",
+        ),
+        (
+            "le32.dylib",
+            ArchitectureWidth::ThirtyTwo,
+            CpuType::X86,
+            "i386",
+        ),
+        (
+            "be64.dylib",
+            ArchitectureWidth::SixtyFour,
+            CpuType::PowerPc64,
+            "ppc64",
+        ),
+        (
+            "be32.dylib",
+            ArchitectureWidth::ThirtyTwo,
+            CpuType::PowerPc,
+            "ppc",
+        ),
+    ];
+    for (name, width, cpu_type, architecture) in headers.iter() {
+        let (_, descriptor) = open(name);
+        let header = &descriptor.header;
+        assert!(header.width == *width, "{}", name);
+        assert!(header.cpu_type == *cpu_type, "{}", name);
+        assert_eq!(header.architecture_name(), Some(*architecture));
+        assert!(header.file_type == FileType::Dylib, "{}", name);
+        assert_eq!(
+            header.command_count as usize,
+            descriptor.load_commands.len()
+        );
+    }
+
+    let (_, descriptor) = open("app/app");
+    assert!(descriptor.header.file_type == FileType::Execute);
+}
+
+#[test]
+fn load_commands() {
+    let (_, descriptor) = open("le64.dylib");
+    assert_eq!(
+        descriptor.install_name(),
+        Some("/usr/local/lib/libfoo.dylib")
+    );
+
+    let dependencies: Vec<_> = descriptor
+        .dependencies()
+        .map(|dylib| (dylib.name.as_str(), dylib.kind))
+        .collect();
+    assert_eq!(dependencies.len(), 2);
+    assert_eq!(dependencies[0].0, "/usr/lib/libSystem.B.dylib");
+    assert!(dependencies[0].1 == DylibKind::Load);
+    assert_eq!(dependencies[1].0, "@rpath/libweak.dylib");
+    assert!(dependencies[1].1 == DylibKind::Weak);
+    let dylib = descriptor.dependencies().next().unwrap();
+    assert_eq!(dylib.current_version.to_string(), "1.2.3");
+    assert_eq!(dylib.compatibility_version.to_string(), "1.0.0");
+
+    assert_eq!(
+        descriptor.rpaths().collect::<Vec<_>>(),
+        ["@loader_path/../lib"]
+    );
+
+    let segments: Vec<_> = descriptor
+        .segments()
+        .map(|segment| segment.name.as_str())
+        .collect();
+    assert_eq!(segments, ["__TEXT", "__LINKEDIT"]);
+    assert!(descriptor.segment("__TEXT").is_some());
+    let sections: Vec<_> = descriptor
+        .sections()
+        .map(|section| section.name.as_str())
+        .collect();
+    assert_eq!(sections, ["__text"]);
+
+    assert!(descriptor
+        .load_commands
+        .iter()
+        .any(|command| matches!(command, LoadCommand::Uuid(_))));
+}
+
+#[test]
+fn big_endian_load_commands() {
+    let (_, descriptor) = open("be32.dylib");
+    assert_eq!(
+        descriptor.install_name(),
+        Some("/usr/local/lib/libfoo.dylib")
+    );
+    assert_eq!(descriptor.dependencies().count(), 0);
+    assert!(descriptor.segment("__LINKEDIT").is_some());
+}
+
+#[test]
+fn exports_from_dyld_info() {
+    assert_eq!(exports("le64.dylib"), expected_exports());
+    assert_eq!(exports("le32.dylib"), expected_exports());
+}
+
+#[test]
+fn exports_from_exports_trie_command() {
+    assert_eq!(exports("be64.dylib"), expected_exports());
+    assert_eq!(exports("be32.dylib"), expected_exports());
+}
+
+#[test]
+fn rpath_dependency_tree() {
+    assert_eq!(
+        dependency_tree("app/app", None),
+        [
+            "app => app/app",
+            "  @rpath/libA.dylib => app/Frameworks/libA.dylib",
+            "    @loader_path/libB.dylib => app/Frameworks/libB.dylib",
+            "      @rpath/libA.dylib => app/Frameworks/libA.dylib (repeated)",
+            "      /usr/lib/libSystem.B.dylib => sysroot/usr/lib/libSystem.B.dylib (repeated)",
+            "  /usr/lib/libSystem.B.dylib => sysroot/usr/lib/libSystem.B.dylib",
+            "  @rpath/libmissing.dylib => not found (weak)",
+        ]
+    );
+}
+
+#[test]
+fn universal_slices() {
+    let mut file = File::open(fixtures().join("universal64.dylib")).unwrap();
+    let fat = FatBinary::parse(&mut file).unwrap();
+    let names: Vec<_> = fat
+        .architectures
+        .iter()
+        .map(|architecture| architecture.name())
+        .collect();
+    assert_eq!(names, [Some("x86_64"), Some("arm64")]);
+
+    let mut export_names = |architecture| {
+        let descriptor = MachODescriptor::parse_architecture(&mut file, architecture).unwrap();
+        let exports = descriptor.exports(&mut file).unwrap();
+        exports
+            .into_iter()
+            .map(|export| export.name)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(export_names(None), ["_x86only"]);
+    assert_eq!(export_names(Some("arm64")), ["_armonly", "_shared"]);
+    assert!(MachODescriptor::parse_architecture(&mut file, Some("arm64e")).is_err());
+}
+
+#[test]
+fn universal_dependency_tree_prefers_same_subtype() {
+    // Only the arm64e slice of the library needs another library
+    assert_eq!(
+        dependency_tree("app/universal", Some("arm64e")),
+        [
+            "universal => app/universal",
+            "  @rpath/libU.dylib => app/Frameworks/libU.dylib",
+            "    @rpath/libarm64e.dylib => not found (weak)",
+        ]
+    );
+    assert_eq!(
+        dependency_tree("app/universal", Some("arm64")),
+        [
+            "universal => app/universal",
+            "  @rpath/libU.dylib => app/Frameworks/libU.dylib",
+        ]
+    );
+
+    // The library has no x86_64h slice, so its x86_64 slice is loaded
+    assert_eq!(
+        dependency_tree("app/universal", Some("x86_64h")),
+        [
+            "universal => app/universal",
+            "  @rpath/libU.dylib => app/Frameworks/libU.dylib",
+        ]
+    );
+}